@@ -1,5 +1,6 @@
 use std::fmt::{self, Display, Formatter};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::serializer::{
     Reader,
     ReaderError,
@@ -8,7 +9,7 @@ use crate::serializer::{
 };
 
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VersionedNonce {
     nonce: u64,
     previous_topoheight: Option<u64>,
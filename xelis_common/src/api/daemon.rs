@@ -5,6 +5,7 @@ use std::{
 };
 use indexmap::IndexSet;
 use serde::{Deserialize, Serialize, Serializer, Deserializer, de::Error};
+use schemars::JsonSchema;
 use crate::{
     account::{CiphertextCache, VersionedBalance, VersionedNonce},
     block::EXTRA_NONCE_SIZE,
@@ -15,7 +16,7 @@ use crate::{
 };
 use super::RPCTransaction;
 
-#[derive(Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum BlockType {
     Sync,
     Side,
@@ -38,7 +39,7 @@ pub fn deserialize_extra_nonce<'de, 'a, D: Deserializer<'de>>(deserializer: D) -
 }
 
 // Structure used to map the public key to a human readable address
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct RPCBlockResponse<'a> {
     pub hash: Cow<'a, Hash>,
     pub topoheight: Option<u64>,
@@ -56,6 +57,7 @@ pub struct RPCBlockResponse<'a> {
     pub nonce: u64,
     #[serde(serialize_with = "serialize_extra_nonce")]
     #[serde(deserialize_with = "deserialize_extra_nonce")]
+    #[schemars(with = "String")]
     pub extra_nonce: Cow<'a, [u8; EXTRA_NONCE_SIZE]>,
     pub miner: Cow<'a, Address>,
     pub txs_hashes: Cow<'a, IndexSet<Hash>>,
@@ -66,58 +68,78 @@ pub struct RPCBlockResponse<'a> {
 
 pub type BlockResponse = RPCBlockResponse<'static>;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetTopBlockParams {
     #[serde(default)]
     pub include_txs: bool
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBlockAtTopoHeightParams {
     pub topoheight: u64,
     #[serde(default)]
     pub include_txs: bool
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBlocksAtHeightParams {
     pub height: u64,
     #[serde(default)]
     pub include_txs: bool
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBlockByHashParams<'a> {
     pub hash: Cow<'a, Hash>,
     #[serde(default)]
     pub include_txs: bool
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBlockTemplateParams<'a> {
     pub address: Cow<'a, Address>
 }
 
-#[derive(Serialize, Deserialize, PartialEq)]
+#[derive(Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetBlockTemplateResult {
     pub template: String, // template is BlockMiner in hex format
     pub height: u64, // block height
     pub difficulty: Difficulty // difficulty required for valid block
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetBlockTemplateLongPollParams<'a> {
+    pub address: Cow<'a, Address>,
+    // Job id of the last template received
+    // If it is still the current one, wait until a new job is available
+    #[serde(default)]
+    pub last_job_id: Option<u64>,
+    // Maximum time to wait in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct GetBlockTemplateLongPollResult {
+    #[serde(flatten)]
+    pub template: GetBlockTemplateResult,
+    // Job id to send back in the next long poll request
+    pub job_id: u64
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SubmitBlockParams {
     pub block_template: String, // hex: represent the BlockHeader (Block)
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBalanceParams<'a> {
     pub address: Cow<'a, Address>,
     pub asset: Cow<'a, Hash>
 }
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HasBalanceParams<'a> {
     pub address: Cow<'a, Address>,
     pub asset: Cow<'a, Hash>,
@@ -125,55 +147,55 @@ pub struct HasBalanceParams<'a> {
     pub topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HasBalanceResult {
     pub exist: bool
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBalanceAtTopoHeightParams<'a> {
     pub address: Cow<'a, Address>,
     pub asset: Cow<'a, Hash>,
     pub topoheight: u64
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetNonceParams<'a> {
     pub address: Cow<'a, Address>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HasNonceParams<'a> {
     pub address: Cow<'a, Address>,
     #[serde(default)]
     pub topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetNonceAtTopoHeightParams<'a> {
     pub address: Cow<'a, Address>,
     pub topoheight: u64
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetNonceResult {
     pub topoheight: u64,
     #[serde(flatten)]
     pub version: VersionedNonce
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HasNonceResult {
     pub exist: bool
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBalanceResult {
     pub version: VersionedBalance,
     pub topoheight: u64
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetInfoResult {
     pub height: u64,
     pub topoheight: u64,
@@ -199,18 +221,67 @@ pub struct GetInfoResult {
     pub network: Network
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SubmitTransactionParams {
-    pub data: String // should be in hex format
+    pub data: String, // should be in hex format
+    // If set, wait until the transaction is executed in a block
+    // or until this timeout (in seconds) is reached
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_for_execution: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetTransactionParams<'a> {
     pub hash: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetTransactionStatusParams<'a> {
+    pub hash: Cow<'a, Hash>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    // Transaction is waiting in mempool to be included in a block
+    InMempool,
+    // Transaction has been executed in a block
+    Executed,
+    // Transaction was included in a block but is not executed anymore
+    // and not present in mempool
+    Orphaned
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct TransactionStatusResult<'a> {
+    pub hash: Cow<'a, Hash>,
+    pub status: TransactionStatus,
+    // Block in which the transaction was executed
+    pub executed_in_block: Option<Hash>,
+    // Topoheight of the block in which the transaction was executed
+    pub topoheight: Option<u64>,
+    // How many blocks were ordered after the executing block
+    pub confirmations: Option<u64>,
+    // Is the executing block below or at the stable topoheight
+    pub stable: bool,
+    // if its a mempool tx, we add the timestamp when it was added
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub first_seen: Option<TimestampSeconds>
+}
+
+// submit_transaction returns true, or the status of the TX
+// if the caller asked to wait for its execution
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SubmitTransactionResult<'a> {
+    Submitted(bool),
+    Status(TransactionStatusResult<'a>)
+}
+
 // Direction is used for cache to knows from which context it got added
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum Direction {
     // We don't update it because it's In, we won't send back
     In,
@@ -243,7 +314,7 @@ impl Direction {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetPeersResponse<'a> {
     // Peers that are connected and allows to be displayed
     pub peers: Vec<PeerEntry<'a>>,
@@ -253,7 +324,7 @@ pub struct GetPeersResponse<'a> {
     pub hidden_peers: usize
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct PeerEntry<'a> {
     pub id: u64,
     pub addr: Cow<'a, SocketAddr>,
@@ -270,7 +341,7 @@ pub struct PeerEntry<'a> {
     pub connected_on: TimestampSeconds
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct P2pStatusResult<'a> {
     pub peer_count: usize,
     pub max_peers: usize,
@@ -281,24 +352,24 @@ pub struct P2pStatusResult<'a> {
     pub peer_id: u64
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetTopoHeightRangeParams {
     pub start_topoheight: Option<u64>,
     pub end_topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetHeightRangeParams {
     pub start_height: Option<u64>,
     pub end_height: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetTransactionsParams {
     pub tx_hashes: Vec<Hash>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct TransactionResponse<'a> {
     // in which blocks it was included
     pub blocks: Option<HashSet<Hash>>,
@@ -318,7 +389,7 @@ fn default_xelis_asset() -> Hash {
     crate::config::XELIS_ASSET
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAccountHistoryParams {
     pub address: Address,
     #[serde(default = "default_xelis_asset")]
@@ -327,7 +398,7 @@ pub struct GetAccountHistoryParams {
     pub maximum_topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")] 
 pub enum AccountHistoryType {
     DevFee { reward: u64 },
@@ -337,7 +408,7 @@ pub enum AccountHistoryType {
     Incoming { from: Address },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct AccountHistoryEntry {
     pub topoheight: u64,
     pub hash: Hash,
@@ -346,17 +417,17 @@ pub struct AccountHistoryEntry {
     pub block_timestamp: TimestampMillis
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAccountAssetsParams<'a> {
     pub address: Cow<'a, Address>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAssetParams<'a> {
     pub asset: Cow<'a, Hash>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAssetsParams {
     pub skip: Option<usize>,
     pub maximum: Option<usize>,
@@ -364,7 +435,7 @@ pub struct GetAssetsParams {
     pub maximum_topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAccountsParams {
     pub skip: Option<usize>,
     pub maximum: Option<usize>,
@@ -372,26 +443,64 @@ pub struct GetAccountsParams {
     pub maximum_topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct IsAccountRegisteredParams<'a> {
     pub address: Cow<'a, Address>,
     // If it is registered in stable height (confirmed)
     pub in_stable_height: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAccountRegistrationParams<'a> {
     pub address: Cow<'a, Address>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct IsTxExecutedInBlockParams<'a> {
     pub tx_hash: Cow<'a, Hash>,
     pub block_hash: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct MinerStatsEntry {
+    pub miner: Address,
+    // Blocks mined in the requested range
+    pub blocks: u64,
+    // Side blocks mined in the requested range
+    pub side_blocks: u64,
+    // Rewards received (dev fee excluded)
+    pub rewards: u64
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetMiningStatsResult {
+    pub start_topoheight: u64,
+    pub end_topoheight: u64,
+    // Count of blocks ordered in the range
+    pub blocks_count: u64,
+    pub side_blocks_count: u64,
+    // side blocks / ordered blocks
+    pub side_block_rate: f64,
+    // Blocks at the same heights that were not ordered
+    pub orphaned_blocks_count: u64,
+    // orphaned blocks / (ordered blocks + orphaned blocks)
+    pub orphan_rate: f64,
+    // Average time between two ordered blocks in the range
+    pub average_block_time: TimestampMillis,
+    // Average difficulty of the ordered blocks in the range
+    pub average_difficulty: Difficulty,
+    // Network hashrate estimated from average difficulty and block time
+    pub estimated_hashrate: Difficulty,
+    // Total of rewards paid to miners (dev fee excluded)
+    pub total_miners_rewards: u64,
+    // Total of dev fees paid
+    pub total_dev_fees: u64,
+    // Blocks mined per miner, sorted by blocks count
+    pub miners: Vec<MinerStatsEntry>
+}
+
 // Struct to define dev fee threshold
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct DevFeeThreshold {
     // block height to start dev fee
     pub height: u64,
@@ -400,18 +509,48 @@ pub struct DevFeeThreshold {
 }
 
 // Struct to returns the size of the blockchain on disk
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SizeOnDiskResult {
     pub size_bytes: u64,
     pub size_formatted: String
 }
 
-#[derive(Serialize, Deserialize)]
+// Struct returned by the /health endpoint
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct HealthResult {
+    // Is the process alive and the storage reachable
+    pub healthy: bool,
+    // Can we read the storage
+    pub storage_reachable: bool,
+    // Our current topoheight
+    pub topoheight: u64
+}
+
+// Struct returned by the /ready endpoint
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ReadyResult {
+    // Are we ready to serve requests
+    pub ready: bool,
+    // Our current topoheight
+    pub topoheight: u64,
+    // Median topoheight of our peers
+    pub median_topoheight: u64,
+    // Maximum topoheight lag allowed behind the median topoheight
+    pub max_topoheight_lag: u64,
+    // Is the P2p server running
+    pub p2p_enabled: bool,
+    // Count of peers connected
+    pub peer_count: usize,
+    // Are we currently bootstrapping the chain (fast sync)
+    pub bootstrapping: bool
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetMempoolCacheParams<'a> {
     pub address: Cow<'a, Address>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetMempoolCacheResult {
     // lowest nonce used
     min: u64,
@@ -423,7 +562,25 @@ pub struct GetMempoolCacheResult {
     balances: HashMap<Hash, CiphertextCache>
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+impl GetMempoolCacheResult {
+    pub fn get_min_nonce(&self) -> u64 {
+        self.min
+    }
+
+    pub fn get_max_nonce(&self) -> u64 {
+        self.max
+    }
+
+    pub fn get_txs(&self) -> &Vec<Hash> {
+        &self.txs
+    }
+
+    pub fn get_balances(&self) -> &HashMap<Hash, CiphertextCache> {
+        &self.balances
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum NotifyEvent {
     // When a new block is accepted by chain
@@ -475,7 +632,7 @@ pub enum NotifyEvent {
 pub type NewBlockEvent = BlockResponse;
 
 // Value of NotifyEvent::BlockOrdered
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct BlockOrderedEvent<'a> {
     // block hash in which this event was triggered
     pub block_hash: Cow<'a, Hash>,
@@ -485,7 +642,7 @@ pub struct BlockOrderedEvent<'a> {
 }
 
 // Value of NotifyEvent::BlockOrphaned
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct BlockOrphanedEvent<'a> {
     pub block_hash: Cow<'a, Hash>,
     // Tpoheight of the block before being orphaned
@@ -493,7 +650,7 @@ pub struct BlockOrphanedEvent<'a> {
 }
 
 // Value of NotifyEvent::StableHeightChanged
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct StableHeightChangedEvent {
     pub previous_stable_height: u64,
     pub new_stable_height: u64
@@ -505,7 +662,7 @@ pub type TransactionAddedInMempoolEvent = TransactionResponse<'static>;
 pub type TransactionOrphanedEvent = TransactionResponse<'static>;
 
 // Value of NotifyEvent::TransactionExecuted
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct TransactionExecutedEvent<'a> {
     pub block_hash: Cow<'a, Hash>,
     pub tx_hash: Cow<'a, Hash>,
@@ -519,7 +676,7 @@ pub type PeerConnectedEvent = PeerEntry<'static>;
 pub type PeerDisconnectedEvent = PeerEntry<'static>;
 
 // Value of NotifyEvent::PeerPeerListUpdated
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct PeerPeerListUpdatedEvent {
     // Peer ID of the peer that sent us the new peer list
     pub peer_id: u64,
@@ -531,7 +688,7 @@ pub struct PeerPeerListUpdatedEvent {
 pub type PeerStateUpdatedEvent = PeerEntry<'static>;
 
 // Value of NotifyEvent::PeerPeerDisconnected
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct PeerPeerDisconnectedEvent {
     // Peer ID of the peer that sent us this notification
     pub peer_id: u64,
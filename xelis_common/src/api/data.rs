@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use thiserror::Error;
 
 use crate::{
@@ -23,7 +24,7 @@ pub enum DataConversionError {
 }
 
 // All types availables
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy, JsonSchema)]
 pub enum ValueType {
     Bool,
     String,
@@ -35,7 +36,7 @@ pub enum ValueType {
     Hash
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone, JsonSchema)]
 pub enum ElementType {
     // Single value
     Value(ValueType),
@@ -93,7 +94,7 @@ impl Serializer for ValueType {
 }
 
 // This enum allows complex structures with multi depth if necessary
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
 #[serde(untagged)]
 pub enum DataElement {
     Value(DataValue),
@@ -258,7 +259,7 @@ impl Serializer for DataElement {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone, JsonSchema)]
 #[serde(untagged)]
 pub enum DataValue {
     Bool(bool),
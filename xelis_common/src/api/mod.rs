@@ -1,10 +1,12 @@
 mod data;
+mod schema;
 pub mod wallet;
 pub mod daemon;
 pub mod query;
 
 use std::borrow::Cow;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use serde_json::Value;
 use bulletproofs::RangeProof;
 use crate::{
@@ -17,6 +19,7 @@ use crate::{
     },
     transaction::{
         aead::AEADCipher,
+        payment_proof::PaymentProof,
         BurnPayload,
         Reference,
         SourceCommitment,
@@ -39,14 +42,14 @@ pub struct EventResult<'a, E: Clone> {
     pub value: Value
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct DataHash<'a, T: Clone> {
     pub hash: Cow<'a, Hash>,
     #[serde(flatten)]
     pub data: Cow<'a, T>
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RPCTransferPayload<'a> {
     pub asset: Cow<'a, Hash>,
     pub destination: Address,
@@ -71,7 +74,7 @@ impl<'a> From<RPCTransferPayload<'a>> for TransferPayload {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RPCTransactionType<'a> {
     Transfers(Vec<RPCTransferPayload<'a>>),
@@ -116,7 +119,7 @@ impl From<RPCTransactionType<'_>> for TransactionType {
 // We use this one for serde (de)serialization
 // So we have addresses displayed as strings and not Public Key as bytes
 // This is much more easier for developers relying on the API
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
 pub struct RPCTransaction<'a> {
     pub hash: Cow<'a, Hash>,
     /// Version of the transaction
@@ -133,6 +136,7 @@ pub struct RPCTransaction<'a> {
     /// We have one source commitment and equality proof per asset used in the tx.
     pub source_commitments: Cow<'a, Vec<SourceCommitment>>,
     /// The range proof is aggregated across all transfers and across all assets.
+    #[schemars(with = "Vec<u8>")]
     pub range_proof: Cow<'a, RangeProof>,
     /// Reference at which block the transaction was built
     pub reference: Cow<'a, Reference>,
@@ -174,4 +178,18 @@ impl<'a> From<RPCTransaction<'a>> for Transaction {
 
 // We create a type above it so for deserialize we can use this type directly
 // and not have to specify the lifetime
-pub type TransactionResponse = RPCTransaction<'static>;
\ No newline at end of file
+pub type TransactionResponse = RPCTransaction<'static>;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct VerifyPaymentProofParams<'a> {
+    pub proof: Cow<'a, PaymentProof>
+}
+
+// Transfer proven by a valid payment proof
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct VerifyPaymentProofResult<'a> {
+    pub tx_hash: Cow<'a, Hash>,
+    pub destination: Address,
+    pub asset: Cow<'a, Hash>,
+    pub amount: u64
+}
\ No newline at end of file
@@ -0,0 +1,121 @@
+// JSON Schema implementations for the types having a custom serde representation
+// These are used to generate the OpenRPC document of the daemon and wallet APIs
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation},
+    JsonSchema
+};
+use crate::{
+    account::CiphertextCache,
+    crypto::{
+        elgamal::{
+            CompressedCiphertext,
+            CompressedCommitment,
+            CompressedHandle,
+            CompressedPublicKey,
+            PedersenOpening
+        },
+        proofs::CiphertextValidityProof,
+        Address,
+        Hash,
+        Signature
+    },
+    transaction::{SourceCommitment, Transaction},
+    varuint::VarUint
+};
+
+// Build a schema with a description attached to it
+fn described(mut schema: SchemaObject, description: &str) -> Schema {
+    schema.metadata = Some(Box::new(Metadata {
+        description: Some(description.to_owned()),
+        ..Default::default()
+    }));
+    Schema::Object(schema)
+}
+
+// Build a string schema validated by the pattern given
+fn string_schema(pattern: &str, description: &str) -> Schema {
+    described(SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.to_owned()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }, description)
+}
+
+// Implement JsonSchema for a type serialized as a string
+macro_rules! impl_string_schema {
+    ($type: ty, $name: expr, $pattern: expr, $description: expr) => {
+        impl JsonSchema for $type {
+            fn schema_name() -> String {
+                $name.to_owned()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                string_schema($pattern, $description)
+            }
+        }
+    };
+}
+
+// Implement JsonSchema for a compressed Ristretto point
+// serialized as an array of 32 bytes
+macro_rules! impl_point_schema {
+    ($type: ty, $name: expr, $description: expr) => {
+        impl JsonSchema for $type {
+            fn schema_name() -> String {
+                $name.to_owned()
+            }
+
+            fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+                described(<[u8; 32]>::json_schema(gen).into_object(), $description)
+            }
+        }
+    };
+}
+
+// Implement JsonSchema for an object that is only meant to be
+// passed around by the clients without being inspected
+macro_rules! impl_opaque_schema {
+    ($type: ty, $name: expr, $description: expr) => {
+        impl JsonSchema for $type {
+            fn schema_name() -> String {
+                $name.to_owned()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                described(SchemaObject {
+                    instance_type: Some(InstanceType::Object.into()),
+                    ..Default::default()
+                }, $description)
+            }
+        }
+    };
+}
+
+impl_string_schema!(Hash, "Hash", "^[0-9a-fA-F]{64}$", "32 bytes hash in hexadecimal format");
+impl_string_schema!(Signature, "Signature", "^[0-9a-fA-F]{128}$", "Signature in hexadecimal format");
+impl_string_schema!(Address, "Address", "^xe[lt]:[02-9ac-hj-np-z]+$", "Bech32 encoded address, with integrated data if any");
+impl_string_schema!(PedersenOpening, "PedersenOpening", "^[0-9a-fA-F]{64}$", "Pedersen opening (scalar) in hexadecimal format");
+impl_string_schema!(VarUint, "VarUint", "^[0-9]+$", "Unsigned integer up to 256 bits in decimal format");
+
+impl_point_schema!(CompressedPublicKey, "CompressedPublicKey", "Compressed public key");
+impl_point_schema!(CompressedCommitment, "CompressedCommitment", "Compressed Pedersen commitment");
+impl_point_schema!(CompressedHandle, "CompressedHandle", "Compressed decrypt handle");
+
+impl_opaque_schema!(CiphertextValidityProof, "CiphertextValidityProof", "Proof that the ciphertext is valid");
+impl_opaque_schema!(SourceCommitment, "SourceCommitment", "Commitment of the final source balance with its equality proof");
+// Transaction is flattened in some responses, so it must stay an object
+impl_opaque_schema!(Transaction, "Transaction", "Transaction with its source as a public key");
+
+impl JsonSchema for CiphertextCache {
+    fn schema_name() -> String {
+        CompressedCiphertext::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        CompressedCiphertext::json_schema(gen)
+    }
+}
@@ -1,7 +1,9 @@
 use std::borrow::Cow;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::{
     crypto::{Address, Hash},
+    time::{TimestampMillis, TimestampSeconds},
     transaction::{
         builder::{FeeBuilder, TransactionTypeBuilder},
         Transaction
@@ -9,7 +11,7 @@ use crate::{
 };
 use super::{DataHash, DataElement, DataValue, query::Query};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct BuildTransactionParams {
     #[serde(flatten)]
     pub tx_type: TransactionTypeBuilder,
@@ -21,7 +23,52 @@ pub struct BuildTransactionParams {
     pub tx_as_hex: bool
 }
 
-#[derive(Serialize, Deserialize)]
+// Status of a recipient in a batch payout
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutStatus {
+    // Not processed yet
+    #[default]
+    Pending,
+    // Included in a transaction built but not yet accepted by the daemon
+    // Saved before broadcasting so the entry is never paid twice when resuming
+    Broadcasting {
+        hash: Hash,
+        nonce: u64
+    },
+    // Included in the transaction submitted
+    Submitted(Hash),
+    // No transaction was built or it can't be executed anymore
+    Failed(String)
+}
+
+impl PayoutStatus {
+    // Entries that can be sent when resuming a payout
+    pub fn can_be_sent(&self) -> bool {
+        matches!(self, Self::Pending | Self::Failed(_))
+    }
+}
+
+// Recipient of a batch payout
+// The same structure is used as input and as report,
+// so a report can be given again to resume the payout
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct PayoutEntry {
+    pub destination: Address,
+    pub asset: Hash,
+    pub amount: u64,
+    pub extra_data: Option<DataElement>,
+    #[serde(default)]
+    pub status: PayoutStatus
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct BatchPayoutParams {
+    pub entries: Vec<PayoutEntry>,
+    pub fee: Option<FeeBuilder>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct EstimateFeesParams {
     #[serde(flatten)]
     pub tx_type: TransactionTypeBuilder,
@@ -36,7 +83,7 @@ fn default_false_value() -> bool {
     false
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct ListTransactionsParams {
     pub min_topoheight: Option<u64>,
     pub max_topoheight: Option<u64>,
@@ -54,7 +101,75 @@ pub struct ListTransactionsParams {
     pub query: Option<Query>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ExportTransactionsParams {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub min_topoheight: Option<u64>,
+    pub max_topoheight: Option<u64>,
+    // Filter by block timestamp in milliseconds
+    // Wallet must be online to retrieve the block timestamps
+    pub min_timestamp: Option<TimestampMillis>,
+    pub max_timestamp: Option<TimestampMillis>,
+    #[serde(default = "default_true_value")]
+    pub accept_incoming: bool,
+    #[serde(default = "default_true_value")]
+    pub accept_outgoing: bool,
+    #[serde(default = "default_true_value")]
+    pub accept_coinbase: bool,
+    #[serde(default = "default_true_value")]
+    pub accept_burn: bool
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportEntryType {
+    Coinbase,
+    Burn,
+    Incoming,
+    Outgoing
+}
+
+impl ExportEntryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Coinbase => "coinbase",
+            Self::Burn => "burn",
+            Self::Incoming => "incoming",
+            Self::Outgoing => "outgoing"
+        }
+    }
+}
+
+// One exported row per transfer of a transaction entry
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct ExportEntry {
+    pub hash: Hash,
+    pub topoheight: u64,
+    // Timestamp of the block, None if wallet was offline during the export
+    pub timestamp: Option<TimestampMillis>,
+    #[serde(rename = "type")]
+    pub entry_type: ExportEntryType,
+    pub asset: Hash,
+    // Amount formatted with the asset decimals
+    // None if it couldn't be decrypted (watch-only wallet)
+    pub amount: Option<String>,
+    // Fee paid in XELIS, only set on the first transfer of an outgoing transaction
+    pub fee: Option<String>,
+    // Sender for incoming, destination for outgoing
+    pub address: Option<Address>,
+    pub extra_data: Option<DataElement>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct TransactionResponse<'a> {
     #[serde(flatten)]
     pub inner: DataHash<'a, Transaction>,
@@ -62,25 +177,25 @@ pub struct TransactionResponse<'a> {
     pub tx_as_hex: Option<String>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAssetPrecisionParams<'a> {
     pub asset: Cow<'a, Hash>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAddressParams {
     // Data to use for creating an integrated address
     // Returned address will contains all the data provided here
     pub integrated_data: Option<DataElement>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SplitAddressParams {
     // address which must be in integrated form
     pub address: Address
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SplitAddressResult {
     // Normal address
     pub address: Address,
@@ -88,64 +203,233 @@ pub struct SplitAddressResult {
     pub integrated_data: DataElement
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct RescanParams {
     pub until_topoheight: Option<u64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SetOnlineModeParams {
-    pub daemon_address: String
+    pub daemon_address: String,
+    // Daemons to switch to if the first one is unreachable, lagging or on another chain
+    #[serde(default)]
+    pub backup_daemon_addresses: Vec<String>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetPaymentProofParams {
+    pub hash: Hash,
+    // Index of the transfer in the transaction
+    pub transfer_index: u8
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SwitchAccountParams {
+    pub index: u32
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct AccountEntry {
+    // Index used to derive the account from the seed
+    pub index: u32,
+    pub address: Address,
+    // Is it the account currently used by the wallet
+    pub selected: bool
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    // No payment received yet
+    Pending,
+    // Payments received are below the amount requested
+    PartiallyPaid,
+    Paid,
+    // Payments received are above the amount requested
+    Overpaid,
+    // Expiry reached before being fully paid
+    Expired
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct InvoicePaymentEntry {
+    // Transaction which paid the invoice
+    pub hash: Hash,
+    pub topoheight: u64,
+    pub amount: u64
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct InvoiceEntry {
+    pub id: Hash,
+    // Integrated address to share with the payer
+    pub address: Address,
+    pub asset: Hash,
+    // Amount requested in atomic units
+    pub amount: u64,
+    pub description: Option<String>,
+    pub created_at: TimestampSeconds,
+    pub expiry: Option<TimestampSeconds>,
+    // Sum of all the payments received
+    pub amount_paid: u64,
+    pub status: InvoiceStatus,
+    pub payments: Vec<InvoicePaymentEntry>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CreateInvoiceParams {
+    // Amount requested in atomic units
+    pub amount: u64,
+    // XELIS is used if not set
+    pub asset: Option<Hash>,
+    pub description: Option<String>,
+    // Delay in seconds before the invoice expires, it never expires if not set
+    pub expires_in: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetInvoiceParams {
+    pub id: Hash
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ListInvoicesParams {
+    // Only returns the invoices with this status
+    pub status: Option<InvoiceStatus>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SendMessageParams {
+    pub destination: Address,
+    pub message: String,
+    // Hash of the transaction containing the message replied to
+    pub reply_to: Option<Hash>,
+    // Asset sent with the message, XELIS if not set
+    pub asset: Option<Hash>,
+    // Amount sent with the message, a message can be sent without any amount
+    #[serde(default)]
+    pub amount: u64,
+    pub fee: Option<FeeBuilder>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ListMessagesParams {
+    // Only returns the messages exchanged with this address
+    pub address: Option<Address>,
+    pub min_topoheight: Option<u64>,
+    pub max_topoheight: Option<u64>,
+    #[serde(default = "default_true_value")]
+    pub accept_incoming: bool,
+    #[serde(default = "default_true_value")]
+    pub accept_outgoing: bool
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct MessageEntry {
+    // Transaction containing the message
+    pub hash: Hash,
+    pub topoheight: u64,
+    // Sender for incoming, destination for outgoing
+    pub counterparty: Address,
+    pub outgoing: bool,
+    pub asset: Hash,
+    // Amount sent with the message, null if it couldn't be decrypted (watch-only wallet)
+    pub amount: Option<u64>,
+    pub message: String,
+    pub reply_to: Option<Hash>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct InboxEntry {
+    pub counterparty: Address,
+    // Messages received from the counterparty, oldest first
+    pub messages: Vec<MessageEntry>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBalanceParams {
     pub asset: Option<Hash>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetBalanceHistoryParams {
+    // Only returns the changes of this asset
+    pub asset: Option<Hash>,
+    pub min_topoheight: Option<u64>,
+    pub max_topoheight: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct BalanceHistoryEntry {
+    // Transaction or block (for coinbase) changing the balance
+    pub hash: Hash,
+    pub topoheight: u64,
+    pub asset: Hash,
+    pub received: u64,
+    // Fee paid is included for XELIS
+    pub sent: u64,
+    // Running balance of the asset after this entry
+    pub balance: u64
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct BalanceReconciliation {
+    pub asset: Hash,
+    // Balance computed from the full history of the wallet
+    pub computed: u64,
+    // Balance decrypted from the daemon, null in watch-only mode
+    pub balance: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetBalanceHistoryResult {
+    pub history: Vec<BalanceHistoryEntry>,
+    pub reconciliation: Vec<BalanceReconciliation>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetTransactionParams {
     pub hash: Hash
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
 pub struct BalanceChanged {
     pub asset: Hash,
     pub balance: u64
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetValueFromKeyParams {
     pub tree: String,
     pub key: DataValue
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HasKeyParams {
     pub tree: String,
     pub key: DataValue
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetMatchingKeysParams {
     pub tree: String,
     pub query: Option<Query>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct StoreParams {
     pub tree: String,
     pub key: DataValue,
     pub value: DataElement
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct DeleteParams {
     pub tree: String,
     pub key: DataValue
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct QueryDBParams {
     pub tree: String,
     pub key: Option<Query>,
@@ -154,7 +438,7 @@ pub struct QueryDBParams {
     pub return_on_first: bool
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum NotifyEvent {
     // When a new topoheight is detected by wallet
@@ -173,35 +457,38 @@ pub enum NotifyEvent {
     // When a rescan happened on the wallet
     // Contains a topoheight as value to indicate until which topoheight transactions got deleted
     Rescan,
+    // When an invoice received a payment
+    // Contains an InvoiceEntry as value
+    InvoiceUpdated,
     // When network state changed
     Online,
     // Same here
     Offline,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransferOut {
     // Destination address
     pub destination: Address,
     // Asset spent
     pub asset: Hash,
-    // Plaintext amount
-    pub amount: u64,
+    // Plaintext amount, null if it couldn't be decrypted (watch-only wallet)
+    pub amount: Option<u64>,
     // extra data
     pub extra_data: Option<DataElement>
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransferIn {
     // Asset spent
     pub asset: Hash,
-    // Plaintext amount
-    pub amount: u64,
+    // Plaintext amount, null if it couldn't be decrypted (watch-only wallet)
+    pub amount: Option<u64>,
     // extra data
     pub extra_data: Option<DataElement>
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EntryType {
     // Coinbase is only XELIS_ASSET
@@ -210,7 +497,9 @@ pub enum EntryType {
     },
     Burn {
         asset: Hash,
-        amount: u64
+        amount: u64,
+        // Fee paid
+        fee: u64
     },
     Incoming {
         from: Address,
@@ -227,7 +516,7 @@ pub enum EntryType {
 
 // This struct is used to represent a transaction entry like in wallet
 // But we replace every PublicKey to use Address instead
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionEntry {
     pub hash: Hash,
     pub topoheight: u64,
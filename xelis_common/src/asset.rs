@@ -4,7 +4,7 @@ use crate::{
     crypto::Hash
 };
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, schemars::JsonSchema)]
 pub struct AssetData {
     // At which topoheight this asset is registered
     topoheight: u64,
@@ -46,7 +46,7 @@ impl Serializer for AssetData {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, schemars::JsonSchema)]
 pub struct AssetWithData {
     asset: Hash,
     #[serde(flatten)]
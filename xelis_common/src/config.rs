@@ -30,4 +30,9 @@ pub const TESTNET_PREFIX_ADDRESS: &str = "xet";
 pub const MAX_TRANSACTION_SIZE: usize = 1024 * 1024; // 1 MB
 
 // BlockDAG rules
-pub const TIPS_LIMIT: usize = 3; // maximum 3 TIPS per block
\ No newline at end of file
+pub const TIPS_LIMIT: usize = 3; // maximum 3 TIPS per block
+
+// Genesis block hash for both networks
+// It must be the same as the hash of the genesis block
+pub const MAINNET_GENESIS_BLOCK_HASH: Hash = Hash::new([175, 118, 37, 203, 175, 200, 25, 148, 9, 202, 29, 120, 93, 128, 36, 209, 146, 193, 217, 36, 61, 51, 24, 194, 114, 113, 121, 208, 237, 163, 27, 55]);
+pub const TESTNET_GENESIS_BLOCK_HASH: Hash = Hash::new([183, 21, 203, 2, 41, 209, 63, 95, 84, 10, 228, 138, 223, 3, 188, 49, 176, 148, 176, 64, 176, 117, 106, 36, 84, 99, 27, 45, 221, 137, 156, 58]);
\ No newline at end of file
@@ -7,16 +7,22 @@ use crate::{
     api::{DataElement, ValueType, DataValue},
     serializer::{Serializer, Writer, Reader, ReaderError},
     config::{PREFIX_ADDRESS, TESTNET_PREFIX_ADDRESS},
+    time::TimestampSeconds,
     transaction::EXTRA_DATA_LIMIT_SIZE
 };
 use super::{
     bech32::{Bech32Error, encode, convert_bits, decode},
+    Hash,
     PublicKey
 };
 use core::fmt;
 use log::debug;
 use serde::de::Error as SerdeError;
 use anyhow::Error;
+use thiserror::Error as ThisError;
+
+// Scheme used by the payment request URIs
+pub const PAYMENT_REQUEST_SCHEME: &str = "xelis";
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AddressType {
@@ -242,11 +248,214 @@ impl Display for Address {
     }
 }
 
+#[derive(ThisError, Debug)]
+pub enum PaymentRequestError {
+    #[error("Invalid scheme, expected '{}:'", PAYMENT_REQUEST_SCHEME)]
+    InvalidScheme,
+    #[error("Invalid address: {}", _0)]
+    InvalidAddress(Error),
+    #[error("Invalid value for parameter '{}'", _0)]
+    InvalidParameter(String),
+    #[error("Parameter '{}' is present several times", _0)]
+    DuplicateParameter(String),
+    #[error("Memo can't be used with an integrated address")]
+    MemoWithIntegratedAddress,
+    #[error("Memo is too big, maximum is {} bytes", u8::MAX)]
+    MemoTooBig
+}
+
+// Payment request shared as a single URI string
+// Format: xelis:<address>?asset=<hash>&amount=<atomic units>&memo=<text>&expiry=<timestamp in seconds>
+// All parameters are optional, unknown parameters are ignored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentRequest {
+    // Address to pay, it can be an integrated address
+    pub address: Address,
+    // Asset requested, XELIS if not set
+    pub asset: Option<Hash>,
+    // Amount requested in atomic units
+    pub amount: Option<u64>,
+    // Memo to send as extra data with the transfer
+    pub memo: Option<String>,
+    // Timestamp in seconds after which the request should not be paid
+    pub expiry: Option<TimestampSeconds>
+}
+
+// Percent-encode all bytes except the unreserved characters of RFC 3986
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value.get(i + 1..i + 3)?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+impl PaymentRequest {
+    pub fn new(address: Address, asset: Option<Hash>, amount: Option<u64>, memo: Option<String>, expiry: Option<TimestampSeconds>) -> Result<Self, PaymentRequestError> {
+        let request = Self {
+            address,
+            asset,
+            amount,
+            memo,
+            expiry
+        };
+        request.verify_memo()?;
+        Ok(request)
+    }
+
+    // Memo is sent as extra data, so it can't be combined with the data of an integrated address
+    fn verify_memo(&self) -> Result<(), PaymentRequestError> {
+        if let Some(memo) = &self.memo {
+            if !self.address.is_normal() {
+                return Err(PaymentRequestError::MemoWithIntegratedAddress)
+            }
+
+            // String length is serialized on a single byte
+            if memo.len() > u8::MAX as usize {
+                return Err(PaymentRequestError::MemoTooBig)
+            }
+        }
+
+        // Limit applies to the extra data once serialized
+        if self.get_extra_data().is_some_and(|extra_data| extra_data.size() > EXTRA_DATA_LIMIT_SIZE) {
+            return Err(PaymentRequestError::MemoTooBig)
+        }
+        Ok(())
+    }
+
+    // Check if the request has expired at the given timestamp
+    pub fn is_expired(&self, now: TimestampSeconds) -> bool {
+        self.expiry.is_some_and(|expiry| now > expiry)
+    }
+
+    // Extra data to include in the transfer paying this request
+    pub fn get_extra_data(&self) -> Option<DataElement> {
+        self.memo.as_ref().map(|memo| DataElement::Value(DataValue::String(memo.clone())))
+    }
+
+    // Format the request as an URI
+    pub fn to_uri(&self) -> Result<String, Bech32Error> {
+        let mut uri = format!("{}:{}", PAYMENT_REQUEST_SCHEME, self.address.as_string()?);
+        let mut params = Vec::new();
+        if let Some(asset) = &self.asset {
+            params.push(format!("asset={}", asset));
+        }
+
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", amount));
+        }
+
+        if let Some(memo) = &self.memo {
+            params.push(format!("memo={}", percent_encode(memo)));
+        }
+
+        if let Some(expiry) = self.expiry {
+            params.push(format!("expiry={}", expiry));
+        }
+
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+
+        Ok(uri)
+    }
+
+    // Parse a request from an URI
+    pub fn from_uri(uri: &str) -> Result<Self, PaymentRequestError> {
+        let content = uri.strip_prefix(PAYMENT_REQUEST_SCHEME)
+            .and_then(|v| v.strip_prefix(':'))
+            .ok_or(PaymentRequestError::InvalidScheme)?;
+
+        let (address, query) = match content.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (content, None)
+        };
+        let address = Address::from_string(&address.to_owned()).map_err(PaymentRequestError::InvalidAddress)?;
+
+        let mut request = Self {
+            address,
+            asset: None,
+            amount: None,
+            memo: None,
+            expiry: None
+        };
+
+        for param in query.into_iter().flat_map(|query| query.split('&')).filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            let invalid = || PaymentRequestError::InvalidParameter(key.to_owned());
+            let duplicate = match key {
+                "asset" => request.asset.replace(Hash::from_hex(value.to_owned()).map_err(|_| invalid())?).is_some(),
+                "amount" => request.amount.replace(value.parse().map_err(|_| invalid())?).is_some(),
+                "memo" => request.memo.replace(percent_decode(value).ok_or_else(invalid)?).is_some(),
+                "expiry" => request.expiry.replace(value.parse().map_err(|_| invalid())?).is_some(),
+                _ => {
+                    debug!("Ignoring unknown parameter '{}' in payment request", key);
+                    false
+                }
+            };
+
+            if duplicate {
+                return Err(PaymentRequestError::DuplicateParameter(key.to_owned()))
+            }
+        }
+
+        request.verify_memo()?;
+        Ok(request)
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_uri(s)
+    }
+}
+
+impl Display for PaymentRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_uri().map_err(|_| fmt::Error)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::crypto::KeyPair;
+    use crate::{
+        crypto::{Hash, KeyPair},
+        serializer::Serializer,
+        transaction::EXTRA_DATA_LIMIT_SIZE
+    };
 
-    use super::{Address, AddressType};
+    use super::{Address, AddressType, PaymentRequest};
 
     #[test]
     fn test_serde() {
@@ -256,4 +465,24 @@ mod tests {
         let addr2: Address = Address::from_string(&v).unwrap();
         assert_eq!(addr, addr2);
     }
+
+    #[test]
+    fn test_payment_request_uri() {
+        let (pub_key, _) = KeyPair::new().split();
+        let addr = Address::new(true, AddressType::Normal, pub_key.compress());
+        let request = PaymentRequest::new(addr, Some(Hash::zero()), Some(150_000), Some("order #42 & co".to_owned()), Some(1700000000)).unwrap();
+        let uri = request.to_uri().unwrap();
+        assert!(uri.starts_with("xelis:xel:"));
+        assert_eq!(PaymentRequest::from_uri(&uri).unwrap(), request);
+
+        let uri = format!("xelis:{}?amount=1&amount=2", request.address);
+        assert!(PaymentRequest::from_uri(&uri).is_err());
+
+        // Memo is checked once serialized as extra data
+        let memo = "a".repeat(u8::MAX as usize);
+        let request = PaymentRequest::new(request.address.clone(), None, None, Some(memo), None).unwrap();
+        assert!(request.get_extra_data().unwrap().size() <= EXTRA_DATA_LIMIT_SIZE);
+        let memo = "a".repeat(u8::MAX as usize + 1);
+        assert!(PaymentRequest::new(request.address.clone(), None, None, Some(memo), None).is_err());
+    }
 }
\ No newline at end of file
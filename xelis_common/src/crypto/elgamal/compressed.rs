@@ -1,5 +1,6 @@
 use curve25519_dalek::{ristretto::CompressedRistretto, Scalar};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use thiserror::Error;
 use crate::{api::DataElement, crypto::{Address, AddressType}, serializer::{Reader, ReaderError, Serializer, Writer}};
 use super::{Ciphertext, DecryptHandle, PedersenCommitment, PublicKey};
@@ -37,7 +38,7 @@ pub struct CompressedCommitment(CompressedRistretto);
 pub struct CompressedHandle(CompressedRistretto);
 
 // A compressed ciphertext that can be serialized and deserialized with only 64 bytes
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CompressedCiphertext {
     commitment: CompressedCommitment,
     handle: CompressedHandle
@@ -4,6 +4,7 @@ use curve25519_dalek::{
     Scalar
 };
 use rand::rngs::OsRng;
+use sha3::{Digest, Sha3_512};
 use zeroize::Zeroize;
 use crate::{
     api::DataElement,
@@ -25,6 +26,11 @@ use super::{
     H
 };
 
+// Domain separator used to derive child private keys
+const CHILD_KEY_DOMAIN: &[u8] = b"XELIS_CHILD_KEY";
+// Domain separator used to derive private keys protected by a passphrase
+const PASSPHRASE_KEY_DOMAIN: &[u8] = b"XELIS_PASSPHRASE_KEY";
+
 #[derive(Clone)]
 pub struct PublicKey(RistrettoPoint);
 
@@ -109,6 +115,30 @@ impl PrivateKey {
         &self.0
     }
 
+    // Derive deterministically a child private key at the requested index
+    // Scalar is computed from Sha3-512(domain | private key | index)
+    pub fn derive_child(&self, index: u32) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(CHILD_KEY_DOMAIN);
+        hasher.update(self.0.as_bytes());
+        hasher.update(index.to_be_bytes());
+
+        let hash = hasher.finalize();
+        Self::from_scalar(Scalar::from_bytes_mod_order_wide(&hash.try_into().unwrap()))
+    }
+
+    // Derive deterministically a private key from this one and a passphrase hash
+    // Scalar is computed from Sha3-512(domain | private key | passphrase hash)
+    pub fn derive_with_passphrase(&self, passphrase_hash: &[u8]) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(PASSPHRASE_KEY_DOMAIN);
+        hasher.update(self.0.as_bytes());
+        hasher.update(passphrase_hash);
+
+        let hash = hasher.finalize();
+        Self::from_scalar(Scalar::from_bytes_mod_order_wide(&hash.try_into().unwrap()))
+    }
+
     // Decrypt a Ciphertext to a point
     pub fn decrypt_to_point(&self, ciphertext: &Ciphertext) -> RistrettoPoint {
         let commitment = ciphertext.commitment().as_point();
@@ -2,7 +2,9 @@ use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 use curve25519_dalek::{traits::MultiscalarMul, RistrettoPoint, Scalar};
 use rand::rngs::OsRng;
-use super::{key::PublicKey, CompressedCommitment, CompressedHandle, G, H};
+use serde::{de::Error, Deserialize, Serialize};
+use crate::serializer::{Reader, ReaderError, Serializer, Writer};
+use super::{key::PublicKey, CompressedCommitment, CompressedHandle, G, H, SCALAR_SIZE};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PedersenOpening(Scalar);
@@ -21,6 +23,39 @@ impl PedersenOpening {
     }
 }
 
+impl Serializer for PedersenOpening {
+    fn write(&self, writer: &mut Writer) {
+        self.0.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self(Scalar::read(reader)?))
+    }
+
+    fn size(&self) -> usize {
+        SCALAR_SIZE
+    }
+}
+
+impl Serialize for PedersenOpening {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for PedersenOpening {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from_hex(s).map_err(D::Error::custom)?)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PedersenCommitment(RistrettoPoint);
 
@@ -1,9 +1,10 @@
 use std::{fmt::{Display, Formatter, self}, str::FromStr};
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 
 use crate::serializer::{Serializer, Reader, ReaderError, Writer};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum Network {
     Mainnet,
@@ -1,12 +1,14 @@
 pub mod websocket;
 mod error;
 mod rpc_handler;
+mod openrpc;
 
 use std::borrow::Cow;
 
 pub use error::{RpcResponseError, InternalRpcError};
 pub use rpc_handler::{RPCHandler, Handler};
 pub use rpc_handler::parse_params;
+pub use openrpc::{OpenRpcDocument, RPC_DISCOVER_METHOD};
 
 use actix_web::{HttpResponse, web::{self, Data, Payload}, Responder, HttpRequest};
 use serde::{Deserialize, Serialize};
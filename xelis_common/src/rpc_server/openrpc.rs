@@ -0,0 +1,128 @@
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{InstanceType, Schema, SingleOrVec},
+    JsonSchema,
+    Map
+};
+use serde::Serialize;
+
+// Version of the OpenRPC specification followed
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+// Name of the method returning the OpenRPC document
+pub const RPC_DISCOVER_METHOD: &str = "rpc.discover";
+
+// Where the shared schemas are stored in the document
+const DEFINITIONS_PATH: &str = "#/components/schemas/";
+
+#[derive(Serialize, Clone)]
+pub struct Info {
+    pub title: String,
+    pub version: String
+}
+
+// Describe a param or the result of a method
+#[derive(Serialize, Clone)]
+pub struct ContentDescriptor {
+    pub name: String,
+    pub required: bool,
+    pub schema: Schema
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Method {
+    pub name: String,
+    // Params are always sent as an object
+    pub param_structure: &'static str,
+    pub params: Vec<ContentDescriptor>,
+    pub result: ContentDescriptor
+}
+
+#[derive(Serialize, Clone)]
+pub struct Components {
+    pub schemas: Map<String, Schema>
+}
+
+// OpenRPC document returned by the rpc.discover method
+#[derive(Serialize, Clone)]
+pub struct OpenRpcDocument {
+    pub openrpc: &'static str,
+    pub info: Info,
+    pub methods: Vec<Method>,
+    pub components: Components
+}
+
+impl OpenRpcDocument {
+    pub fn new(title: String, version: String) -> Self {
+        Self {
+            openrpc: OPENRPC_VERSION,
+            info: Info {
+                title,
+                version
+            },
+            methods: Vec::new(),
+            components: Components {
+                schemas: Map::new()
+            }
+        }
+    }
+
+    pub fn set_info(&mut self, title: String, version: String) {
+        self.info = Info {
+            title,
+            version
+        };
+    }
+
+    // Add a method described by its params and result types
+    // A fresh generator is used each time and its definitions are merged in the components
+    pub fn add_method<P: JsonSchema, R: JsonSchema>(&mut self, name: &str) {
+        let mut gen = SchemaSettings::draft07()
+            .with(|settings| settings.definitions_path = DEFINITIONS_PATH.into())
+            .into_generator();
+
+        let params = Self::params_descriptors::<P>(&mut gen);
+        let result = ContentDescriptor {
+            name: "result".into(),
+            required: true,
+            schema: gen.subschema_for::<R>()
+        };
+
+        self.components.schemas.extend(gen.take_definitions());
+        self.methods.retain(|method| method.name != name);
+        self.methods.push(Method {
+            name: name.into(),
+            param_structure: "by-name",
+            params,
+            result
+        });
+    }
+
+    // Split the params object into one descriptor per field
+    fn params_descriptors<P: JsonSchema>(gen: &mut SchemaGenerator) -> Vec<ContentDescriptor> {
+        let schema = P::json_schema(gen).into_object();
+        // No params expected
+        if schema.instance_type == Some(SingleOrVec::Single(Box::new(InstanceType::Null))) {
+            return Vec::new()
+        }
+
+        match schema.object {
+            Some(object) => {
+                let required = object.required;
+                object.properties.into_iter()
+                    .map(|(name, schema)| ContentDescriptor {
+                        required: required.contains(&name),
+                        name,
+                        schema
+                    })
+                    .collect()
+            },
+            None => vec![ContentDescriptor {
+                name: "params".into(),
+                required: true,
+                schema: Schema::Object(schema)
+            }]
+        }
+    }
+}
@@ -1,15 +1,24 @@
 use std::{collections::HashMap, pin::Pin, future::Future};
+use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde_json::{Value, json};
-use crate::context::Context;
+use crate::{context::Context, config::VERSION};
 
-use super::{InternalRpcError, RpcResponseError, RpcRequest, JSON_RPC_VERSION};
+use super::{
+    InternalRpcError,
+    RpcResponseError,
+    RpcRequest,
+    OpenRpcDocument,
+    JSON_RPC_VERSION,
+    RPC_DISCOVER_METHOD
+};
 use log::{error, trace};
 
 pub type Handler = fn(Context, Value) -> Pin<Box<dyn Future<Output = Result<Value, InternalRpcError>> + Send>>;
 
 pub struct RPCHandler<T: Send + Clone + 'static> {
     methods: HashMap<String, Handler>, // all RPC methods registered
+    openrpc: OpenRpcDocument, // OpenRPC document of the methods registered with a schema
     data: T
 }
 
@@ -20,6 +29,7 @@ where
     pub fn new(data: T) -> Self {
         Self {
             methods: HashMap::new(),
+            openrpc: OpenRpcDocument::new("XELIS".into(), VERSION.into()),
             data
         }
     }
@@ -42,10 +52,18 @@ where
     }
 
     pub fn has_method(&self, method_name: &String) -> bool {
-        self.methods.contains_key(method_name)
+        method_name == RPC_DISCOVER_METHOD || self.methods.contains_key(method_name)
     }
 
     pub async fn execute_method(&self, mut context: Context, mut request: RpcRequest) -> Result<Value, RpcResponseError> {
+        if request.method == RPC_DISCOVER_METHOD {
+            return Ok(json!({
+                "jsonrpc": JSON_RPC_VERSION,
+                "id": request.id,
+                "result": self.openrpc
+            }))
+        }
+
         let handler = match self.methods.get(&request.method) {
             Some(handler) => handler,
             None => return Err(RpcResponseError::new(request.id, InternalRpcError::MethodNotFound(request.method)))
@@ -69,6 +87,18 @@ where
         }
     }
 
+    // register a new RPC method handler and describe it in the OpenRPC document
+    // P is the params type expected and R the result type returned
+    pub fn register_method_with_schema<P: JsonSchema, R: JsonSchema>(&mut self, name: &str, handler: Handler) {
+        self.register_method(name, handler);
+        self.openrpc.add_method::<P, R>(name);
+    }
+
+    // set the title and version shown in the OpenRPC document
+    pub fn set_openrpc_info(&mut self, title: &str, version: &str) {
+        self.openrpc.set_info(title.into(), version.into());
+    }
+
     pub fn get_data(&self) -> &T {
         &self.data
     }
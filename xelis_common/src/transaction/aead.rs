@@ -39,7 +39,7 @@ const NONCE: &[u8; 12] = b"xelis-crypto";
 /// It is a simple wrapper around a vector of bytes.
 /// This doesn't contain the nonce, which is always the same.
 /// Cipher format isn't validated, it is assumed to be correct.
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, schemars::JsonSchema)]
 pub struct AEADCipher(pub Vec<u8>);
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Zeroize)]
@@ -5,6 +5,7 @@
 use bulletproofs::RangeProof;
 use curve25519_dalek::Scalar;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::{
     collections::HashSet,
     iter,
@@ -78,7 +79,7 @@ pub enum GenerationError<T> {
     Proof(#[from] ProofGenerationError),
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum FeeBuilder {
     // calculate tx fees based on its size and multiply by this value
@@ -130,7 +131,7 @@ pub trait AccountState: FeeHelper {
     fn update_nonce(&mut self, new_nonce: u64) -> Result<(), Self::Error>;
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TransactionTypeBuilder {
     Transfers(Vec<TransferBuilder>),
@@ -138,7 +139,7 @@ pub enum TransactionTypeBuilder {
     Burn(BurnPayload)
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct TransferBuilder {
     pub asset: Hash,
     pub amount: u64,
@@ -257,7 +258,7 @@ impl TransactionBuilder {
 
     /// Estimate by hand the bytes size of a final TX
     // Returns bytes size and transfers count
-    fn estimate_size(&self) -> usize {
+    pub fn estimate_size(&self) -> usize {
         let assets_used = self.data.used_assets().len();
         // Version byte
         let mut size = 1
@@ -404,10 +405,21 @@ impl TransactionBuilder {
     }
 
     pub fn build<B: AccountState>(
-        mut self,
+        self,
         state: &mut B,
         source_keypair: &KeyPair,
     ) -> Result<Transaction, GenerationError<B::Error>> {
+        self.build_with_openings(state, source_keypair)
+            .map(|(transaction, _)| transaction)
+    }
+
+    // Build the transaction and returns the opening generated for each transfer
+    // Openings are required to create a payment proof of a transfer
+    pub fn build_with_openings<B: AccountState>(
+        mut self,
+        state: &mut B,
+        source_keypair: &KeyPair,
+    ) -> Result<(Transaction, Vec<PedersenOpening>), GenerationError<B::Error>> {
         // Compute the fees
         let fee = self.estimate_fees(state)?;
 
@@ -557,6 +569,10 @@ impl TransactionBuilder {
             })
             .collect::<Result<Vec<_>, GenerationError<B::Error>>>()?;
 
+        let openings = transfers.iter()
+            .map(|transfer| transfer.amount_opening.clone())
+            .collect();
+
         let transfers = if let TransactionTypeBuilder::Transfers(_) = &mut self.data {
             range_proof_values.reserve(transfers.len());
             range_proof_openings.reserve(transfers.len());
@@ -654,7 +670,7 @@ impl TransactionBuilder {
             range_proof,
         }.sign(source_keypair);
 
-        Ok(transaction)
+        Ok((transaction, openings))
     }
 }
 
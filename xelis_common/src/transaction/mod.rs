@@ -11,11 +11,13 @@ use crate::{
 use bulletproofs::RangeProof;
 use log::debug;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use self::aead::AEADCipher;
 
 pub mod builder;
 pub mod verify;
 pub mod aead;
+pub mod payment_proof;
 
 #[cfg(test)]
 mod tests;
@@ -24,7 +26,7 @@ mod tests;
 pub const EXTRA_DATA_LIMIT_SIZE: usize = 1024;
 pub const MAX_TRANSFER_COUNT: usize = 255;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct Reference {
     pub hash: Hash,
     pub topoheight: u64,
@@ -63,7 +65,7 @@ pub struct TransferPayload {
 }
 
 // Burn is a public payload allowing to use it as a proof of burn
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct BurnPayload {
     pub asset: Hash,
     pub amount: u64
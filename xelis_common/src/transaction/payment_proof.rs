@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use thiserror::Error;
+use crate::{
+    crypto::{
+        elgamal::{PedersenCommitment, PedersenOpening},
+        Hash,
+        Hashable
+    },
+    serializer::{Reader, ReaderError, Serializer, Writer}
+};
+use super::{Transaction, TransactionType, TransferPayload};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PaymentProofError {
+    #[error("Transaction hash doesn't match the proof")]
+    InvalidTransactionHash,
+    #[error("Transaction is not a transfer")]
+    NotTransfers,
+    #[error("Transfer {} was not found in transaction", _0)]
+    TransferNotFound(u8),
+    #[error("Invalid destination public key")]
+    InvalidDestination,
+    #[error("Amount doesn't match the transfer commitment")]
+    InvalidCommitment,
+    #[error("Opening doesn't match the receiver handle")]
+    InvalidReceiverHandle
+}
+
+// Proof that a transfer sent the amount given to its destination
+// The opening generated by the sender allows to recompute
+// the commitment and the receiver handle of the transfer
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct PaymentProof {
+    pub tx_hash: Hash,
+    // Index of the transfer in the transaction
+    pub transfer_index: u8,
+    pub amount: u64,
+    pub opening: PedersenOpening
+}
+
+impl PaymentProof {
+    pub fn new(tx_hash: Hash, transfer_index: u8, amount: u64, opening: PedersenOpening) -> Self {
+        Self {
+            tx_hash,
+            transfer_index,
+            amount,
+            opening
+        }
+    }
+
+    // Verify the proof against the transfer included in the transaction
+    // Returns the transfer paid if the proof is valid
+    pub fn verify<'a>(&self, tx: &'a Transaction) -> Result<&'a TransferPayload, PaymentProofError> {
+        if tx.hash() != self.tx_hash {
+            return Err(PaymentProofError::InvalidTransactionHash)
+        }
+
+        let TransactionType::Transfers(transfers) = tx.get_data() else {
+            return Err(PaymentProofError::NotTransfers)
+        };
+
+        let transfer = transfers.get(self.transfer_index as usize)
+            .ok_or(PaymentProofError::TransferNotFound(self.transfer_index))?;
+
+        let commitment = PedersenCommitment::new_with_opening(self.amount, &self.opening).compress();
+        if commitment != *transfer.get_commitment() {
+            return Err(PaymentProofError::InvalidCommitment)
+        }
+
+        let destination = transfer.get_destination()
+            .decompress()
+            .map_err(|_| PaymentProofError::InvalidDestination)?;
+        let receiver_handle = destination.decrypt_handle(&self.opening).compress();
+        if receiver_handle != *transfer.get_receiver_handle() {
+            return Err(PaymentProofError::InvalidReceiverHandle)
+        }
+
+        Ok(transfer)
+    }
+}
+
+impl Serializer for PaymentProof {
+    fn write(&self, writer: &mut Writer) {
+        self.tx_hash.write(writer);
+        self.transfer_index.write(writer);
+        self.amount.write(writer);
+        self.opening.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let tx_hash = Hash::read(reader)?;
+        let transfer_index = u8::read(reader)?;
+        let amount = u64::read(reader)?;
+        let opening = PedersenOpening::read(reader)?;
+
+        Ok(Self::new(tx_hash, transfer_index, amount, opening))
+    }
+
+    fn size(&self) -> usize {
+        self.tx_hash.size() + self.transfer_index.size() + self.amount.size() + self.opening.size()
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
         elgamal::{Ciphertext, PedersenOpening},
         Address,
         Hash,
+        Hashable,
         KeyPair,
         PublicKey
     },
@@ -28,6 +29,7 @@ use super::{
         TransactionTypeBuilder,
         TransferBuilder
     },
+    payment_proof::{PaymentProof, PaymentProofError},
     verify::BlockchainVerificationState,
     BurnPayload,
     Reference,
@@ -329,6 +331,57 @@ async fn test_max_transfers() {
     assert!(tx.verify(&mut state).await.is_ok());
 }
 
+#[test]
+fn test_payment_proof() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let build = |amounts: &[u64]| {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let transfers = amounts.iter().map(|amount| TransferBuilder {
+            amount: *amount,
+            destination: bob.address(),
+            asset: XELIS_ASSET,
+            extra_data: None,
+        }).collect();
+        let builder = TransactionBuilder::new(0, alice.keypair.get_public_key().compress(), TransactionTypeBuilder::Transfers(transfers), FeeBuilder::Multiplier(1f64));
+        builder.build_with_openings(&mut state, &alice.keypair).unwrap()
+    };
+
+    let (tx, openings) = build(&[50, 20]);
+    let hash = tx.hash();
+
+    // Valid proof
+    let transfer = PaymentProof::new(hash.clone(), 0, 50, openings[0].clone()).verify(&tx).unwrap();
+    assert_eq!(*transfer.get_destination(), bob.keypair.get_public_key().compress());
+
+    // Wrong amount
+    let result = PaymentProof::new(hash.clone(), 0, 51, openings[0].clone()).verify(&tx);
+    assert_eq!(result.unwrap_err(), PaymentProofError::InvalidCommitment);
+
+    // Wrong transfer index
+    let result = PaymentProof::new(hash.clone(), 1, 50, openings[0].clone()).verify(&tx);
+    assert_eq!(result.unwrap_err(), PaymentProofError::InvalidCommitment);
+    let result = PaymentProof::new(hash.clone(), 2, 50, openings[0].clone()).verify(&tx);
+    assert_eq!(result.unwrap_err(), PaymentProofError::TransferNotFound(2));
+
+    // Wrong transaction
+    let (other_tx, _) = build(&[50]);
+    let result = PaymentProof::new(hash, 0, 50, openings[0].clone()).verify(&other_tx);
+    assert_eq!(result.unwrap_err(), PaymentProofError::InvalidTransactionHash);
+    let result = PaymentProof::new(other_tx.hash(), 0, 50, openings[0].clone()).verify(&other_tx);
+    assert_eq!(result.unwrap_err(), PaymentProofError::InvalidCommitment);
+}
+
 #[async_trait]
 impl<'a> BlockchainVerificationState<'a, ()> for ChainState {
 
@@ -1,6 +1,7 @@
 use lazy_static::lazy_static;
 use xelis_common::{
     api::daemon::DevFeeThreshold,
+    config::{MAINNET_GENESIS_BLOCK_HASH, TESTNET_GENESIS_BLOCK_HASH},
     crypto::{
         Address,
         Hash,
@@ -22,6 +23,11 @@ pub const DEFAULT_RPC_BIND_ADDRESS: &str = "0.0.0.0:8080";
 // Default cache size for storage DB
 pub const DEFAULT_CACHE_SIZE: usize = 1024;
 
+// Readiness rules
+// maximum topoheight difference with the peers median topoheight
+// to consider the node as synced and ready to serve requests
+pub const DEFAULT_READY_MAX_TOPOHEIGHT_LAG: u64 = STABLE_LIMIT;
+
 // Block rules
 // Millis per second, it is used to prevent having random 1000 values anywhere
 pub const MILLIS_PER_SECOND: u64 = 1000;
@@ -159,11 +165,6 @@ lazy_static! {
     pub static ref DEV_PUBLIC_KEY: PublicKey = Address::from_string(&DEV_ADDRESS.to_owned()).unwrap().to_public_key();
 }
 
-// Genesis block hash for both networks
-// It must be the same as the hash of the genesis block
-const MAINNET_GENESIS_BLOCK_HASH: Hash = Hash::new([175, 118, 37, 203, 175, 200, 25, 148, 9, 202, 29, 120, 93, 128, 36, 209, 146, 193, 217, 36, 61, 51, 24, 194, 114, 113, 121, 208, 237, 163, 27, 55]);
-const TESTNET_GENESIS_BLOCK_HASH: Hash = Hash::new([183, 21, 203, 2, 41, 209, 63, 95, 84, 10, 228, 138, 223, 3, 188, 49, 176, 148, 176, 64, 176, 117, 106, 36, 84, 99, 27, 45, 221, 137, 156, 58]);
-
 // Genesis block hash based on network selected
 pub fn get_genesis_block_hash(network: &Network) -> &'static Hash {
     match network {
@@ -51,7 +51,7 @@ use crate::{
     config::{
         get_genesis_block_hash, get_hex_genesis_block, get_minimum_difficulty,
         BLOCK_TIME_MILLIS, CHAIN_SYNC_RESPONSE_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MIN_BLOCKS,
-        DEFAULT_CACHE_SIZE, DEFAULT_P2P_BIND_ADDRESS, DEFAULT_RPC_BIND_ADDRESS, DEV_FEES,
+        DEFAULT_CACHE_SIZE, DEFAULT_P2P_BIND_ADDRESS, DEFAULT_RPC_BIND_ADDRESS, DEFAULT_READY_MAX_TOPOHEIGHT_LAG, DEV_FEES,
         DEV_PUBLIC_KEY, EMISSION_SPEED_FACTOR, GENESIS_BLOCK_DIFFICULTY, MAX_BLOCK_SIZE,
         MILLIS_PER_SECOND, P2P_DEFAULT_MAX_PEERS, SIDE_BLOCK_REWARD_MAX_BLOCKS, PRUNE_SAFETY_LIMIT,
         SIDE_BLOCK_REWARD_PERCENT, SIDE_BLOCK_REWARD_MIN_PERCENT, STABLE_LIMIT, TIMESTAMP_IN_FUTURE_LIMIT
@@ -137,6 +137,10 @@ pub struct Config {
     /// Disable GetWork Server (WebSocket for miners).
     #[clap(long)]
     pub disable_getwork_server: bool,
+    /// Maximum topoheight lag behind the peers median topoheight
+    /// for the node to be reported as ready on the `/ready` endpoint.
+    #[clap(long, default_value_t = DEFAULT_READY_MAX_TOPOHEIGHT_LAG)]
+    pub ready_max_topoheight_lag: u64,
     /// Enable the simulator (skip PoW verification, generate a new block for every BLOCK_TIME).
     #[clap(long)]
     pub simulator: Option<Simulator>,
@@ -336,7 +340,7 @@ impl<S: Storage> Blockchain<S> {
         // create RPC Server
         {
             info!("RPC Server will listen on: {}", config.rpc_bind_address);
-            match DaemonRpcServer::new(config.rpc_bind_address, Arc::clone(&arc), config.disable_getwork_server).await {
+            match DaemonRpcServer::new(config.rpc_bind_address, Arc::clone(&arc), config.disable_getwork_server, config.ready_max_topoheight_lag).await {
                 Ok(server) => *arc.rpc.write().await = Some(server),
                 Err(e) => error!("Error while starting RPC server: {}", e)
             };
@@ -1272,6 +1276,9 @@ impl<S: Storage> Blockchain<S> {
 
             // broadcast to websocket this tx
             if let Some(rpc) = self.rpc.read().await.as_ref() {
+                // Notify long polling clients for a new block template
+                rpc.notify_new_block_template_rate_limited();
+
                 // Notify miners if getwork is enabled
                 if let Some(getwork) = rpc.getwork_server() {
                     if let Err(e) = getwork.notify_new_job_rate_limited().await {
@@ -2157,6 +2164,9 @@ impl<S: Storage> Blockchain<S> {
         if let Some(rpc) = rpc_server.as_ref() {
             // if we have a getwork server, and that its not from syncing, notify miners
             if broadcast {
+                // Notify long polling clients for a new block template
+                rpc.notify_new_block_template();
+
                 if let Some(getwork) = rpc.getwork_server() {
                     let getwork = getwork.clone();
                     tokio::spawn(async move {
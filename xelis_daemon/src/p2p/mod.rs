@@ -159,6 +159,8 @@ pub struct P2pServer<S: Storage> {
     sharable: bool,
     // Are we syncing the chain with another peer
     is_syncing: AtomicBool,
+    // Are we bootstrapping the chain (fast sync) with another peer
+    is_bootstrapping: AtomicBool,
 }
 
 impl<S: Storage> P2pServer<S> {
@@ -201,6 +203,7 @@ impl<S: Storage> P2pServer<S> {
             exclusive_nodes: HashSet::from_iter(exclusive_nodes.into_iter()),
             sharable,
             is_syncing: AtomicBool::new(false),
+            is_bootstrapping: AtomicBool::new(false),
         };
 
         let arc = Arc::new(server);
@@ -675,6 +678,16 @@ impl<S: Storage> P2pServer<S> {
         self.is_syncing.load(Ordering::Acquire)
     }
 
+    // Set the chain bootstrapping state
+    fn set_chain_bootstrapping(&self, bootstrapping: bool) {
+        self.is_bootstrapping.store(bootstrapping, Ordering::Release);
+    }
+
+    // Check if we are bootstrapping the chain using fast sync
+    pub fn is_bootstrapping_chain(&self) -> bool {
+        self.is_bootstrapping.load(Ordering::Acquire)
+    }
+
     // This a infinite task that is running every CHAIN_SYNC_DELAY seconds
     // Based on the user configuration, it will try to sync the chain with another node with longest chain if any
     async fn chain_sync_loop(self: Arc<Self>) {
@@ -727,7 +740,10 @@ impl<S: Storage> P2pServer<S> {
                 // check if we can maybe fast sync first
                 // otherwise, fallback on the normal chain sync
                 let err = if fast_sync {
-                    if let Err(e) = self.bootstrap_chain(&peer).await {
+                    self.set_chain_bootstrapping(true);
+                    let res = self.bootstrap_chain(&peer).await;
+                    self.set_chain_bootstrapping(false);
+                    if let Err(e) = res {
                         warn!("Error occured while fast syncing with {}: {}", peer, e);
                         true
                     } else {
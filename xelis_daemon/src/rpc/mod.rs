@@ -27,11 +27,16 @@ use actix_web::{
 };
 use actix_web_actors::ws::WsResponseBuilder;
 use serde_json::{Value, json};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, watch};
 use xelis_common::{
-    api::daemon::NotifyEvent,
+    api::daemon::{
+        HealthResult,
+        NotifyEvent,
+        ReadyResult
+    },
     config,
     crypto::Address,
+    time::get_current_time_in_millis,
     rpc_server::{
         websocket::{
             EventWebSocketHandler,
@@ -48,7 +53,10 @@ use xelis_common::{
 };
 use std::{
     collections::HashSet,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc
+    },
 };
 use log::{
     trace,
@@ -67,7 +75,17 @@ pub type SharedDaemonRpcServer<S> = Arc<DaemonRpcServer<S>>;
 pub struct DaemonRpcServer<S: Storage> {
     handle: Mutex<Option<ServerHandle>>,
     websocket: WebSocketServerShared<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>>,
-    getwork: Option<SharedGetWorkServer<S>>
+    getwork: Option<SharedGetWorkServer<S>>,
+    // maximum topoheight lag behind the peers median
+    // to be considered as ready by the /ready endpoint
+    ready_max_topoheight_lag: u64,
+    // current block template job id
+    // incremented each time tips or mempool changed
+    // used by long polling clients to be notified of a new job
+    block_template_job: watch::Sender<u64>,
+    // used only when a new TX is received in mempool
+    last_block_template_notify: AtomicU64,
+    block_template_notify_rate_limit_ms: u64
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -79,11 +97,13 @@ pub enum ApiError {
     #[error("P2p engine is not running")]
     NoP2p,
     #[error("WebSocket server is not started")]
-    NoWebSocketServer
+    NoWebSocketServer,
+    #[error("RPC server is not started")]
+    NoRpcServer
 }
 
 impl<S: Storage> DaemonRpcServer<S> {
-    pub async fn new(bind_address: String, blockchain: Arc<Blockchain<S>>, disable_getwork_server: bool) -> Result<SharedDaemonRpcServer<S>, BlockchainError> {
+    pub async fn new(bind_address: String, blockchain: Arc<Blockchain<S>>, disable_getwork_server: bool, ready_max_topoheight_lag: u64) -> Result<SharedDaemonRpcServer<S>, BlockchainError> {
         let getwork: Option<SharedGetWorkServer<S>> = if !disable_getwork_server {
             info!("Creating GetWork server...");
             Some(Arc::new(GetWorkServer::new(blockchain.clone())))
@@ -102,6 +122,10 @@ impl<S: Storage> DaemonRpcServer<S> {
             handle: Mutex::new(None),
             websocket: ws,
             getwork,
+            ready_max_topoheight_lag,
+            block_template_job: watch::Sender::new(0),
+            last_block_template_notify: AtomicU64::new(0),
+            block_template_notify_rate_limit_ms: 500 // maximum one time every 500ms
         });
 
         {
@@ -114,6 +138,9 @@ impl<S: Storage> DaemonRpcServer<S> {
                     // WebSocket support
                     .route("/json_rpc", web::get().to(websocket::<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>, DaemonRpcServer<S>>))
                     .route("/getwork/{address}/{worker}", web::get().to(getwork_endpoint::<S>))
+                    // Orchestration probes
+                    .route("/health", web::get().to(health_endpoint::<S>))
+                    .route("/ready", web::get().to(ready_endpoint::<S>))
                     .service(index)
             })
             .disable_signals()
@@ -164,6 +191,34 @@ impl<S: Storage> DaemonRpcServer<S> {
     pub fn getwork_server(&self) -> &Option<SharedGetWorkServer<S>> {
         &self.getwork
     }
+
+    // Notify all long polling clients that a new block template is available
+    pub fn notify_new_block_template(&self) {
+        self.block_template_job.send_modify(|job_id| *job_id = job_id.wrapping_add(1));
+    }
+
+    // Same as notify_new_block_template, but at most one time per rate limit
+    pub fn notify_new_block_template_rate_limited(&self) {
+        let now = get_current_time_in_millis();
+        let last_notify = self.last_block_template_notify.load(Ordering::SeqCst);
+        if now - last_notify < self.block_template_notify_rate_limit_ms {
+            debug!("Rate limit reached, no need to notify long polling clients");
+            return;
+        }
+        self.last_block_template_notify.store(now, Ordering::SeqCst);
+
+        self.notify_new_block_template();
+    }
+
+    // Subscribe to the block template job changes
+    pub fn subscribe_block_template(&self) -> watch::Receiver<u64> {
+        self.block_template_job.subscribe()
+    }
+
+    // Maximum topoheight lag allowed to be considered as ready
+    pub fn get_ready_max_topoheight_lag(&self) -> u64 {
+        self.ready_max_topoheight_lag
+    }
 }
 
 impl<S: Storage> WebSocketServerHandler<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>> for DaemonRpcServer<S> {
@@ -216,4 +271,66 @@ async fn getwork_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>, request:
         },
         None => Ok(HttpResponse::NotFound().reason("GetWork server is not enabled").finish()) // getwork server is not started
     }
-}
\ No newline at end of file
+}
+
+// Liveness probe: the process is alive and the storage can be read
+async fn health_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>) -> Result<HttpResponse, Error> {
+    let blockchain = server.get_rpc_handler().get_data();
+    let (storage_reachable, topoheight) = {
+        let storage = blockchain.get_storage().read().await;
+        match storage.get_top_topoheight() {
+            Ok(topoheight) => (true, topoheight),
+            Err(e) => {
+                warn!("Storage is not reachable for health check: {}", e);
+                (false, blockchain.get_topo_height())
+            }
+        }
+    };
+
+    let result = HealthResult {
+        healthy: storage_reachable,
+        storage_reachable,
+        topoheight
+    };
+
+    Ok(if storage_reachable {
+        HttpResponse::Ok().json(result)
+    } else {
+        HttpResponse::ServiceUnavailable().json(result)
+    })
+}
+
+// Readiness probe: the node is synced with its peers, connected to the network
+// and not bootstrapping the chain
+async fn ready_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>) -> Result<HttpResponse, Error> {
+    let blockchain = server.get_rpc_handler().get_data();
+    let topoheight = blockchain.get_topo_height();
+    let max_topoheight_lag = server.get_ready_max_topoheight_lag();
+
+    let (p2p_enabled, peer_count, median_topoheight, bootstrapping) = {
+        let p2p = blockchain.get_p2p().read().await;
+        match p2p.as_ref() {
+            Some(p2p) => (true, p2p.get_peer_count().await, p2p.get_median_topoheight_of_peers().await, p2p.is_bootstrapping_chain()),
+            None => (false, 0, topoheight, false)
+        }
+    };
+
+    let synced = topoheight.saturating_add(max_topoheight_lag) >= median_topoheight;
+    let ready = p2p_enabled && peer_count > 0 && !bootstrapping && synced;
+
+    let result = ReadyResult {
+        ready,
+        topoheight,
+        median_topoheight,
+        max_topoheight_lag,
+        p2p_enabled,
+        peer_count,
+        bootstrapping
+    };
+
+    Ok(if ready {
+        HttpResponse::Ok().json(result)
+    } else {
+        HttpResponse::ServiceUnavailable().json(result)
+    })
+}
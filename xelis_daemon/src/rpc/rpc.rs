@@ -2,7 +2,8 @@ use crate::{
     config::{
         BLOCK_TIME_MILLIS,
         DEV_FEES,
-        DEV_PUBLIC_KEY
+        DEV_PUBLIC_KEY,
+        MILLIS_PER_SECOND
     },
     core::{
         blockchain::{
@@ -22,6 +23,8 @@ use xelis_common::{
         daemon::{
             AccountHistoryEntry,
             AccountHistoryType,
+            BlockResponse,
+            DevFeeThreshold,
             RPCBlockResponse,
             BlockType,
             GetAccountAssetsParams,
@@ -36,35 +39,48 @@ use xelis_common::{
             GetBlockByHashParams,
             GetBlockTemplateParams,
             GetBlockTemplateResult,
+            GetBlockTemplateLongPollParams,
+            GetBlockTemplateLongPollResult,
             GetBlocksAtHeightParams,
             GetHeightRangeParams,
             GetInfoResult,
+            GetMiningStatsResult,
             GetNonceAtTopoHeightParams,
             GetNonceParams,
             GetNonceResult,
             GetTopBlockParams,
             GetTopoHeightRangeParams,
             GetTransactionParams,
+            GetTransactionStatusParams,
             GetTransactionsParams,
             HasBalanceParams,
             HasBalanceResult,
             HasNonceParams,
             HasNonceResult,
             IsTxExecutedInBlockParams,
+            MinerStatsEntry,
             P2pStatusResult,
             GetPeersResponse,
             PeerEntry,
             SizeOnDiskResult,
             SubmitBlockParams,
             SubmitTransactionParams,
+            SubmitTransactionResult,
             TransactionResponse,
+            TransactionStatus,
+            TransactionStatusResult,
             GetMempoolCacheParams,
+            GetMempoolCacheResult,
             IsAccountRegisteredParams,
             GetAccountRegistrationParams,
         },
         RPCTransaction,
-        RPCTransactionType as RPCTransactionType
+        RPCTransactionType as RPCTransactionType,
+        VerifyPaymentProofParams,
+        VerifyPaymentProofResult
     },
+    account::{VersionedBalance, VersionedNonce},
+    asset::{AssetData, AssetWithData},
     async_handler,
     block::{
         Block,
@@ -77,7 +93,7 @@ use xelis_common::{
         XELIS_ASSET
     },
     context::Context,
-    crypto::Hash,
+    crypto::{Address, Hash, Hashable, PublicKey},
     difficulty::{
         CumulativeDifficulty,
         Difficulty
@@ -97,7 +113,8 @@ use xelis_common::{
 use anyhow::Context as AnyContext;
 use human_bytes::human_bytes;
 use serde_json::{json, Value};
-use std::{sync::Arc, borrow::Cow};
+use std::{sync::Arc, borrow::Cow, collections::HashMap, time::Duration};
+use tokio::time::{self, sleep};
 use log::{info, debug, trace};
 
 // Get the block type using the block hash and the blockchain current state
@@ -260,6 +277,45 @@ pub async fn get_transaction_response_for_hash<S: Storage>(storage: &S, mempool:
     }
 }
 
+// Build the transaction status based on chain and mempool state
+// first check if it was executed in a block, then check in mempool
+pub async fn get_transaction_status_for_hash<'a, S: Storage>(blockchain: &Blockchain<S>, storage: &S, mempool: &Mempool, hash: &'a Hash) -> Result<TransactionStatusResult<'a>, InternalRpcError> {
+    if storage.is_tx_executed_in_a_block(hash).context("Error while checking if tx was executed")? {
+        let block_hash = storage.get_block_executor_for_tx(hash).context("Error while retrieving block executor for tx")?;
+        let topoheight = storage.get_topo_height_for_hash(&block_hash).await.context("Error while retrieving topoheight of executing block")?;
+        let confirmations = blockchain.get_topo_height().saturating_sub(topoheight);
+        let stable = topoheight <= blockchain.get_stable_topoheight();
+
+        return Ok(TransactionStatusResult {
+            hash: Cow::Borrowed(hash),
+            status: TransactionStatus::Executed,
+            executed_in_block: Some(block_hash),
+            topoheight: Some(topoheight),
+            confirmations: Some(confirmations),
+            stable,
+            first_seen: None
+        })
+    }
+
+    let (status, first_seen) = if let Ok(sorted_tx) = mempool.get_sorted_tx(hash) {
+        (TransactionStatus::InMempool, Some(sorted_tx.get_first_seen()))
+    } else if storage.has_transaction(hash).await.context("Error while checking if tx is stored")? {
+        (TransactionStatus::Orphaned, None)
+    } else {
+        return Err(InternalRpcError::AnyError(BlockchainError::TxNotFound(hash.clone()).into()))
+    };
+
+    Ok(TransactionStatusResult {
+        hash: Cow::Borrowed(hash),
+        status,
+        executed_in_block: None,
+        topoheight: None,
+        confirmations: None,
+        stable: false,
+        first_seen
+    })
+}
+
 // Get a Peer Entry based on peer data
 pub async fn get_peer_entry(peer: &Peer) -> PeerEntry {
     let top_block_hash = peer.get_top_block_hash().lock().await.clone();
@@ -285,47 +341,52 @@ pub async fn get_peer_entry(peer: &Peer) -> PeerEntry {
 // This function is used to register all the RPC methods
 pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>) {
     info!("Registering RPC methods...");
-    handler.register_method("get_version", async_handler!(version::<S>));
-    handler.register_method("get_height", async_handler!(get_height::<S>));
-    handler.register_method("get_topoheight", async_handler!(get_topoheight::<S>));
-    handler.register_method("get_stableheight", async_handler!(get_stableheight::<S>));
-    handler.register_method("get_block_template", async_handler!(get_block_template::<S>));
-    handler.register_method("get_block_at_topoheight", async_handler!(get_block_at_topoheight::<S>));
-    handler.register_method("get_blocks_at_height", async_handler!(get_blocks_at_height::<S>));
-    handler.register_method("get_block_by_hash", async_handler!(get_block_by_hash::<S>));
-    handler.register_method("get_top_block", async_handler!(get_top_block::<S>));
-    handler.register_method("submit_block", async_handler!(submit_block::<S>));
-    handler.register_method("get_balance", async_handler!(get_balance::<S>));
-    handler.register_method("has_balance", async_handler!(has_balance::<S>));
-    handler.register_method("get_balance_at_topoheight", async_handler!(get_balance_at_topoheight::<S>));
-    handler.register_method("get_info", async_handler!(get_info::<S>));
-    handler.register_method("get_nonce", async_handler!(get_nonce::<S>));
-    handler.register_method("has_nonce", async_handler!(has_nonce::<S>));
-    handler.register_method("get_nonce_at_topoheight", async_handler!(get_nonce_at_topoheight::<S>));
-    handler.register_method("get_asset", async_handler!(get_asset::<S>));
-    handler.register_method("get_assets", async_handler!(get_assets::<S>));
-    handler.register_method("count_assets", async_handler!(count_assets::<S>));
-    handler.register_method("count_accounts", async_handler!(count_accounts::<S>));
-    handler.register_method("count_transactions", async_handler!(count_transactions::<S>));
-    handler.register_method("submit_transaction", async_handler!(submit_transaction::<S>));
-    handler.register_method("get_transaction", async_handler!(get_transaction::<S>));
-    handler.register_method("p2p_status", async_handler!(p2p_status::<S>));
-    handler.register_method("get_peers", async_handler!(get_peers::<S>));
-    handler.register_method("get_mempool", async_handler!(get_mempool::<S>));
-    handler.register_method("get_tips", async_handler!(get_tips::<S>));
-    handler.register_method("get_dag_order", async_handler!(get_dag_order::<S>));
-    handler.register_method("get_blocks_range_by_topoheight", async_handler!(get_blocks_range_by_topoheight::<S>));
-    handler.register_method("get_blocks_range_by_height", async_handler!(get_blocks_range_by_height::<S>));
-    handler.register_method("get_transactions", async_handler!(get_transactions::<S>));
-    handler.register_method("get_account_history", async_handler!(get_account_history::<S>));
-    handler.register_method("get_account_assets", async_handler!(get_account_assets::<S>));
-    handler.register_method("get_accounts", async_handler!(get_accounts::<S>));
-    handler.register_method("is_account_registered", async_handler!(is_account_registered::<S>));
-    handler.register_method("get_account_registration_topoheight", async_handler!(get_account_registration_topoheight::<S>));
-    handler.register_method("is_tx_executed_in_block", async_handler!(is_tx_executed_in_block::<S>));
-    handler.register_method("get_dev_fee_thresholds", async_handler!(get_dev_fee_thresholds::<S>));
-    handler.register_method("get_size_on_disk", async_handler!(get_size_on_disk::<S>));
-    handler.register_method("get_mempool_cache", async_handler!(get_mempool_cache::<S>));
+    handler.set_openrpc_info("XELIS Daemon", VERSION);
+    handler.register_method_with_schema::<(), String>("get_version", async_handler!(version::<S>));
+    handler.register_method_with_schema::<(), u64>("get_height", async_handler!(get_height::<S>));
+    handler.register_method_with_schema::<(), u64>("get_topoheight", async_handler!(get_topoheight::<S>));
+    handler.register_method_with_schema::<(), u64>("get_stableheight", async_handler!(get_stableheight::<S>));
+    handler.register_method_with_schema::<GetBlockTemplateParams, GetBlockTemplateResult>("get_block_template", async_handler!(get_block_template::<S>));
+    handler.register_method_with_schema::<GetBlockTemplateLongPollParams, GetBlockTemplateLongPollResult>("get_block_template_long_poll", async_handler!(get_block_template_long_poll::<S>));
+    handler.register_method_with_schema::<GetBlockAtTopoHeightParams, BlockResponse>("get_block_at_topoheight", async_handler!(get_block_at_topoheight::<S>));
+    handler.register_method_with_schema::<GetBlocksAtHeightParams, Vec<BlockResponse>>("get_blocks_at_height", async_handler!(get_blocks_at_height::<S>));
+    handler.register_method_with_schema::<GetBlockByHashParams, BlockResponse>("get_block_by_hash", async_handler!(get_block_by_hash::<S>));
+    handler.register_method_with_schema::<GetTopBlockParams, BlockResponse>("get_top_block", async_handler!(get_top_block::<S>));
+    handler.register_method_with_schema::<SubmitBlockParams, bool>("submit_block", async_handler!(submit_block::<S>));
+    handler.register_method_with_schema::<GetBalanceParams, GetBalanceResult>("get_balance", async_handler!(get_balance::<S>));
+    handler.register_method_with_schema::<HasBalanceParams, HasBalanceResult>("has_balance", async_handler!(has_balance::<S>));
+    handler.register_method_with_schema::<GetBalanceAtTopoHeightParams, VersionedBalance>("get_balance_at_topoheight", async_handler!(get_balance_at_topoheight::<S>));
+    handler.register_method_with_schema::<(), GetInfoResult>("get_info", async_handler!(get_info::<S>));
+    handler.register_method_with_schema::<GetNonceParams, GetNonceResult>("get_nonce", async_handler!(get_nonce::<S>));
+    handler.register_method_with_schema::<HasNonceParams, HasNonceResult>("has_nonce", async_handler!(has_nonce::<S>));
+    handler.register_method_with_schema::<GetNonceAtTopoHeightParams, VersionedNonce>("get_nonce_at_topoheight", async_handler!(get_nonce_at_topoheight::<S>));
+    handler.register_method_with_schema::<GetAssetParams, AssetData>("get_asset", async_handler!(get_asset::<S>));
+    handler.register_method_with_schema::<GetAssetsParams, Vec<AssetWithData>>("get_assets", async_handler!(get_assets::<S>));
+    handler.register_method_with_schema::<(), u64>("count_assets", async_handler!(count_assets::<S>));
+    handler.register_method_with_schema::<(), u64>("count_accounts", async_handler!(count_accounts::<S>));
+    handler.register_method_with_schema::<(), u64>("count_transactions", async_handler!(count_transactions::<S>));
+    handler.register_method_with_schema::<SubmitTransactionParams, SubmitTransactionResult>("submit_transaction", async_handler!(submit_transaction::<S>));
+    handler.register_method_with_schema::<GetTransactionParams, TransactionResponse>("get_transaction", async_handler!(get_transaction::<S>));
+    handler.register_method_with_schema::<GetTransactionStatusParams, TransactionStatusResult>("get_transaction_status", async_handler!(get_transaction_status::<S>));
+    handler.register_method_with_schema::<(), P2pStatusResult>("p2p_status", async_handler!(p2p_status::<S>));
+    handler.register_method_with_schema::<(), GetPeersResponse>("get_peers", async_handler!(get_peers::<S>));
+    handler.register_method_with_schema::<(), Vec<TransactionResponse>>("get_mempool", async_handler!(get_mempool::<S>));
+    handler.register_method_with_schema::<(), Vec<Hash>>("get_tips", async_handler!(get_tips::<S>));
+    handler.register_method_with_schema::<GetTopoHeightRangeParams, Vec<Hash>>("get_dag_order", async_handler!(get_dag_order::<S>));
+    handler.register_method_with_schema::<GetTopoHeightRangeParams, Vec<BlockResponse>>("get_blocks_range_by_topoheight", async_handler!(get_blocks_range_by_topoheight::<S>));
+    handler.register_method_with_schema::<GetHeightRangeParams, Vec<BlockResponse>>("get_blocks_range_by_height", async_handler!(get_blocks_range_by_height::<S>));
+    handler.register_method_with_schema::<GetTransactionsParams, Vec<Option<TransactionResponse>>>("get_transactions", async_handler!(get_transactions::<S>));
+    handler.register_method_with_schema::<GetAccountHistoryParams, Vec<AccountHistoryEntry>>("get_account_history", async_handler!(get_account_history::<S>));
+    handler.register_method_with_schema::<GetAccountAssetsParams, Vec<Hash>>("get_account_assets", async_handler!(get_account_assets::<S>));
+    handler.register_method_with_schema::<GetAccountsParams, Vec<Address>>("get_accounts", async_handler!(get_accounts::<S>));
+    handler.register_method_with_schema::<IsAccountRegisteredParams, bool>("is_account_registered", async_handler!(is_account_registered::<S>));
+    handler.register_method_with_schema::<GetAccountRegistrationParams, u64>("get_account_registration_topoheight", async_handler!(get_account_registration_topoheight::<S>));
+    handler.register_method_with_schema::<IsTxExecutedInBlockParams, bool>("is_tx_executed_in_block", async_handler!(is_tx_executed_in_block::<S>));
+    handler.register_method_with_schema::<(), Vec<DevFeeThreshold>>("get_dev_fee_thresholds", async_handler!(get_dev_fee_thresholds::<S>));
+    handler.register_method_with_schema::<GetTopoHeightRangeParams, GetMiningStatsResult>("get_mining_stats", async_handler!(get_mining_stats::<S>));
+    handler.register_method_with_schema::<(), SizeOnDiskResult>("get_size_on_disk", async_handler!(get_size_on_disk::<S>));
+    handler.register_method_with_schema::<GetMempoolCacheParams, GetMempoolCacheResult>("get_mempool_cache", async_handler!(get_mempool_cache::<S>));
+    handler.register_method_with_schema::<VerifyPaymentProofParams, VerifyPaymentProofResult>("verify_payment_proof", async_handler!(verify_payment_proof::<S>));
 }
 
 async fn version<S: Storage>(_: Context, body: Value) -> Result<Value, InternalRpcError> {
@@ -382,22 +443,63 @@ async fn get_top_block<S: Storage>(context: Context, body: Value) -> Result<Valu
     get_block_response_for_hash(&blockchain, &storage, &hash, params.include_txs).await
 }
 
-async fn get_block_template<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
-    let params: GetBlockTemplateParams = parse_params(body)?;
-    if !params.address.is_normal() {
+// Verify the miner address and build a block template for it
+async fn build_block_template<S: Storage>(blockchain: &Blockchain<S>, address: Cow<'_, Address>) -> Result<GetBlockTemplateResult, InternalRpcError> {
+    if !address.is_normal() {
         return Err(InternalRpcError::AnyError(ApiError::ExpectedNormalAddress.into()))
     }
 
-    let blockchain: &Arc<Blockchain<S>> = context.get()?;
-    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+    if address.is_mainnet() != blockchain.get_network().is_mainnet() {
         return Err(InternalRpcError::AnyError(BlockchainError::InvalidNetwork.into()))
     }
 
     let storage = blockchain.get_storage().read().await;
-    let block = blockchain.get_block_template_for_storage(&storage, params.address.into_owned().to_public_key()).await.context("Error while retrieving block template")?;
+    let block = blockchain.get_block_template_for_storage(&storage, address.into_owned().to_public_key()).await.context("Error while retrieving block template")?;
     let (difficulty, _) = blockchain.get_difficulty_at_tips(&*storage, block.get_tips().iter()).await.context("Error while retrieving difficulty at tips")?;
     let height = block.height;
-    Ok(json!(GetBlockTemplateResult { template: block.to_hex(), height, difficulty }))
+    Ok(GetBlockTemplateResult { template: block.to_hex(), height, difficulty })
+}
+
+async fn get_block_template<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBlockTemplateParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let template = build_block_template(blockchain, params.address).await?;
+    Ok(json!(template))
+}
+
+const DEFAULT_LONG_POLL_TIMEOUT_SECS: u64 = 30;
+const MAX_LONG_POLL_TIMEOUT_SECS: u64 = 120;
+
+// Long polling version of get_block_template
+// If the last job id is still the current one, wait until tips or mempool change (or timeout)
+// and returns the new block template with its job id
+async fn get_block_template_long_poll<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBlockTemplateLongPollParams = parse_params(body)?;
+    let timeout = params.timeout.unwrap_or(DEFAULT_LONG_POLL_TIMEOUT_SECS);
+    if timeout > MAX_LONG_POLL_TIMEOUT_SECS {
+        return Err(InternalRpcError::InvalidRequest).context(format!("Long poll timeout cannot be greater than {} seconds", MAX_LONG_POLL_TIMEOUT_SECS))?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mut receiver = {
+        let rpc = blockchain.get_rpc().read().await;
+        let rpc = rpc.as_ref().ok_or_else(|| InternalRpcError::AnyError(ApiError::NoRpcServer.into()))?;
+        rpc.subscribe_block_template()
+    };
+
+    // Wait only if the client already has the current job
+    let current_job_id = *receiver.borrow_and_update();
+    if params.last_job_id == Some(current_job_id) {
+        match time::timeout(Duration::from_secs(timeout), receiver.changed()).await {
+            Ok(Ok(())) => trace!("New block template job available for long polling"),
+            Ok(Err(e)) => debug!("Block template job channel closed: {}", e),
+            Err(_) => trace!("Long polling timed out, sending current block template")
+        }
+    }
+
+    let job_id = *receiver.borrow();
+    let template = build_block_template(blockchain, params.address).await?;
+    Ok(json!(GetBlockTemplateLongPollResult { template, job_id }))
 }
 
 async fn submit_block<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
@@ -456,7 +558,12 @@ async fn get_info<S: Storage>(context: Context, body: Value) -> Result<Value, In
         let top_block_hash = storage.get_hash_at_topo_height(topoheight).await.context("Error while retrieving hash at topo height")?;
         let supply = storage.get_supply_at_topo_height(topoheight).await.context("Error while retrieving supply at topo height")?;
         let pruned_topoheight = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?;
-        let average_block_time = blockchain.get_average_block_time::<S>(&storage).await.context("Error while retrieving average block time")?;
+        // Block time is computed over the same blocks as the difficulty
+    let average_block_time = if blocks_count > 1 {
+        end_timestamp.saturating_sub(start_timestamp) / (blocks_count - 1)
+    } else {
+        BLOCK_TIME_MILLIS
+    };
         (top_block_hash, supply, pruned_topoheight, average_block_time)
     };
     let difficulty = blockchain.get_difficulty().await;
@@ -610,6 +717,9 @@ async fn count_transactions<S: Storage>(context: Context, body: Value) -> Result
     Ok(json!(count))
 }
 
+const MAX_WAIT_FOR_EXECUTION_SECS: u64 = 300;
+const WAIT_FOR_EXECUTION_INTERVAL_MS: u64 = 500;
+
 async fn submit_transaction<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: SubmitTransactionParams = parse_params(body)?;
     // x2 because of hex encoding
@@ -617,10 +727,53 @@ async fn submit_transaction<S: Storage>(context: Context, body: Value) -> Result
         return Err(InternalRpcError::InvalidRequest).context(format!("Transaction size cannot be greater than {}", human_bytes(MAX_TRANSACTION_SIZE as f64)))?
     }
 
+    let wait_for_execution = match params.wait_for_execution {
+        Some(timeout) if timeout > MAX_WAIT_FOR_EXECUTION_SECS => {
+            return Err(InternalRpcError::InvalidRequest).context(format!("Wait for execution timeout cannot be greater than {} seconds", MAX_WAIT_FOR_EXECUTION_SECS))?
+        },
+        v => v
+    };
+
     let transaction = Transaction::from_hex(params.data)?;
+    let hash = transaction.hash();
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
-    blockchain.add_tx_to_mempool(transaction, true).await.map_err(|e| InternalRpcError::AnyError(e.into()))?;
-    Ok(json!(true))
+    blockchain.add_tx_to_mempool_with_hash(transaction, hash.clone(), true).await.map_err(|e| InternalRpcError::AnyError(e.into()))?;
+
+    match wait_for_execution {
+        Some(timeout) => {
+            // Poll the chain state until the TX is executed or until timeout
+            let wait = async {
+                loop {
+                    sleep(Duration::from_millis(WAIT_FOR_EXECUTION_INTERVAL_MS)).await;
+                    let storage = blockchain.get_storage().read().await;
+                    if storage.is_tx_executed_in_a_block(&hash).context("Error while checking if tx was executed")? {
+                        return Ok::<_, InternalRpcError>(())
+                    }
+                }
+            };
+
+            match time::timeout(Duration::from_secs(timeout), wait).await {
+                Ok(res) => res?,
+                Err(_) => trace!("Timed out while waiting for execution of tx {}", hash)
+            };
+
+            let storage = blockchain.get_storage().read().await;
+            let mempool = blockchain.get_mempool().read().await;
+            let status = get_transaction_status_for_hash(blockchain, &*storage, &mempool, &hash).await?;
+            Ok(json!(status))
+        },
+        None => Ok(json!(true))
+    }
+}
+
+async fn get_transaction_status<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetTransactionStatusParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let mempool = blockchain.get_mempool().read().await;
+
+    let status = get_transaction_status_for_hash(blockchain, &*storage, &mempool, &params.hash).await?;
+    Ok(json!(status))
 }
 
 async fn get_transaction<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
@@ -1070,6 +1223,130 @@ async fn is_tx_executed_in_block<S: Storage>(context: Context, body: Value) -> R
     Ok(json!(storage.is_tx_executed_in_block(&params.tx_hash, &params.block_hash).context("Error while checking if tx was executed in block")?))
 }
 
+// Verify a payment proof against a transaction executed in a block
+async fn verify_payment_proof<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: VerifyPaymentProofParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let proof = params.proof.into_owned();
+    if !storage.is_tx_executed_in_a_block(&proof.tx_hash).context("Error while checking if tx was executed")? {
+        return Err(InternalRpcError::InvalidRequest).context(format!("Transaction {} was not executed in a block", proof.tx_hash))?
+    }
+
+    let tx = storage.get_transaction(&proof.tx_hash).await.context("Error while retrieving transaction")?;
+    let transfer = proof.verify(&tx).context("Invalid payment proof")?;
+    Ok(json!(VerifyPaymentProofResult {
+        tx_hash: Cow::Borrowed(&proof.tx_hash),
+        destination: transfer.get_destination().as_address(blockchain.get_network().is_mainnet()),
+        asset: Cow::Borrowed(transfer.get_asset()),
+        amount: proof.amount
+    }))
+}
+
+const MAX_MINING_STATS_TOPOHEIGHT_RANGE: u64 = 1000;
+// Compute mining and DAG statistics over a topoheight range
+// if no params found, use the last 1000 blocks
+async fn get_mining_stats<S: Storage>(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetTopoHeightRangeParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let current_topoheight = blockchain.get_topo_height();
+    let (start_topoheight, end_topoheight) = get_range(params.start_topoheight, params.end_topoheight, MAX_MINING_STATS_TOPOHEIGHT_RANGE, current_topoheight)?;
+
+    let storage = blockchain.get_storage().read().await;
+    let pruned_topoheight = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?.unwrap_or(0);
+    if start_topoheight < pruned_topoheight {
+        return Err(InternalRpcError::InvalidRequest).context(format!("Start topoheight cannot be lower than pruned topoheight {}", pruned_topoheight))?
+    }
+
+    let mainnet = blockchain.get_network().is_mainnet();
+    let mut miners: HashMap<PublicKey, MinerStatsEntry> = HashMap::new();
+    let mut blocks_count = 0;
+    let mut side_blocks_count = 0;
+    let mut total_miners_rewards = 0;
+    let mut total_dev_fees = 0;
+    let mut total_difficulty = Difficulty::zero();
+    let (mut min_height, mut max_height) = (u64::MAX, 0);
+    let (mut start_timestamp, mut end_timestamp) = (0, 0);
+
+    for topoheight in start_topoheight..=end_topoheight {
+        let (hash, header) = storage.get_block_header_at_topoheight(topoheight).await.context(format!("Error while retrieving block header at topo height {topoheight}"))?;
+        let is_side_block = blockchain.is_side_block(&storage, &hash).await.context("Error while checking if block is side")?;
+        let difficulty = storage.get_difficulty_for_block_hash(&hash).await.context("Error while retrieving difficulty")?;
+
+        let mut reward = storage.get_block_reward_at_topo_height(topoheight).context("Error while retrieving block reward")?;
+        let dev_fee_percentage = get_block_dev_fee(header.get_height());
+        if dev_fee_percentage != 0 {
+            let dev_fee = reward * dev_fee_percentage / 100;
+            total_dev_fees += dev_fee;
+            reward -= dev_fee;
+        }
+        total_miners_rewards += reward;
+
+        let entry = miners.entry(header.get_miner().clone()).or_insert_with(|| MinerStatsEntry {
+            miner: header.get_miner().as_address(mainnet),
+            blocks: 0,
+            side_blocks: 0,
+            rewards: 0
+        });
+        entry.blocks += 1;
+        entry.rewards += reward;
+        if is_side_block {
+            entry.side_blocks += 1;
+            side_blocks_count += 1;
+        }
+
+        if topoheight == start_topoheight {
+            start_timestamp = header.get_timestamp();
+        }
+        end_timestamp = header.get_timestamp();
+
+        min_height = min_height.min(header.get_height());
+        max_height = max_height.max(header.get_height());
+        total_difficulty += difficulty;
+        blocks_count += 1;
+    }
+
+    // Search all blocks at the same heights that were never ordered
+    let mut orphaned_blocks_count = 0;
+    for height in min_height..=max_height {
+        for hash in storage.get_blocks_at_height(height).await.context("Error while retrieving blocks at height")? {
+            if blockchain.is_block_orphaned_for_storage(&storage, &hash).await {
+                orphaned_blocks_count += 1;
+            }
+        }
+    }
+
+    let average_block_time = blockchain.get_average_block_time::<S>(&storage).await.context("Error while retrieving average block time")?;
+
+    let average_difficulty = total_difficulty / blocks_count;
+    // Difficulty is the expected number of hashes to find a block
+    // so the hashrate is the difficulty divided by the block time in seconds
+    let estimated_hashrate = if average_block_time > 0 {
+        average_difficulty * MILLIS_PER_SECOND / average_block_time
+    } else {
+        average_difficulty
+    };
+
+    let mut miners: Vec<MinerStatsEntry> = miners.into_values().collect();
+    miners.sort_by(|a, b| b.blocks.cmp(&a.blocks));
+
+    Ok(json!(GetMiningStatsResult {
+        start_topoheight,
+        end_topoheight,
+        blocks_count,
+        side_blocks_count,
+        side_block_rate: side_blocks_count as f64 / blocks_count as f64,
+        orphaned_blocks_count,
+        orphan_rate: orphaned_blocks_count as f64 / (blocks_count + orphaned_blocks_count) as f64,
+        average_block_time,
+        average_difficulty,
+        estimated_hashrate,
+        total_miners_rewards,
+        total_dev_fees,
+        miners
+    }))
+}
+
 // Get the configured dev fees
 async fn get_dev_fee_thresholds<S: Storage>(_: Context, body: Value) -> Result<Value, InternalRpcError> {
     if body != Value::Null {
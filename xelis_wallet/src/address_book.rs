@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use xelis_common::{
+    crypto::{Address, AddressType, Hash, PublicKey},
+    serializer::{Reader, ReaderError, Serializer, Writer}
+};
+use crate::wallet::WalletError;
+
+// Maximum size in bytes of a contact label
+pub const MAX_LABEL_SIZE: usize = 32;
+// Maximum size in bytes of the notes of a contact
+pub const MAX_NOTES_SIZE: usize = 255;
+
+// Contact saved in the address book of the wallet
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Contact {
+    // Unique label used to find the contact
+    label: String,
+    // Address of the contact, integrated data is kept
+    address: Address,
+    // Asset used by default when sending to this contact
+    asset: Option<Hash>,
+    notes: Option<String>
+}
+
+impl Contact {
+    pub fn new(label: String, address: Address, asset: Option<Hash>, notes: Option<String>) -> Result<Self, WalletError> {
+        if label.is_empty() || label.len() > MAX_LABEL_SIZE || label.contains(char::is_whitespace) {
+            return Err(WalletError::InvalidContactLabel)
+        }
+
+        // Empty notes are considered as no notes to be serialized correctly
+        let notes = notes.filter(|notes| !notes.is_empty());
+        if notes.as_ref().is_some_and(|notes| notes.len() > MAX_NOTES_SIZE) {
+            return Err(WalletError::ContactNotesTooLong)
+        }
+
+        Ok(Self {
+            label,
+            address,
+            asset,
+            notes
+        })
+    }
+
+    pub fn get_label(&self) -> &String {
+        &self.label
+    }
+
+    pub fn get_address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn get_public_key(&self) -> &PublicKey {
+        self.address.get_public_key()
+    }
+
+    pub fn get_asset(&self) -> Option<&Hash> {
+        self.asset.as_ref()
+    }
+
+    pub fn get_notes(&self) -> Option<&String> {
+        self.notes.as_ref()
+    }
+}
+
+impl Serializer for Contact {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.label);
+        writer.write_bool(self.address.is_mainnet());
+        self.address.get_public_key().write(writer);
+        self.address.get_type().write(writer);
+        self.asset.write(writer);
+        writer.write_optional_string(&self.notes);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let label = reader.read_string()?;
+        let mainnet = reader.read_bool()?;
+        let key = PublicKey::read(reader)?;
+        let addr_type = AddressType::read(reader)?;
+        let asset = Option::read(reader)?;
+        let notes = reader.read_optional_string()?;
+
+        Ok(Self {
+            label,
+            address: Address::new(mainnet, addr_type, key),
+            asset,
+            notes
+        })
+    }
+}
@@ -1,6 +1,7 @@
 mod rpc;
 mod rpc_server;
 mod xswd;
+mod policy;
 
 use serde::ser::Serialize;
 use xelis_common::{api::wallet::NotifyEvent, rpc_server::WebSocketServerHandler};
@@ -10,11 +11,14 @@ pub use self::{
     xswd::{
         XSWD,
         AppStateShared,
+        Permission,
         PermissionResult,
         PermissionRequest,
+        TrustedApplication,
         XSWDPermissionHandler,
         XSWDNodeMethodHandler
     },
+    policy::{ApplicationPolicy, PolicyError, Spending, SPEND_LIMIT_PERIOD},
     rpc::register_methods as register_rpc_methods
 };
 
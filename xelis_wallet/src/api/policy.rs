@@ -0,0 +1,223 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt
+};
+use thiserror::Error;
+use xelis_common::{
+    config::XELIS_ASSET,
+    crypto::{Hash, PublicKey},
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    time::TimestampSeconds
+};
+
+// Period used for the spend limits (24 hours)
+pub const SPEND_LIMIT_PERIOD: TimestampSeconds = 24 * 60 * 60;
+
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Spend limit of {} per day reached for asset {}", _1, _0)]
+    SpendLimitExceeded(Hash, u64),
+    #[error("Destination is not allowed by the application policy")]
+    DestinationNotAllowed,
+    #[error("Fee of {} is above the maximum of {} allowed", _0, _1)]
+    FeeTooHigh(u64, u64),
+    #[error("An explicit fee value is required by the application policy")]
+    FeeRequired,
+    #[error("Transaction must be broadcasted by the wallet to be counted in the spend limits")]
+    BroadcastRequired,
+    #[error("Access to tree '{}' is not allowed by the application policy", _0)]
+    TreeNotAllowed(String),
+    #[error("Application has only a read access to its trees")]
+    ReadOnly
+}
+
+// Amount sent by an application, kept to enforce its spend limits
+#[derive(Clone, Debug)]
+pub struct Spending {
+    pub timestamp: TimestampSeconds,
+    pub asset: Hash,
+    pub amount: u64
+}
+
+impl Serializer for Spending {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u64(&self.timestamp);
+        writer.write_hash(&self.asset);
+        writer.write_u64(&self.amount);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            timestamp: reader.read_u64()?,
+            asset: reader.read_hash()?,
+            amount: reader.read_u64()?
+        })
+    }
+}
+
+// Policy configured by the user for an application
+// It is enforced on each request, even for methods always accepted
+#[derive(Clone, Debug, Default)]
+pub struct ApplicationPolicy {
+    // Maximum amount per asset the application can spend in a period
+    pub spend_limits: HashMap<Hash, u64>,
+    // Destinations allowed for transfers, any destination if empty
+    pub allowed_destinations: HashSet<PublicKey>,
+    // Maximum fee per transaction
+    pub max_fee: Option<u64>,
+    // Trees of the application that can be read, all writes are denied when set
+    pub read_only_trees: Option<HashSet<String>>
+}
+
+impl ApplicationPolicy {
+    pub fn is_unrestricted(&self) -> bool {
+        self.spend_limits.is_empty()
+            && self.allowed_destinations.is_empty()
+            && self.max_fee.is_none()
+            && self.read_only_trees.is_none()
+    }
+
+    // Verify an outgoing transaction requested by the application
+    // Fee is None if it can't be known before building the transaction
+    // Amounts must include the fees paid in XELIS
+    pub fn verify_spending(&self, history: &[Spending], destinations: &[&PublicKey], amounts: &HashMap<Hash, u64>, fee: Option<u64>, now: TimestampSeconds) -> Result<(), PolicyError> {
+        if let Some(max_fee) = self.max_fee {
+            match fee {
+                Some(fee) if fee > max_fee => return Err(PolicyError::FeeTooHigh(fee, max_fee)),
+                None => return Err(PolicyError::FeeRequired),
+                _ => {}
+            }
+        }
+
+        // An unknown fee can't be counted in the XELIS spend limit
+        if fee.is_none() && self.spend_limits.contains_key(&XELIS_ASSET) {
+            return Err(PolicyError::FeeRequired)
+        }
+
+        if !self.allowed_destinations.is_empty() && destinations.iter().any(|key| !self.allowed_destinations.contains(key)) {
+            return Err(PolicyError::DestinationNotAllowed)
+        }
+
+        for (asset, limit) in &self.spend_limits {
+            let requested = amounts.get(asset).copied().unwrap_or(0);
+            if requested == 0 {
+                continue;
+            }
+
+            let spent: u64 = history.iter()
+                .filter(|spending| spending.asset == *asset && spending.timestamp + SPEND_LIMIT_PERIOD > now)
+                .map(|spending| spending.amount)
+                .sum();
+
+            if spent.saturating_add(requested) > *limit {
+                return Err(PolicyError::SpendLimitExceeded(asset.clone(), *limit))
+            }
+        }
+
+        Ok(())
+    }
+
+    // Verify that the application can read this tree
+    pub fn verify_tree_read(&self, tree: &str) -> Result<(), PolicyError> {
+        match &self.read_only_trees {
+            Some(trees) if !trees.contains(tree) => Err(PolicyError::TreeNotAllowed(tree.to_owned())),
+            _ => Ok(())
+        }
+    }
+
+    // Verify that the application can write in its trees
+    pub fn verify_tree_write(&self) -> Result<(), PolicyError> {
+        if self.read_only_trees.is_some() {
+            return Err(PolicyError::ReadOnly)
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ApplicationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_unrestricted() {
+            return write!(f, "unrestricted")
+        }
+
+        let mut parts = Vec::new();
+        if !self.spend_limits.is_empty() {
+            let limits: Vec<String> = self.spend_limits.iter().map(|(asset, limit)| format!("{} of {}", limit, asset)).collect();
+            parts.push(format!("daily limits: {}", limits.join(", ")));
+        }
+
+        if !self.allowed_destinations.is_empty() {
+            parts.push(format!("{} allowed destinations", self.allowed_destinations.len()));
+        }
+
+        if let Some(max_fee) = self.max_fee {
+            parts.push(format!("max fee: {}", max_fee));
+        }
+
+        if let Some(trees) = &self.read_only_trees {
+            let trees: Vec<&str> = trees.iter().map(String::as_str).collect();
+            parts.push(format!("read only trees: [{}]", trees.join(", ")));
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl Serializer for ApplicationPolicy {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.spend_limits.len() as u16);
+        for (asset, limit) in &self.spend_limits {
+            writer.write_hash(asset);
+            writer.write_u64(limit);
+        }
+
+        writer.write_u16(self.allowed_destinations.len() as u16);
+        for key in &self.allowed_destinations {
+            key.write(writer);
+        }
+
+        self.max_fee.write(writer);
+
+        writer.write_bool(self.read_only_trees.is_some());
+        if let Some(trees) = &self.read_only_trees {
+            writer.write_u16(trees.len() as u16);
+            for tree in trees {
+                writer.write_string(tree);
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let count = reader.read_u16()?;
+        let mut spend_limits = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            spend_limits.insert(reader.read_hash()?, reader.read_u64()?);
+        }
+
+        let count = reader.read_u16()?;
+        let mut allowed_destinations = HashSet::with_capacity(count as usize);
+        for _ in 0..count {
+            allowed_destinations.insert(PublicKey::read(reader)?);
+        }
+
+        let max_fee = Option::read(reader)?;
+
+        let read_only_trees = if reader.read_bool()? {
+            let count = reader.read_u16()?;
+            let mut trees = HashSet::with_capacity(count as usize);
+            for _ in 0..count {
+                trees.insert(reader.read_string()?);
+            }
+            Some(trees)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            spend_limits,
+            allowed_destinations,
+            max_fee,
+            read_only_trees
+        })
+    }
+}
@@ -3,32 +3,54 @@ use anyhow::Context as AnyContext;
 use xelis_common::{
     api::{
         wallet::{
+            AccountEntry,
+            BatchPayoutParams,
             BuildTransactionParams,
+            CreateInvoiceParams,
             DeleteParams,
             EstimateFeesParams,
+            ExportTransactionsParams,
             GetAddressParams,
             GetAssetPrecisionParams,
+            GetBalanceHistoryParams,
+            GetBalanceHistoryResult,
             GetBalanceParams,
+            GetInvoiceParams,
             GetMatchingKeysParams,
+            GetPaymentProofParams,
             GetTransactionParams,
             GetValueFromKeyParams,
             HasKeyParams,
+            InboxEntry,
+            InvoiceEntry,
+            ListInvoicesParams,
+            ListMessagesParams,
             ListTransactionsParams,
+            MessageEntry,
+            PayoutEntry,
             QueryDBParams,
             RescanParams,
+            SendMessageParams,
             SplitAddressParams,
             SplitAddressResult,
             StoreParams,
+            TransactionEntry,
             TransactionResponse,
             SetOnlineModeParams,
+            SwitchAccountParams,
         },
+        query::QueryResult,
+        VerifyPaymentProofParams,
+        VerifyPaymentProofResult,
         DataElement,
-        DataHash
+        DataHash,
+        DataValue
     },
     async_handler,
     config::{VERSION, XELIS_ASSET},
     context::Context,
-    crypto::Hashable,
+    crypto::{Address, Hash, Hashable, Signature},
+    network::Network,
     rpc_server::{
         parse_params,
         websocket::WebSocketSessionShared,
@@ -36,12 +58,17 @@ use xelis_common::{
         RPCHandler
     },
     serializer::Serializer,
-    transaction::builder::FeeBuilder
+    transaction::{builder::FeeBuilder, payment_proof::PaymentProof}
 };
 use serde_json::{Value, json};
-use crate::wallet::{
-    Wallet,
-    WalletError
+use crate::{
+    export,
+    message::Message,
+    payout::process_payouts,
+    wallet::{
+        Wallet,
+        WalletError
+    }
 };
 use super::xswd::XSWDWebSocketHandler;
 use log::info;
@@ -49,36 +76,53 @@ use log::info;
 // Register all RPC methods
 pub fn register_methods(handler: &mut RPCHandler<Arc<Wallet>>) {
     info!("Registering RPC methods...");
-    handler.register_method("get_version", async_handler!(get_version));
-    handler.register_method("get_network", async_handler!(get_network));
-    handler.register_method("get_nonce", async_handler!(get_nonce));
-    handler.register_method("get_topoheight", async_handler!(get_topoheight));
-    handler.register_method("get_address", async_handler!(get_address));
-    handler.register_method("split_address", async_handler!(split_address));
-    handler.register_method("rescan", async_handler!(rescan));
-    handler.register_method("get_balance", async_handler!(get_balance));
-    handler.register_method("has_balance", async_handler!(has_balance));
-    handler.register_method("get_tracked_assets", async_handler!(get_tracked_assets));
-    handler.register_method("get_asset_precision", async_handler!(get_asset_precision));
-    handler.register_method("get_transaction", async_handler!(get_transaction));
-    handler.register_method("build_transaction", async_handler!(build_transaction));
-    handler.register_method("list_transactions", async_handler!(list_transactions));
-    handler.register_method("is_online", async_handler!(is_online));
-    handler.register_method("set_online_mode", async_handler!(set_online_mode));
-    handler.register_method("set_offline_mode", async_handler!(set_offline_mode));
-    handler.register_method("sign_data", async_handler!(sign_data));
-    handler.register_method("estimate_fees", async_handler!(estimate_fees));
+    handler.set_openrpc_info("XELIS Wallet", VERSION);
+    handler.register_method_with_schema::<(), String>("get_version", async_handler!(get_version));
+    handler.register_method_with_schema::<(), Network>("get_network", async_handler!(get_network));
+    handler.register_method_with_schema::<(), u64>("get_nonce", async_handler!(get_nonce));
+    handler.register_method_with_schema::<(), u64>("get_topoheight", async_handler!(get_topoheight));
+    handler.register_method_with_schema::<GetAddressParams, Address>("get_address", async_handler!(get_address));
+    handler.register_method_with_schema::<SplitAddressParams, SplitAddressResult>("split_address", async_handler!(split_address));
+    handler.register_method_with_schema::<RescanParams, bool>("rescan", async_handler!(rescan));
+    handler.register_method_with_schema::<GetBalanceParams, u64>("get_balance", async_handler!(get_balance));
+    handler.register_method_with_schema::<GetBalanceParams, bool>("has_balance", async_handler!(has_balance));
+    handler.register_method_with_schema::<GetBalanceHistoryParams, GetBalanceHistoryResult>("get_balance_history", async_handler!(get_balance_history));
+    handler.register_method_with_schema::<(), Vec<Hash>>("get_tracked_assets", async_handler!(get_tracked_assets));
+    handler.register_method_with_schema::<GetAssetPrecisionParams, u8>("get_asset_precision", async_handler!(get_asset_precision));
+    handler.register_method_with_schema::<GetTransactionParams, TransactionEntry>("get_transaction", async_handler!(get_transaction));
+    handler.register_method_with_schema::<BuildTransactionParams, TransactionResponse>("build_transaction", async_handler!(build_transaction));
+    handler.register_method_with_schema::<ListTransactionsParams, Vec<TransactionEntry>>("list_transactions", async_handler!(list_transactions));
+    handler.register_method_with_schema::<ExportTransactionsParams, String>("export_transactions", async_handler!(export_transactions));
+    handler.register_method_with_schema::<(), bool>("is_online", async_handler!(is_online));
+    handler.register_method_with_schema::<(), bool>("is_watch_only", async_handler!(is_watch_only));
+    handler.register_method_with_schema::<SetOnlineModeParams, bool>("set_online_mode", async_handler!(set_online_mode));
+    handler.register_method_with_schema::<(), bool>("set_offline_mode", async_handler!(set_offline_mode));
+    handler.register_method_with_schema::<DataElement, Signature>("sign_data", async_handler!(sign_data));
+    handler.register_method_with_schema::<EstimateFeesParams, u64>("estimate_fees", async_handler!(estimate_fees));
+    handler.register_method_with_schema::<BatchPayoutParams, Vec<PayoutEntry>>("batch_payout", async_handler!(batch_payout));
+    handler.register_method_with_schema::<(), Vec<PayoutEntry>>("get_payout_report", async_handler!(get_payout_report));
+    handler.register_method_with_schema::<(), AccountEntry>("create_account", async_handler!(create_account));
+    handler.register_method_with_schema::<(), Vec<AccountEntry>>("list_accounts", async_handler!(list_accounts));
+    handler.register_method_with_schema::<SwitchAccountParams, bool>("switch_account", async_handler!(switch_account));
+    handler.register_method_with_schema::<GetPaymentProofParams, PaymentProof>("get_payment_proof", async_handler!(get_payment_proof));
+    handler.register_method_with_schema::<VerifyPaymentProofParams, VerifyPaymentProofResult>("verify_payment_proof", async_handler!(verify_payment_proof));
+    handler.register_method_with_schema::<CreateInvoiceParams, InvoiceEntry>("create_invoice", async_handler!(create_invoice));
+    handler.register_method_with_schema::<GetInvoiceParams, InvoiceEntry>("get_invoice", async_handler!(get_invoice));
+    handler.register_method_with_schema::<ListInvoicesParams, Vec<InvoiceEntry>>("list_invoices", async_handler!(list_invoices));
+    handler.register_method_with_schema::<SendMessageParams, TransactionResponse>("send_message", async_handler!(send_message));
+    handler.register_method_with_schema::<ListMessagesParams, Vec<MessageEntry>>("list_messages", async_handler!(list_messages));
+    handler.register_method_with_schema::<(), Vec<InboxEntry>>("get_inbox", async_handler!(get_inbox));
 
     // These functions allow to have an encrypted DB directly in the wallet storage
     // You can retrieve keys, values, have differents trees, and store values
     // It is restricted in XSWD context (each app access to their own trees), and open to everything in RPC
     // Keys and values can be anything
-    handler.register_method("get_matching_keys", async_handler!(get_matching_keys));
-    handler.register_method("get_value_from_key", async_handler!(get_value_from_key));
-    handler.register_method("store", async_handler!(store));
-    handler.register_method("delete", async_handler!(delete));
-    handler.register_method("has_key", async_handler!(has_key));
-    handler.register_method("query_db", async_handler!(query_db));
+    handler.register_method_with_schema::<GetMatchingKeysParams, Vec<DataValue>>("get_matching_keys", async_handler!(get_matching_keys));
+    handler.register_method_with_schema::<GetValueFromKeyParams, DataElement>("get_value_from_key", async_handler!(get_value_from_key));
+    handler.register_method_with_schema::<StoreParams, bool>("store", async_handler!(store));
+    handler.register_method_with_schema::<DeleteParams, bool>("delete", async_handler!(delete));
+    handler.register_method_with_schema::<HasKeyParams, bool>("has_key", async_handler!(has_key));
+    handler.register_method_with_schema::<QueryDBParams, QueryResult>("query_db", async_handler!(query_db));
 }
 
 // Retrieve the version of the wallet
@@ -166,6 +210,10 @@ async fn get_balance(context: Context, body: Value) -> Result<Value, InternalRpc
     let params: GetBalanceParams = parse_params(body)?;
     let asset = params.asset.unwrap_or(XELIS_ASSET);
     let wallet: &Arc<Wallet> = context.get()?;
+    if wallet.is_watch_only() {
+        return Err(InternalRpcError::CustomStr("Balance is encrypted, wallet is in watch-only mode"))
+    }
+
     let storage = wallet.get_storage().read().await;
 
     // If the asset is not found, it will returns 0
@@ -174,6 +222,17 @@ async fn get_balance(context: Context, body: Value) -> Result<Value, InternalRpc
     Ok(json!(balance))
 }
 
+// Running balance per asset after each transaction
+// Computed balances are compared to the balances decrypted from the daemon
+async fn get_balance_history(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBalanceHistoryParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let result = wallet.get_balance_history(params.asset.as_ref(), params.min_topoheight, params.max_topoheight).await
+        .context("Error while computing balance history")?;
+
+    Ok(json!(result))
+}
+
 // Check if the wallet has a balance for a specific asset
 async fn has_balance(context: Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBalanceParams = parse_params(body)?;
@@ -264,6 +323,195 @@ async fn estimate_fees(context: Context, body: Value) -> Result<Value, InternalR
     Ok(json!(fees))
 }
 
+// Pay all the entries using as few transactions as possible
+// Returns the report with the status of each entry
+// Report is also saved in the wallet storage, see get_payout_report
+async fn batch_payout(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: BatchPayoutParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let mut entries = params.entries;
+    process_payouts(wallet, &mut entries, params.fee.unwrap_or_default(), |_| Ok(())).await
+        .context("Error while processing payouts")?;
+
+    Ok(json!(entries))
+}
+
+// Retrieve the report of the last batch payout saved in the wallet storage
+// It can be used to resume a payout if the response of batch_payout was lost
+async fn get_payout_report(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let storage = wallet.get_storage().read().await;
+    let entries = if storage.has_payout_report().context("Error while checking payout report")? {
+        storage.get_payout_report().context("Error while retrieving payout report")?.0
+    } else {
+        Vec::new()
+    };
+
+    Ok(json!(entries))
+}
+
+// Create a new account derived from the wallet seed
+async fn create_account(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let account = wallet.create_account().await.context("Error while creating account")?;
+
+    Ok(json!(AccountEntry {
+        index: account.get_index(),
+        address: account.get_public_key().to_address(wallet.get_network().is_mainnet()),
+        selected: false
+    }))
+}
+
+// List all accounts derived from the wallet seed
+async fn list_accounts(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let selected = wallet.get_account().get_index();
+    let mainnet = wallet.get_network().is_mainnet();
+    let accounts: Vec<AccountEntry> = wallet.get_accounts().await.context("Error while retrieving accounts")?
+        .into_iter()
+        .map(|account| AccountEntry {
+            index: account.get_index(),
+            address: account.get_public_key().to_address(mainnet),
+            selected: account.get_index() == selected
+        })
+        .collect();
+
+    Ok(json!(accounts))
+}
+
+// Switch the wallet to another account
+async fn switch_account(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SwitchAccountParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.switch_account(params.index).await.context("Error while switching account")?;
+
+    Ok(json!(true))
+}
+
+// Retrieve the payment proof of an outgoing transfer
+async fn get_payment_proof(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetPaymentProofParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let proof = wallet.get_payment_proof(&params.hash, params.transfer_index).await
+        .context("Error while retrieving payment proof")?;
+
+    Ok(json!(proof))
+}
+
+// Verify a payment proof against the transaction fetched from the daemon
+async fn verify_payment_proof(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: VerifyPaymentProofParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let (destination, asset, amount) = wallet.verify_payment_proof(&params.proof).await
+        .context("Error while verifying payment proof")?;
+
+    Ok(json!(VerifyPaymentProofResult {
+        tx_hash: Cow::Borrowed(&params.proof.tx_hash),
+        destination,
+        asset: Cow::Owned(asset),
+        amount
+    }))
+}
+
+// Create an invoice and returns it with its integrated address
+async fn create_invoice(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: CreateInvoiceParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let asset = params.asset.unwrap_or(XELIS_ASSET);
+    let invoice = wallet.create_invoice(asset, params.amount, params.description, params.expires_in).await
+        .context("Error while creating invoice")?;
+
+    Ok(json!(wallet.get_invoice_entry(&invoice)))
+}
+
+// Retrieve an invoice with its payments and its status
+async fn get_invoice(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetInvoiceParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let invoice = wallet.get_invoice(&params.id).await
+        .context("Error while retrieving invoice")?;
+
+    Ok(json!(wallet.get_invoice_entry(&invoice)))
+}
+
+// List all invoices of the selected account, filtered by status if requested
+async fn list_invoices(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ListInvoicesParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let invoices: Vec<InvoiceEntry> = wallet.get_invoices().await
+        .context("Error while retrieving invoices")?
+        .iter()
+        .map(|invoice| wallet.get_invoice_entry(invoice))
+        .filter(|entry| params.status.map_or(true, |status| entry.status == status))
+        .collect();
+
+    Ok(json!(invoices))
+}
+
+// Send an encrypted message with a transfer, amount can be zero
+async fn send_message(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SendMessageParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    if !wallet.is_online().await {
+        return Err(WalletError::NotOnlineMode).context("Cannot send message")?
+    }
+
+    let message = Message::new(params.message, params.reply_to).context("Invalid message")?;
+    let asset = params.asset.unwrap_or(XELIS_ASSET);
+    let tx = wallet.create_message_transaction(params.destination, &message, asset, params.amount, params.fee.unwrap_or_default()).await
+        .context("Error while creating transaction")?;
+    wallet.submit_transaction(&tx).await.context("Couldn't broadcast transaction")?;
+
+    Ok(json!(TransactionResponse {
+        tx_as_hex: None,
+        inner: DataHash {
+            hash: Cow::Owned(tx.hash()),
+            data: Cow::Owned(tx)
+        }
+    }))
+}
+
+// List the messages sent and received, oldest first
+async fn list_messages(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ListMessagesParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    if let Some(address) = &params.address {
+        if address.is_mainnet() != wallet.get_network().is_mainnet() {
+            return Err(WalletError::InvalidAddressParams).context("Invalid address")?
+        }
+    }
+
+    let counterparty = params.address.map(|address| address.to_public_key());
+    let messages = wallet.get_messages(counterparty.as_ref(), params.min_topoheight, params.max_topoheight, params.accept_incoming, params.accept_outgoing).await
+        .context("Error while retrieving messages")?;
+
+    Ok(json!(messages))
+}
+
+// Messages received grouped per sender, most recent conversation first
+async fn get_inbox(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let inbox = wallet.get_inbox().await.context("Error while retrieving inbox")?;
+
+    Ok(json!(inbox))
+}
+
 // List transactions from the wallet storage
 async fn list_transactions(context: Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: ListTransactionsParams = parse_params(body)?;
@@ -286,6 +534,16 @@ async fn list_transactions(context: Context, body: Value) -> Result<Value, Inter
     Ok(json!(txs))
 }
 
+// Export the filtered transactions in CSV or JSON format
+async fn export_transactions(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ExportTransactionsParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let content = export::export_transactions(wallet, &params).await
+        .context("Error while exporting transactions")?;
+
+    Ok(json!(content))
+}
+
 // Check if the wallet is currently connected to a daemon
 async fn is_online(context: Context, body: Value) -> Result<Value, InternalRpcError> {
     if body != Value::Null {
@@ -297,6 +555,16 @@ async fn is_online(context: Context, body: Value) -> Result<Value, InternalRpcEr
     Ok(json!(is_connected))
 }
 
+// Check if the wallet is only watching an address without its private key
+async fn is_watch_only(context: Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    Ok(json!(wallet.is_watch_only()))
+}
+
 // Connect the wallet to a daemon if not already connected
 async fn set_online_mode(context: Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: SetOnlineModeParams = parse_params(body)?;
@@ -306,7 +574,9 @@ async fn set_online_mode(context: Context, body: Value) -> Result<Value, Interna
         return Err(InternalRpcError::CustomStr("Wallet is already connected to a daemon"))
     }
 
-    wallet.set_online_mode(&params.daemon_address).await.context("Error while setting online mode")?;
+    let mut daemon_addresses = vec![params.daemon_address];
+    daemon_addresses.extend(params.backup_daemon_addresses);
+    wallet.set_online_mode_with_failover(daemon_addresses).await.context("Error while setting online mode")?;
 
     Ok(json!(true))
 }
@@ -332,7 +602,7 @@ async fn sign_data(context: Context, body: Value) -> Result<Value, InternalRpcEr
     let params: DataElement = parse_params(body)?;
 
     let wallet: &Arc<Wallet> = context.get()?;
-    let signature = wallet.sign_data(&params.to_bytes());
+    let signature = wallet.sign_data(&params.to_bytes()).context("Error while signing data")?;
     Ok(json!(signature))
 }
 
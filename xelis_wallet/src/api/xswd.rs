@@ -35,6 +35,7 @@ use serde_json::{
 };
 use tokio::sync::{
     Mutex,
+    MutexGuard,
     RwLock,
     Semaphore
 };
@@ -46,8 +47,8 @@ use xelis_common::{
     context::Context,
     crypto::{
         elgamal::PublicKey as DecompressedPublicKey,
-        Signature,
-        SIGNATURE_SIZE
+        Hash,
+        Signature
     },
     rpc_server::{
         websocket::{
@@ -66,10 +67,12 @@ use xelis_common::{
         ReaderError,
         Serializer,
         Writer
-    }
+    },
+    time::TimestampSeconds
 };
 use serde::{Deserialize, Serialize};
 use crate::config::XSWD_BIND_ADDRESS;
+use super::policy::{ApplicationPolicy, Spending, SPEND_LIMIT_PERIOD};
 use log::{
     debug,
     info,
@@ -106,7 +109,20 @@ pub trait XSWDPermissionHandler {
     // Handler function to cancel the request permission from app (app has disconnected)
     async fn cancel_request_permission(&self, app_state: &AppStateShared) -> Result<(), Error>;
     // Public key to use to verify the signature
-    async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error>;
+    async fn get_public_key(&self) -> Result<DecompressedPublicKey, Error>;
+    // Sign the manifest of an application approved by the user
+    async fn sign_application(&self, manifest: &[u8]) -> Result<Signature, Error>;
+    // Retrieve an application previously approved by the user
+    async fn get_trusted_application(&self, id: &String) -> Result<Option<TrustedApplication>, Error>;
+    // Persist an application approved by the user
+    async fn save_trusted_application(&self, application: &TrustedApplication) -> Result<(), Error>;
+    // Persist the permissions updated by the user for an application
+    async fn update_trusted_permissions(&self, app_state: &AppStateShared, permissions: &HashMap<String, Permission>) -> Result<(), Error>;
+    // Verify the request against the policy of the application
+    // Returns true if the request may spend funds counted in the spend limits
+    async fn verify_policy(&self, app_state: &AppStateShared, request: &RpcRequest) -> Result<bool, Error>;
+    // Count the amounts spent by the request in the spend limits of the application once it was executed
+    async fn record_spending(&self, app_state: &AppStateShared, request: &RpcRequest, response: &Value) -> Result<(), Error>;
 }
 
 #[async_trait]
@@ -211,6 +227,10 @@ impl ApplicationData {
     pub fn get_signature(&self) -> &Option<Signature> {
         &self.signature
     }
+
+    pub fn set_permissions(&mut self, permissions: HashMap<String, Permission>) {
+        self.permissions = permissions;
+    }
 }
 
 // This serializer is only used to sign/verify a signature!
@@ -243,7 +263,10 @@ impl Serializer for ApplicationData {
         writer.write_optional_string(&self.url);
         writer.write_u8(self.permissions.len() as u8);
 
-        for (method, permission) in &self.permissions {
+        // Sort the methods so the bytes signed are always the same
+        let mut permissions: Vec<_> = self.permissions.iter().collect();
+        permissions.sort_by(|a, b| a.0.cmp(b.0));
+        for (method, permission) in permissions {
             writer.write_string(method);
             writer.write_u8(permission.get_id());
         }
@@ -259,6 +282,90 @@ impl Serializer for ApplicationData {
     }
 }
 
+// Application approved by the user and persisted in the wallet
+// Permissions are kept up to date, signature is the one returned on approval
+pub struct TrustedApplication {
+    data: ApplicationData,
+    signature: Signature,
+    // Restrictions configured by the user for this application
+    policy: ApplicationPolicy,
+    // Amounts sent by the application in the current spend limit period
+    spendings: Vec<Spending>
+}
+
+impl TrustedApplication {
+    pub fn new(data: ApplicationData, signature: Signature) -> Self {
+        Self {
+            data,
+            signature,
+            policy: ApplicationPolicy::default(),
+            spendings: Vec::new()
+        }
+    }
+
+    pub fn get_data(&self) -> &ApplicationData {
+        &self.data
+    }
+
+    pub fn get_mut_data(&mut self) -> &mut ApplicationData {
+        &mut self.data
+    }
+
+    pub fn get_signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    pub fn get_policy(&self) -> &ApplicationPolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: ApplicationPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn get_spendings(&self) -> &Vec<Spending> {
+        &self.spendings
+    }
+
+    // Add the amounts sent and drop the ones outside of the spend limit period
+    pub fn add_spendings(&mut self, amounts: HashMap<Hash, u64>, now: TimestampSeconds) {
+        self.spendings.retain(|spending| spending.timestamp + SPEND_LIMIT_PERIOD > now);
+        for (asset, amount) in amounts {
+            self.spendings.push(Spending { timestamp: now, asset, amount });
+        }
+    }
+}
+
+impl Serializer for TrustedApplication {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let data = ApplicationData::read(reader)?;
+        let signature = Signature::read(reader)?;
+        let policy = ApplicationPolicy::read(reader)?;
+        let count = reader.read_u16()?;
+        let mut spendings = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            spendings.push(Spending::read(reader)?);
+        }
+
+        Ok(Self {
+            data,
+            signature,
+            policy,
+            spendings
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        self.data.write(writer);
+        self.signature.write(writer);
+        self.policy.write(writer);
+        writer.write_u16(self.spendings.len() as u16);
+        for spending in &self.spendings {
+            spending.write(writer);
+        }
+    }
+}
+
 const PERMISSION_DENIED_ERROR: InternalRpcError = InternalRpcError::CustomStr("Permission denied");
 
 impl<W> XSWD<W>
@@ -362,7 +469,10 @@ where
     // Applications listening for events
     listeners: Mutex<HashMap<WebSocketSessionShared<Self>, HashMap<NotifyEvent, Option<usize>>>>,
     // This is used to limit to one at a time a permission request
-    permission_handler_semaphore: Semaphore
+    permission_handler_semaphore: Semaphore,
+    // Held from the policy verification until the spending is recorded
+    // so concurrent requests can't go above the spend limits
+    spending_lock: Mutex<()>
 }
 
 impl<W> XSWDWebSocketHandler<W>
@@ -374,7 +484,8 @@ where
             handler,
             applications: RwLock::new(HashMap::new()),
             listeners: Mutex::new(HashMap::new()),
-            permission_handler_semaphore: Semaphore::new(1)
+            permission_handler_semaphore: Semaphore::new(1),
+            spending_lock: Mutex::new(())
         }
     }
 
@@ -416,7 +527,8 @@ where
         }
     }
 
-    async fn verify_permission_for_request(&self, app: &AppStateShared, request: &RpcRequest) -> Result<(), RpcResponseError> {
+    // Returns the spending lock if the request may spend funds, it must be kept until the spending is recorded
+    async fn verify_permission_for_request(&self, app: &AppStateShared, request: &RpcRequest) -> Result<Option<MutexGuard<'_, ()>>, RpcResponseError> {
         let _permit = self.permission_handler_semaphore.acquire().await
             .map_err(|_| RpcResponseError::new(request.id, InternalRpcError::CustomStr("Permission handler semaphore error")))?;
         let mut permissions = app.permissions.lock().await;
@@ -426,37 +538,58 @@ where
             return Err(RpcResponseError::new(request.id, InternalRpcError::CustomStr("Application not found")))
         }
 
+        // Policy is verified first so it also applies to methods always accepted
+        let wallet = self.handler.get_data();
+        let spending_lock = self.spending_lock.lock().await;
+        let spending = wallet.verify_policy(app, request).await
+            .map_err(|msg| RpcResponseError::new(request.id, InternalRpcError::Custom(msg.to_string())))?;
+        let spending_lock = spending.then_some(spending_lock);
+
         let permission = permissions.get(&request.method).map(|v| *v).unwrap_or(Permission::Ask);
-        match permission {
+        let allowed = match permission {
             // Request permission from user
             Permission::Ask => {
-                let result = self.handler.get_data()
-                .request_permission(app, PermissionRequest::Request(request)).await
-                .map_err(|msg| RpcResponseError::new(request.id, InternalRpcError::Custom(msg.to_string())))?;
+                let result = wallet.request_permission(app, PermissionRequest::Request(request)).await
+                    .map_err(|msg| RpcResponseError::new(request.id, InternalRpcError::Custom(msg.to_string())))?;
 
                 match result {
-                    PermissionResult::Allow => Ok(()),
-                    PermissionResult::Deny => Err(RpcResponseError::new(request.id, PERMISSION_DENIED_ERROR)),
+                    PermissionResult::Allow => true,
+                    PermissionResult::Deny => false,
                     PermissionResult::AlwaysAllow => {
                         permissions.insert(request.method.clone(), Permission::AcceptAlways);
-                        Ok(())
+                        self.save_permissions(app, &permissions).await;
+                        true
                     },
                     PermissionResult::AlwaysDeny => {
-                        permissions.insert(request.method.clone(), Permission::AcceptAlways);
-                        Err(RpcResponseError::new(request.id, PERMISSION_DENIED_ERROR))
-                    }   
+                        permissions.insert(request.method.clone(), Permission::DenyAlways);
+                        self.save_permissions(app, &permissions).await;
+                        false
+                    }
                 }
             }
             // User has already accepted this method
-            Permission::AcceptAlways => Ok(()),
+            Permission::AcceptAlways => true,
             // User has denied access to this method
-            Permission::DenyAlways => Err(RpcResponseError::new(request.id, PERMISSION_DENIED_ERROR))
+            Permission::DenyAlways => false
+        };
+
+        if !allowed {
+            return Err(RpcResponseError::new(request.id, PERMISSION_DENIED_ERROR))
+        }
+
+        Ok(spending_lock)
+    }
+
+    // Persist the permissions of the application so they are restored on its next connection
+    async fn save_permissions(&self, app: &AppStateShared, permissions: &HashMap<String, Permission>) {
+        if let Err(e) = self.handler.get_data().update_trusted_permissions(app, permissions).await {
+            error!("Error while saving permissions of application {}: {}", app.get_id(), e);
         }
     }
 
     async fn add_application(&self, session: &WebSocketSessionShared<Self>, message: &[u8]) -> Result<Value, RpcResponseError> {
         // Application is not registered, register it
-        let app_data: ApplicationData = serde_json::from_slice::<ApplicationData>(&message)
+        let mut app_data: ApplicationData = serde_json::from_slice::<ApplicationData>(&message)
             .map_err(|_| RpcResponseError::new(None, InternalRpcError::CustomStr("Invalid JSON format for application data")))?;
         // Sanity check
         {
@@ -497,31 +630,39 @@ where
                 return Err(RpcResponseError::new(None, InternalRpcError::CustomStr("Application permissions are not signed")))
             }
 
-            if app_data.signature.is_some() {
-                // TODO: verify the signature
-                return Err(RpcResponseError::new(None, InternalRpcError::CustomStr("Application signature not supported yet")))
-            }
-
             if app_data.permissions.len() > 255 {
                 return Err(RpcResponseError::new(None, InternalRpcError::CustomStr("Too many permissions")))
             }
         }
 
         let wallet = self.handler.get_data();
+        // Signature doesn't include itself
+        let manifest = app_data.to_bytes();
+        let signature = app_data.signature.clone();
         // Verify the signature of the app data to validate permissions previously set
-        if let Some(signature) = &app_data.signature {
-            let bytes = app_data.to_bytes();
-            // remove signature bytes for verification
-            let bytes = &bytes[0..bytes.len() - SIGNATURE_SIZE];
+        if let Some(signature) = &signature {
             let key = wallet.get_public_key().await
                 .map_err(|e| {
                     error!("error while retrieving public key: {}", e);
                     RpcResponseError::new(None, InternalRpcError::CustomStr("Error while retrieving public key"))
                 })?;
 
-            if signature.verify(bytes, key) {
+            if !signature.verify(&manifest, &key) {
                 return Err(RpcResponseError::new(None, InternalRpcError::CustomStr("Invalid signature for application data")));
             }
+
+            // A valid signature is not enough, the application must not have been revoked
+            let trusted = wallet.get_trusted_application(&app_data.id).await
+                .map_err(|e| {
+                    error!("error while retrieving trusted application: {}", e);
+                    RpcResponseError::new(None, InternalRpcError::CustomStr("Error while retrieving trusted application"))
+                })?;
+
+            match trusted {
+                // Restore the latest permissions set by the user
+                Some(trusted) if trusted.signature == *signature => app_data.permissions = trusted.data.permissions,
+                _ => return Err(RpcResponseError::new(None, InternalRpcError::CustomStr("Application is no longer trusted")))
+            }
         }
 
         // Verify that this app ID is not already in use:
@@ -557,16 +698,48 @@ where
             return Err(RpcResponseError::new(None, PERMISSION_DENIED_ERROR))
         }
 
+        // Sign the manifest so the application can reuse it on its next connection
+        let signature = match signature {
+            Some(signature) => signature,
+            None => match self.trust_application(&state, &manifest).await {
+                Ok(signature) => signature,
+                Err(e) => {
+                    error!("Error while saving trusted application {}: {}", state.get_id(), e);
+                    let mut applications = self.applications.write().await;
+                    applications.remove(session);
+                    return Err(RpcResponseError::new(None, InternalRpcError::CustomStr("Error while saving trusted application")))
+                }
+            }
+        };
+
         Ok(json!({
             "jsonrpc": "2.0",
             "id": Value::Null,
             "result": {
                 "message": "Application has been registered",
-                "success": true
+                "success": true,
+                "signature": signature
             }
         }))
     }
 
+    // Sign the manifest of an approved application and persist it
+    async fn trust_application(&self, state: &AppStateShared, manifest: &[u8]) -> Result<Signature, Error> {
+        let wallet = self.handler.get_data();
+        let signature = wallet.sign_application(manifest).await?;
+        let data = ApplicationData {
+            id: state.id.clone(),
+            name: state.name.clone(),
+            description: state.description.clone(),
+            url: state.url.clone(),
+            permissions: state.permissions.lock().await.clone(),
+            signature: None
+        };
+
+        wallet.save_trusted_application(&TrustedApplication::new(data, signature.clone())).await?;
+        Ok(signature)
+    }
+
     // register a new event listener for the specified connection/application
     async fn subscribe_session_to_event(&self, session: &WebSocketSessionShared<Self>, event: NotifyEvent, id: Option<usize>) -> Result<(), RpcResponseError> {
         let mut listeners = self.listeners.lock().await;
@@ -601,7 +774,7 @@ where
     }
 
     async fn on_message_internal(&self, session: &WebSocketSessionShared<Self>, message: &[u8]) -> Result<Option<Value>, RpcResponseError> {
-        let (request, is_subscribe, is_unsubscribe) = {
+        let (app, request, is_subscribe, is_unsubscribe, spending_lock) = {
             let app_state = {
                 let applications = self.applications.read().await;
                 applications.get(session).cloned()
@@ -632,10 +805,10 @@ where
     
                 // let's check the permission set by user for this method
                 app.set_requesting(true);
-                self.verify_permission_for_request(&app, &request).await?;
+                let spending_lock = self.verify_permission_for_request(&app, &request).await?;
                 app.set_requesting(false);
 
-                (request, is_subscribe, is_unsubscribe)
+                (app, request, is_subscribe, is_unsubscribe, spending_lock)
             } else {
                 // Application is not registered, register it
                 return match self.add_application(session, message).await {
@@ -672,7 +845,19 @@ where
             let mut context = Context::default();
             // Store the session
             context.store(session.clone());
-            self.handler.execute_method(context, request).await.map(|v| Some(v))
+            let Some(_spending_lock) = spending_lock else {
+                return self.handler.execute_method(context, request).await.map(|v| Some(v))
+            };
+
+            // Spending is only recorded once the transaction has been submitted
+            let response = self.handler.execute_method(context, request.clone()).await?;
+            if let Some(result) = response.get("result") {
+                if let Err(e) = self.handler.get_data().record_spending(&app, &request, result).await {
+                    error!("Error while recording spending of application {}: {}", app.get_id(), e);
+                }
+            }
+
+            Ok(Some(response))
         }
     }
 }
@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use log::{debug, trace};
+use chacha20poly1305::aead::OsRng;
+use rand::RngCore;
+use xelis_common::{
+    network::Network,
+    serializer::{Reader, ReaderError, Serializer, Writer}
+};
+use crate::{
+    cipher::Cipher,
+    config::SALT_SIZE,
+    kdf::KdfParams,
+    storage::Storage,
+    wallet::WalletError
+};
+
+// Magic bytes at the start of each backup file
+const BACKUP_MAGIC: &[u8; 8] = b"XELISBAK";
+// Version of the backup format, increased on each breaking change
+pub const BACKUP_VERSION: u8 = 1;
+
+// Raw content of a tree from the wallet database
+// Keys and values are kept in their encrypted form
+pub struct BackupTree {
+    pub name: Vec<u8>,
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>
+}
+
+// Content of a backup once decrypted
+// It contains all the trees of the wallet database:
+// keys, transactions, balances, assets, custom data trees and settings
+pub struct Backup {
+    pub network: Network,
+    pub trees: Vec<BackupTree>
+}
+
+fn write_bytes_with_size(writer: &mut Writer, bytes: &[u8]) {
+    writer.write_u32(&(bytes.len() as u32));
+    writer.write_bytes(bytes);
+}
+
+fn read_bytes_with_size(reader: &mut Reader) -> Result<Vec<u8>, ReaderError> {
+    let size = reader.read_u32()? as usize;
+    reader.read_bytes_ref(size).map(<[u8]>::to_vec)
+}
+
+impl Serializer for BackupTree {
+    fn write(&self, writer: &mut Writer) {
+        write_bytes_with_size(writer, &self.name);
+        writer.write_u64(&(self.entries.len() as u64));
+        for (key, value) in &self.entries {
+            write_bytes_with_size(writer, key);
+            write_bytes_with_size(writer, value);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let name = read_bytes_with_size(reader)?;
+        let count = reader.read_u64()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let key = read_bytes_with_size(reader)?;
+            let value = read_bytes_with_size(reader)?;
+            entries.push((key, value));
+        }
+
+        Ok(Self {
+            name,
+            entries
+        })
+    }
+}
+
+impl Serializer for Backup {
+    fn write(&self, writer: &mut Writer) {
+        self.network.write(writer);
+        writer.write_u16(self.trees.len() as u16);
+        for tree in &self.trees {
+            tree.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let network = Network::read(reader)?;
+        let count = reader.read_u16()?;
+        let mut trees = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            trees.push(BackupTree::read(reader)?);
+        }
+
+        Ok(Self {
+            network,
+            trees
+        })
+    }
+}
+
+// Create the backup file of the wallet database
+// File is encrypted using a key derived from the password with a random salt and the KDF parameters given
+// Format: magic | version | KDF parameters | salt | encrypted backup
+pub fn create_backup(storage: &Storage, network: Network, password: String, kdf_params: &KdfParams) -> Result<Vec<u8>> {
+    trace!("create backup");
+    let backup = Backup {
+        network,
+        trees: storage.export_trees()?
+    };
+    debug!("Exporting {} trees in backup using {}", backup.trees.len(), kdf_params);
+
+    let mut salt: [u8; SALT_SIZE] = [0; SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+
+    let hashed_password = kdf_params.hash_password(password, &salt)?;
+    let cipher = Cipher::new(&hashed_password, None)?;
+    let encrypted = cipher.encrypt_value(&backup.to_bytes())?;
+
+    let params = kdf_params.to_bytes();
+    let mut content = Vec::with_capacity(BACKUP_MAGIC.len() + 1 + params.len() + SALT_SIZE + encrypted.len());
+    content.extend_from_slice(BACKUP_MAGIC);
+    content.push(BACKUP_VERSION);
+    content.extend_from_slice(&params);
+    content.extend_from_slice(&salt);
+    content.extend_from_slice(&encrypted);
+
+    Ok(content)
+}
+
+// Decrypt a backup file created by `create_backup`
+pub fn read_backup(content: &[u8], password: String) -> Result<Backup> {
+    trace!("read backup");
+    if content.len() < BACKUP_MAGIC.len() + 1 || &content[..BACKUP_MAGIC.len()] != BACKUP_MAGIC {
+        return Err(WalletError::InvalidBackup.into())
+    }
+
+    let version = content[BACKUP_MAGIC.len()];
+    if version != BACKUP_VERSION {
+        return Err(WalletError::UnsupportedBackupVersion(version).into())
+    }
+
+    let mut reader = Reader::new(&content[BACKUP_MAGIC.len() + 1..]);
+    let kdf_params = KdfParams::read(&mut reader).map_err(|_| WalletError::InvalidBackup)?;
+    let salt = reader.read_bytes_ref(SALT_SIZE).map_err(|_| WalletError::InvalidBackup)?;
+
+    let hashed_password = kdf_params.hash_password(password, salt)?;
+    let cipher = Cipher::new(&hashed_password, None)?;
+    let header_size = BACKUP_MAGIC.len() + 1 + reader.total_read();
+    let decrypted = cipher.decrypt_value(&content[header_size..]).context("Invalid password provided for this backup")?;
+
+    Ok(Backup::from_bytes(&decrypted).map_err(|_| WalletError::InvalidBackup)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use chacha20poly1305::aead::OsRng;
+    use rand::RngCore;
+    use xelis_common::{network::Network, serializer::Serializer};
+    use crate::{
+        config::SALT_SIZE,
+        kdf::{KdfAlgorithm, KdfParams},
+        storage::{EncryptedStorage, Storage}
+    };
+    use super::{create_backup, read_backup, BACKUP_MAGIC, BACKUP_VERSION};
+
+    fn temp_path() -> String {
+        env::temp_dir().join(format!("xelis-backup-{}", hex::encode(OsRng.next_u64().to_be_bytes()))).to_string_lossy().into_owned()
+    }
+
+    fn dump(storage: &Storage) -> Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)> {
+        storage.export_trees().unwrap().into_iter().map(|tree| (tree.name, tree.entries)).collect()
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let mut master_key = [0; 32];
+        OsRng.fill_bytes(&mut master_key);
+        let mut storage_salt = [0; SALT_SIZE];
+        OsRng.fill_bytes(&mut storage_salt);
+
+        let (path, restored_path) = (temp_path(), temp_path());
+        let mut storage = EncryptedStorage::new(Storage::new(path.clone()).unwrap(), &master_key, storage_salt, Network::Testnet).unwrap();
+        storage.set_nonce(42).unwrap();
+        storage.set_synced_topoheight(1000).unwrap();
+
+        let kdf_params = KdfParams::new(KdfAlgorithm::Argon2id, 8 * 1024, 2, 1).unwrap();
+        let content = create_backup(storage.get_public_storage(), Network::Testnet, "password".to_owned(), &kdf_params).unwrap();
+        assert_eq!(content[BACKUP_MAGIC.len()], BACKUP_VERSION);
+        assert_eq!(KdfParams::from_bytes(&content[BACKUP_MAGIC.len() + 1..]).unwrap(), kdf_params);
+
+        assert!(read_backup(&content, "wrong password".to_owned()).is_err());
+
+        let backup = read_backup(&content, "password".to_owned()).unwrap();
+        assert_eq!(backup.network, Network::Testnet);
+
+        let mut restored = Storage::new(restored_path.clone()).unwrap();
+        restored.import_trees(backup.trees).unwrap();
+        assert_eq!(dump(&restored), dump(storage.get_public_storage()));
+
+        let restored = EncryptedStorage::new(restored, &master_key, storage_salt, Network::Testnet).unwrap();
+        assert_eq!(restored.get_nonce().unwrap(), 42);
+        assert_eq!(restored.get_synced_topoheight().unwrap(), 1000);
+
+        drop((storage, restored));
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(restored_path).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use xelis_common::{
+    api::wallet::{BalanceHistoryEntry, BalanceReconciliation},
+    config::XELIS_ASSET,
+    crypto::Hash
+};
+use crate::{
+    entry::{EntryData, TransactionEntry},
+    storage::EncryptedStorage
+};
+
+// Amounts received and sent per asset by a transaction entry
+// Amounts that can't be decrypted (watch-only wallet) are ignored
+fn get_changes(entry: &EntryData) -> HashMap<&Hash, (u64, u64)> {
+    let mut changes: HashMap<&Hash, (u64, u64)> = HashMap::new();
+    match entry {
+        EntryData::Coinbase { reward } => {
+            changes.insert(&XELIS_ASSET, (*reward, 0));
+        },
+        EntryData::Burn { asset, amount, fee } => {
+            changes.insert(asset, (0, *amount));
+
+            let sent = &mut changes.entry(&XELIS_ASSET).or_default().1;
+            *sent = sent.saturating_add(*fee);
+        },
+        EntryData::Incoming { transfers, .. } => for transfer in transfers {
+            let received = &mut changes.entry(transfer.get_asset()).or_default().0;
+            *received = received.saturating_add(transfer.get_amount().unwrap_or(0));
+        },
+        EntryData::Outgoing { transfers, fee, .. } => {
+            for transfer in transfers {
+                let sent = &mut changes.entry(transfer.get_asset()).or_default().1;
+                *sent = sent.saturating_add(transfer.get_amount().unwrap_or(0));
+            }
+
+            let sent = &mut changes.entry(&XELIS_ASSET).or_default().1;
+            *sent = sent.saturating_add(*fee);
+        }
+    }
+
+    changes
+}
+
+// Apply a transaction entry to the running balances
+// Returns one history entry per asset changed
+pub fn apply_entry(balances: &mut HashMap<Hash, u64>, entry: &TransactionEntry) -> Vec<BalanceHistoryEntry> {
+    let mut history = Vec::new();
+    for (asset, (received, sent)) in get_changes(entry.get_entry()) {
+        // A missing history (wallet synced from a topoheight) can't make it negative
+        let balance = balances.entry(asset.clone()).or_insert(0);
+        *balance = balance.saturating_add(received).saturating_sub(sent);
+
+        history.push(BalanceHistoryEntry {
+            hash: entry.get_hash().clone(),
+            topoheight: entry.get_topoheight(),
+            asset: asset.clone(),
+            received,
+            sent,
+            balance: *balance
+        });
+    }
+
+    // Keep a stable order for the assets changed by the same entry
+    history.sort_by(|a, b| a.asset.cmp(&b.asset));
+    history
+}
+
+// Compute the running balance per asset after each transaction entry, oldest first
+// Balances are always computed from the full history, filters only apply to the entries returned
+pub fn get_balance_history(storage: &EncryptedStorage, asset: Option<&Hash>, min_topoheight: Option<u64>, max_topoheight: Option<u64>) -> Result<(Vec<BalanceHistoryEntry>, HashMap<Hash, u64>)> {
+    let mut transactions = storage.get_transactions()?;
+    transactions.sort_by_key(|entry| entry.get_topoheight());
+
+    let mut balances = HashMap::new();
+    let mut history = Vec::new();
+    for entry in transactions {
+        let entries = apply_entry(&mut balances, &entry);
+        if min_topoheight.is_some_and(|min| entry.get_topoheight() < min) || max_topoheight.is_some_and(|max| entry.get_topoheight() > max) {
+            continue
+        }
+
+        history.extend(entries.into_iter().filter(|e| asset.map_or(true, |asset| e.asset == *asset)));
+    }
+
+    Ok((history, balances))
+}
+
+// Compare the computed balances against the balances decrypted from the daemon
+// Balance is set to None when the wallet can't decrypt it
+pub async fn reconcile(storage: &EncryptedStorage, computed: HashMap<Hash, u64>, asset: Option<&Hash>, watch_only: bool) -> Result<Vec<BalanceReconciliation>> {
+    let mut assets = storage.get_assets().await?;
+    assets.extend(computed.keys().cloned());
+    if let Some(asset) = asset {
+        assets.retain(|a| a == asset);
+    }
+
+    let mut assets: Vec<Hash> = assets.into_iter().collect();
+    assets.sort();
+
+    let mut reconciliation = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let balance = if watch_only {
+            None
+        } else if storage.has_balance_for(&asset).await? {
+            Some(storage.get_plaintext_balance_for(&asset).await?)
+        } else {
+            // No balance stored yet for this asset
+            Some(0)
+        };
+
+        reconciliation.push(BalanceReconciliation {
+            computed: computed.get(&asset).copied().unwrap_or(0),
+            asset,
+            balance
+        });
+    }
+
+    Ok(reconciliation)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use xelis_common::{config::XELIS_ASSET, crypto::Hash};
+    use crate::entry::{EntryData, TransactionEntry};
+    use super::apply_entry;
+
+    #[test]
+    fn test_balance_history() {
+        let mut balances = HashMap::new();
+        let history = apply_entry(&mut balances, &TransactionEntry::new(Hash::zero(), 1, EntryData::Coinbase { reward: 100 }));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].balance, 100);
+
+        let history = apply_entry(&mut balances, &TransactionEntry::new(Hash::max(), 2, EntryData::Outgoing { transfers: Vec::new(), fee: 10, nonce: 0 }));
+        assert_eq!(history[0].sent, 10);
+        assert_eq!(history[0].balance, 90);
+
+        let history = apply_entry(&mut balances, &TransactionEntry::new(Hash::max(), 3, EntryData::Burn { asset: XELIS_ASSET, amount: 50, fee: 5 }));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].sent, 55);
+        assert_eq!(history[0].balance, 35);
+
+        // Fee of a burn is paid in XELIS whatever the asset burned
+        let asset = Hash::max();
+        balances.insert(asset.clone(), 100);
+        let history = apply_entry(&mut balances, &TransactionEntry::new(Hash::max(), 4, EntryData::Burn { asset: asset.clone(), amount: 40, fee: 5 }));
+        assert_eq!(history.len(), 2);
+        assert_eq!(balances.get(&asset), Some(&60));
+        assert_eq!(balances.get(&XELIS_ASSET), Some(&30));
+
+        let history = apply_entry(&mut balances, &TransactionEntry::new(Hash::max(), 5, EntryData::Burn { asset: XELIS_ASSET, amount: 200, fee: 5 }));
+        assert_eq!(history[0].balance, 0);
+        assert_eq!(balances.get(&XELIS_ASSET), Some(&0));
+    }
+}
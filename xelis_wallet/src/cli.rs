@@ -0,0 +1,369 @@
+use std::{
+    env,
+    fs,
+    path::Path,
+    sync::Arc
+};
+use anyhow::Error;
+use clap::Subcommand;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::{timeout_at, Duration, Instant}
+};
+use xelis_common::{
+    api::wallet::{ExportFormat, ExportTransactionsParams},
+    config::{COIN_DECIMALS, XELIS_ASSET},
+    crypto::{Address, Hash, Hashable},
+    serializer::Serializer,
+    transaction::{
+        builder::{FeeBuilder, TransactionTypeBuilder, TransferBuilder},
+        BurnPayload
+    },
+    utils::format_coin
+};
+use xelis_wallet::{
+    export,
+    wallet::{Event, Wallet, WalletError}
+};
+use crate::{Config, LogProgressTableGenerationReportFunction};
+
+// Environment variable used to read the wallet password
+const PASSWORD_ENV: &str = "XELIS_WALLET_PASSWORD";
+
+// Exit codes returned by the subcommands
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_ERROR: i32 = 1;
+const EXIT_INVALID_ARGUMENT: i32 = 2;
+const EXIT_WALLET_ERROR: i32 = 3;
+const EXIT_DAEMON_ERROR: i32 = 4;
+const EXIT_NOT_ENOUGH_FUNDS: i32 = 5;
+
+// Maximum time in seconds to wait for the wallet to be online again while syncing
+const RECONNECT_TIMEOUT: u64 = 60;
+
+// Subcommands to run a single operation without the interactive prompt
+// Result is printed in JSON on stdout, errors are printed on stderr
+#[derive(Debug, Subcommand)]
+pub enum WalletCommand {
+    /// Show the balance of an asset (XELIS by default)
+    Balance {
+        /// Asset hash
+        #[clap(long)]
+        asset: Option<String>
+    },
+    /// Send an amount of an asset to an address
+    Transfer {
+        /// Destination address
+        address: String,
+        /// Amount to send using the decimals of the asset
+        amount: String,
+        /// Asset hash (XELIS by default)
+        #[clap(long)]
+        asset: Option<String>
+    },
+    /// Burn an amount of an asset
+    Burn {
+        /// Amount to burn using the decimals of the asset
+        amount: String,
+        /// Asset hash (XELIS by default)
+        #[clap(long)]
+        asset: Option<String>
+    },
+    /// List the transactions of the wallet
+    History {
+        #[clap(long)]
+        min_topoheight: Option<u64>,
+        #[clap(long)]
+        max_topoheight: Option<u64>
+    },
+    /// Show the wallet address
+    Address,
+    /// Rescan the wallet from a topoheight
+    Rescan {
+        #[clap(default_value_t = 0)]
+        topoheight: u64
+    },
+    /// Export the transactions of the wallet in CSV or JSON
+    Export {
+        /// Format of the export: csv or json
+        format: String,
+        /// File to write, printed on stdout if not set
+        #[clap(long)]
+        output: Option<String>,
+        #[clap(long)]
+        min_topoheight: Option<u64>,
+        #[clap(long)]
+        max_topoheight: Option<u64>
+    }
+}
+
+impl WalletCommand {
+    // Check if the wallet must be synced with the daemon before running the command
+    fn requires_sync(&self) -> bool {
+        !matches!(self, Self::Address)
+    }
+
+    // Check if the command can't be done without a daemon
+    fn requires_daemon(&self) -> bool {
+        matches!(self, Self::Transfer { .. } | Self::Burn { .. } | Self::Rescan { .. })
+    }
+}
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("{}", _0)]
+    InvalidArgument(String),
+    #[error("{:#}", _0)]
+    Wallet(Error),
+    #[error("{:#}", _0)]
+    Daemon(Error),
+    #[error(transparent)]
+    NotEnoughFunds(WalletError),
+    #[error("{:#}", _0)]
+    Any(#[from] Error)
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::InvalidArgument(_) => EXIT_INVALID_ARGUMENT,
+            Self::Wallet(_) => EXIT_WALLET_ERROR,
+            Self::Daemon(_) => EXIT_DAEMON_ERROR,
+            Self::NotEnoughFunds(_) => EXIT_NOT_ENOUGH_FUNDS,
+            Self::Any(_) => EXIT_ERROR
+        }
+    }
+}
+
+impl From<WalletError> for CliError {
+    fn from(e: WalletError) -> Self {
+        match e {
+            WalletError::NotEnoughFunds(..) | WalletError::NotEnoughFundsForFee(..) => Self::NotEnoughFunds(e),
+            WalletError::NotOnlineMode | WalletError::NetworkError(_) => Self::Daemon(e.into()),
+            e => Self::Any(e.into())
+        }
+    }
+}
+
+// Run the subcommand and returns the exit code of the process
+pub async fn run(config: Config, command: WalletCommand) -> i32 {
+    let wallet = match open_wallet(&config) {
+        Ok(wallet) => wallet,
+        Err(e) => {
+            eprintln!("{}", e);
+            return e.exit_code()
+        }
+    };
+
+    let res = execute(&wallet, &config, command).await;
+    wallet.close().await;
+
+    match res {
+        Ok(value) => {
+            println!("{}", value);
+            EXIT_SUCCESS
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            e.exit_code()
+        }
+    }
+}
+
+// Read the password from the command line, the environment or a file
+fn read_password(config: &Config) -> Result<String, CliError> {
+    if let Some(password) = &config.password {
+        return Ok(password.clone())
+    }
+
+    if let Ok(password) = env::var(PASSWORD_ENV) {
+        return Ok(password)
+    }
+
+    if let Some(path) = &config.password_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| CliError::InvalidArgument(format!("Error while reading password file: {}", e)))?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_owned())
+    }
+
+    Err(CliError::InvalidArgument(format!("No password provided, use --password-file or {}", PASSWORD_ENV)))
+}
+
+fn open_wallet(config: &Config) -> Result<Arc<Wallet>, CliError> {
+    let path = config.wallet_path.clone()
+        .ok_or_else(|| CliError::InvalidArgument("No wallet path provided, use --wallet-path".to_owned()))?;
+    if !Path::new(&path).is_dir() {
+        return Err(CliError::Wallet(anyhow::anyhow!("Wallet {} was not found", path)))
+    }
+
+    let password = read_password(config)?;
+    let precomputed_tables = Wallet::read_or_generate_precomputed_tables(config.precomputed_tables_path.clone(), LogProgressTableGenerationReportFunction)?;
+    Wallet::open(path, password, config.network, precomputed_tables).map_err(CliError::Wallet)
+}
+
+// Connect to the daemon and wait until the wallet is synced
+async fn sync_wallet(wallet: &Arc<Wallet>, config: &Config) -> Result<(), CliError> {
+    let mut receiver = wallet.subscribe_events().await;
+    let mut daemon_addresses = vec![config.daemon_address.clone()];
+    daemon_addresses.extend(config.backup_daemon_address.iter().cloned());
+    wallet.set_online_mode_with_failover(daemon_addresses).await
+        .map_err(|e| CliError::Daemon(e.into()))?;
+
+    wait_for_sync(&mut receiver, false).await
+}
+
+// The new topoheight is propagated once the sync is done
+// Wallet can go offline meanwhile when the network handler is restarted or switches to another daemon,
+// it must then be online again before the timeout
+// If restarted is set, the sync is only done once the wallet is online again
+async fn wait_for_sync(receiver: &mut broadcast::Receiver<Event>, mut restarted: bool) -> Result<(), CliError> {
+    let mut deadline = None;
+    loop {
+        let event = match deadline {
+            Some(deadline) => timeout_at(deadline, receiver.recv()).await
+                .map_err(|_| CliError::Daemon(anyhow::anyhow!("Connection to the daemon was lost while syncing")))?,
+            None => receiver.recv().await
+        };
+
+        match event {
+            Ok(Event::NewTopoHeight { .. }) if !restarted => return Ok(()),
+            Ok(Event::Offline) => if deadline.is_none() {
+                deadline = Some(Instant::now() + Duration::from_secs(RECONNECT_TIMEOUT));
+            },
+            Ok(Event::Online) => {
+                deadline = None;
+                restarted = false;
+            },
+            Ok(_) | Err(RecvError::Lagged(_)) => {},
+            Err(RecvError::Closed) => return Err(CliError::Daemon(anyhow::anyhow!("Wallet events channel was closed while syncing")))
+        }
+    }
+}
+
+fn parse_asset(asset: Option<String>) -> Result<Hash, CliError> {
+    match asset {
+        Some(asset) => Hash::from_hex(asset).map_err(|_| CliError::InvalidArgument("Invalid asset".to_owned())),
+        None => Ok(XELIS_ASSET)
+    }
+}
+
+fn parse_amount(amount: &str, decimals: u8) -> Result<u64, CliError> {
+    let float_amount: f64 = amount.parse()
+        .map_err(|_| CliError::InvalidArgument("Invalid amount".to_owned()))?;
+    Ok((float_amount * 10u32.pow(decimals as u32) as f64) as u64)
+}
+
+async fn get_decimals(wallet: &Wallet, asset: &Hash) -> u8 {
+    let storage = wallet.get_storage().read().await;
+    storage.get_asset_decimals(asset).unwrap_or(COIN_DECIMALS)
+}
+
+// Build and submit the transaction, the wallet must be online
+async fn send_transaction(wallet: &Wallet, tx_type: TransactionTypeBuilder) -> Result<Value, CliError> {
+    let tx = wallet.create_transaction(tx_type, FeeBuilder::default()).await?;
+    wallet.submit_transaction(&tx).await?;
+
+    Ok(json!({
+        "hash": tx.hash(),
+        "nonce": tx.get_nonce(),
+        "fee": tx.get_fee(),
+        "tx_as_hex": tx.to_hex()
+    }))
+}
+
+async fn execute(wallet: &Arc<Wallet>, config: &Config, command: WalletCommand) -> Result<Value, CliError> {
+    if config.offline_mode {
+        if command.requires_daemon() {
+            return Err(CliError::InvalidArgument("This command can't be used in offline mode".to_owned()))
+        }
+    } else if command.requires_sync() {
+        sync_wallet(wallet, config).await?;
+    }
+
+    let mainnet = wallet.get_network().is_mainnet();
+    Ok(match command {
+        WalletCommand::Balance { asset } => {
+            let asset = parse_asset(asset)?;
+            let storage = wallet.get_storage().read().await;
+            let decimals = storage.get_asset_decimals(&asset).unwrap_or(COIN_DECIMALS);
+            // Balances can't be decrypted without the private key
+            let balance = if wallet.is_watch_only() {
+                None
+            } else {
+                Some(storage.get_plaintext_balance_for(&asset).await.unwrap_or(0))
+            };
+
+            json!({
+                "asset": asset,
+                "balance": balance,
+                "formatted": balance.map(|balance| format_coin(balance, decimals)),
+                "decimals": decimals
+            })
+        },
+        WalletCommand::Transfer { address, amount, asset } => {
+            let destination = Address::from_string(&address)
+                .map_err(|_| CliError::InvalidArgument("Invalid address".to_owned()))?;
+            if destination.is_mainnet() != mainnet {
+                return Err(CliError::InvalidArgument("Address is not for this network".to_owned()))
+            }
+
+            let asset = parse_asset(asset)?;
+            let amount = parse_amount(&amount, get_decimals(wallet, &asset).await)?;
+            let transfer = TransferBuilder {
+                destination,
+                amount,
+                asset,
+                extra_data: None
+            };
+            send_transaction(wallet, TransactionTypeBuilder::Transfers(vec![transfer])).await?
+        },
+        WalletCommand::Burn { amount, asset } => {
+            let asset = parse_asset(asset)?;
+            let amount = parse_amount(&amount, get_decimals(wallet, &asset).await)?;
+            send_transaction(wallet, TransactionTypeBuilder::Burn(BurnPayload { asset, amount })).await?
+        },
+        WalletCommand::History { min_topoheight, max_topoheight } => {
+            let storage = wallet.get_storage().read().await;
+            let mut transactions = storage.get_filtered_transactions(None, min_topoheight, max_topoheight, true, true, true, true, None)?;
+            transactions.sort_by_key(|entry| entry.get_topoheight());
+            json!(transactions.into_iter().map(|entry| entry.serializable(mainnet)).collect::<Vec<_>>())
+        },
+        WalletCommand::Address => json!(wallet.get_address()),
+        WalletCommand::Rescan { topoheight } => {
+            let mut receiver = wallet.subscribe_events().await;
+            // Network handler is stopped and started again by the rescan
+            wallet.rescan(topoheight).await?;
+            wait_for_sync(&mut receiver, true).await?;
+            json!(true)
+        },
+        WalletCommand::Export { format, output, min_topoheight, max_topoheight } => {
+            let format = match format.to_lowercase().as_str() {
+                "csv" => ExportFormat::Csv,
+                "json" => ExportFormat::Json,
+                _ => return Err(CliError::InvalidArgument("Format must be csv or json".to_owned()))
+            };
+
+            let params = ExportTransactionsParams {
+                format,
+                min_topoheight,
+                max_topoheight,
+                min_timestamp: None,
+                max_timestamp: None,
+                accept_incoming: true,
+                accept_outgoing: true,
+                accept_coinbase: true,
+                accept_burn: true
+            };
+            let content = export::export_transactions(wallet, &params).await?;
+            match output {
+                Some(path) => {
+                    fs::write(&path, content).map_err(|e| CliError::Any(e.into()))?;
+                    json!({ "path": path })
+                },
+                None => Value::String(content)
+            }
+        }
+    })
+}
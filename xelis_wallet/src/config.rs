@@ -9,11 +9,29 @@ pub const KEY_SIZE: usize = 32;
 
 // daemon address by default when no specified
 pub const DEFAULT_DAEMON_ADDRESS: &str = "http://127.0.0.1:8080";
+// Interval in seconds between each health check of the daemons when several are configured
+pub const DAEMON_HEALTH_CHECK_INTERVAL: u64 = 60;
+// Maximum topoheight lag of the daemon used behind another daemon before switching to it
+pub const MAX_DAEMON_TOPOHEIGHT_LAG: u64 = 16;
+
+// Default password KDF parameters used when creating a new wallet
+// Memory cost is in KiB
+pub const DEFAULT_KDF_MEMORY_COST: u32 = 15 * 1000;
+pub const DEFAULT_KDF_ITERATIONS: u32 = 16;
+pub const DEFAULT_KDF_PARALLELISM: u32 = 1;
+
+// Passphrase KDF parameters used to derive the wallet key from the seed
+// They must NEVER change: keys derived with a passphrase couldn't be recovered from the seed anymore
+pub const PASSPHRASE_KDF_MEMORY_COST: u32 = 15 * 1000;
+pub const PASSPHRASE_KDF_ITERATIONS: u32 = 16;
+pub const PASSPHRASE_KDF_PARALLELISM: u32 = 1;
+pub const PASSPHRASE_HASH_SIZE: usize = 32;
+// Domain prepended to the seed key to build the passphrase salt
+pub const PASSPHRASE_SALT_DOMAIN: &[u8] = b"XELIS_PASSPHRASE_SALT";
 
 lazy_static! {
-    pub static ref PASSWORD_ALGORITHM: Argon2<'static> = {
-        // 15 MB, 16 iterations
-        let params = Params::new(15 * 1000, 16, 1,  Some(PASSWORD_HASH_SIZE)).unwrap();
+    pub static ref PASSPHRASE_ALGORITHM: Argon2<'static> = {
+        let params = Params::new(PASSPHRASE_KDF_MEMORY_COST, PASSPHRASE_KDF_ITERATIONS, PASSPHRASE_KDF_PARALLELISM, Some(PASSPHRASE_HASH_SIZE)).unwrap();
         Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
     };
 }
\ No newline at end of file
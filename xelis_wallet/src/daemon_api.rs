@@ -8,6 +8,7 @@ use xelis_common::{
     json_rpc::{
         WebSocketJsonRPCClient,
         WebSocketJsonRPCClientImpl,
+        JsonRPCError,
         JsonRPCResult,
         EventReceiver
     },
@@ -22,6 +23,8 @@ use xelis_common::{
         GetTransactionParams,
         GetNonceParams,
         GetNonceResult,
+        HasNonceParams,
+        HasNonceResult,
         GetAssetsParams,
         IsTxExecutedInBlockParams,
         NotifyEvent,
@@ -33,7 +36,8 @@ use xelis_common::{
         GetAssetParams,
         GetMempoolCacheParams,
         GetMempoolCacheResult,
-        IsAccountRegisteredParams
+        IsAccountRegisteredParams,
+        TransactionResponse
     },
     account::VersionedBalance,
     crypto::{
@@ -48,6 +52,9 @@ use xelis_common::{
     }
 };
 
+// Error message of the daemon when the account has no transaction in mempool
+const MEMPOOL_CACHE_NOT_FOUND: &str = "Account not found while retrieving mempool cache";
+
 pub struct DaemonAPI {
     client: WebSocketJsonRPCClient<NotifyEvent>,
 }
@@ -188,9 +195,18 @@ impl DaemonAPI {
         Ok(tx)
     }
 
+    // Retrieve the transaction with its mempool and execution status
+    pub async fn get_transaction_with_status(&self, hash: &Hash) -> Result<TransactionResponse<'static>> {
+        let tx = self.client.call_with("get_transaction", &GetTransactionParams {
+            hash: Cow::Borrowed(hash)
+        }).await.context(format!("Error while fetching transaction {}", hash))?;
+        Ok(tx)
+    }
+
     pub async fn submit_transaction(&self, transaction: &Transaction) -> Result<()> {
         let _: bool = self.client.call_with("submit_transaction", &SubmitTransactionParams {
-            data: transaction.to_hex()
+            data: transaction.to_hex(),
+            wait_for_execution: None
         }).await?;
         Ok(())
     }
@@ -202,6 +218,14 @@ impl DaemonAPI {
         Ok(nonce)
     }
 
+    pub async fn has_nonce(&self, address: &Address) -> Result<bool> {
+        let result: HasNonceResult = self.client.call_with("has_nonce", &HasNonceParams {
+            address: Cow::Borrowed(address),
+            topoheight: None
+        }).await.context(format!("Error while checking nonce of address {}", address))?;
+        Ok(result.exist)
+    }
+
     pub async fn is_tx_executed_in_block(&self, tx_hash: &Hash, block_hash: &Hash) -> Result<bool> {
         let is_executed = self.client.call_with("is_tx_executed_in_block", &IsTxExecutedInBlockParams {
             tx_hash: Cow::Borrowed(tx_hash),
@@ -217,6 +241,21 @@ impl DaemonAPI {
         Ok(cache)
     }
 
+    // Retrieve the mempool cache of the account
+    // Returns None if the account has no transaction in mempool
+    pub async fn get_mempool_cache_if_any(&self, address: &Address) -> Result<Option<GetMempoolCacheResult>> {
+        let result = self.client.call_with("get_mempool_cache", &GetMempoolCacheParams {
+            address: Cow::Borrowed(address)
+        }).await;
+
+        match result {
+            Ok(cache) => Ok(Some(cache)),
+            // Daemon returns this error when no cache exists for the account
+            Err(JsonRPCError::ServerError { message, .. }) if message == MEMPOOL_CACHE_NOT_FOUND => Ok(None),
+            Err(e) => Err(e).context("Error while fetching mempool cache")
+        }
+    }
+
     pub async fn is_account_registered(&self, address: &Address, in_stable_height: bool) -> Result<bool> {
         let is_registered = self.client.call_with("is_account_registered", &IsAccountRegisteredParams {
             address: Cow::Borrowed(address),
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use xelis_common::{
     api::{
         DataElement,
@@ -8,7 +9,7 @@ use xelis_common::{
             TransferOut as RPCTransferOut
         }
     },
-    config::XELIS_ASSET,
+    config::{COIN_DECIMALS, XELIS_ASSET},
     crypto::{
         Hash,
         PublicKey
@@ -27,14 +28,35 @@ use xelis_common::{
 use anyhow::Result;
 use crate::storage::EncryptedStorage;
 
+// Value stored on disk for an amount that couldn't be decrypted (watch-only wallet)
+// No valid amount can be above the maximum supply
+const ENCRYPTED_AMOUNT: u64 = u64::MAX;
+
+fn read_amount(reader: &mut Reader) -> Result<Option<u64>, ReaderError> {
+    let amount = reader.read_u64()?;
+    Ok(if amount == ENCRYPTED_AMOUNT { None } else { Some(amount) })
+}
+
+fn write_amount(writer: &mut Writer, amount: &Option<u64>) {
+    writer.write_u64(&amount.unwrap_or(ENCRYPTED_AMOUNT));
+}
+
+// Format the amount or show it as encrypted if it couldn't be decrypted
+fn format_amount(amount: Option<u64>, decimals: u8) -> String {
+    match amount {
+        Some(amount) => format_coin(amount, decimals),
+        None => "encrypted".to_owned()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TransferOut {
     // Destination key
     destination: PublicKey,
     // Asset used
     asset: Hash,
-    // Amount spent
-    amount: u64,
+    // Amount spent, None if it couldn't be decrypted
+    amount: Option<u64>,
     // Extra data with good format
     extra_data: Option<DataElement>
 }
@@ -43,14 +65,14 @@ pub struct TransferOut {
 pub struct TransferIn {
     // Asset used
     asset: Hash,
-    // Amount spent
-    amount: u64,
+    // Amount spent, None if it couldn't be decrypted
+    amount: Option<u64>,
     // Extra data with good format
     extra_data: Option<DataElement>
 }
 
 impl TransferOut {
-    pub fn new(destination: PublicKey, asset: Hash, amount: u64, extra_data: Option<DataElement>) -> Self {
+    pub fn new(destination: PublicKey, asset: Hash, amount: Option<u64>, extra_data: Option<DataElement>) -> Self {
         Self {
             destination,
             asset,
@@ -67,7 +89,7 @@ impl TransferOut {
         &self.asset
     }
 
-    pub fn get_amount(&self) -> u64 {
+    pub fn get_amount(&self) -> Option<u64> {
         self.amount
     }
 
@@ -78,7 +100,7 @@ impl TransferOut {
 
 
 impl TransferIn {
-    pub fn new(asset: Hash, amount: u64, extra_data: Option<DataElement>) -> Self {
+    pub fn new(asset: Hash, amount: Option<u64>, extra_data: Option<DataElement>) -> Self {
         Self {
             asset,
             amount,
@@ -90,7 +112,7 @@ impl TransferIn {
         &self.asset
     }
 
-    pub fn get_amount(&self) -> u64 {
+    pub fn get_amount(&self) -> Option<u64> {
         self.amount
     }
 
@@ -103,7 +125,7 @@ impl Serializer for TransferOut {
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
         let destination = PublicKey::read(reader)?;
         let asset = reader.read_hash()?;
-        let amount = reader.read_u64()?;
+        let amount = read_amount(reader)?;
 
         let extra_data = Option::read(reader)?;
 
@@ -118,20 +140,20 @@ impl Serializer for TransferOut {
     fn write(&self, writer: &mut Writer) {
         self.destination.write(writer);
         writer.write_hash(&self.asset);
-        writer.write_u64(&self.amount);
+        write_amount(writer, &self.amount);
 
         self.extra_data.write(writer);
     }
 
     fn size(&self) -> usize {
-        self.destination.size() + self.asset.size() + self.amount.size() + self.extra_data.size()
+        self.destination.size() + self.asset.size() + 8 + self.extra_data.size()
     }
 }
 
 impl Serializer for TransferIn {
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
         let asset = reader.read_hash()?;
-        let amount = reader.read_u64()?;
+        let amount = read_amount(reader)?;
 
         let extra_data = Option::read(reader)?;
 
@@ -144,13 +166,13 @@ impl Serializer for TransferIn {
 
     fn write(&self, writer: &mut Writer) {
         writer.write_hash(&self.asset);
-        writer.write_u64(&self.amount);
+        write_amount(writer, &self.amount);
 
         self.extra_data.write(writer);
     }
 
     fn size(&self) -> usize {
-        self.asset.size() + self.amount.size() + self.extra_data.size()
+        self.asset.size() + 8 + self.extra_data.size()
     }
 }
 
@@ -162,7 +184,9 @@ pub enum EntryData {
     },
     Burn {
         asset: Hash,
-        amount: u64
+        amount: u64,
+        // Fee paid
+        fee: u64
     },
     Incoming {
         from: PublicKey,
@@ -182,9 +206,11 @@ impl Serializer for EntryData {
         let id = reader.read_u8()?;
         Ok(match id  {
             0 => Self::Coinbase { reward: reader.read_u64()? },
+            // Burns saved before the fee was stored
             1 => Self::Burn {
                 asset: reader.read_hash()?,
-                amount: reader.read_u64()?
+                amount: reader.read_u64()?,
+                fee: 0
             },
             2 => {
                 let key = PublicKey::read(reader)?;
@@ -207,7 +233,12 @@ impl Serializer for EntryData {
                 let nonce = reader.read_u64()?;
 
                 Self::Outgoing { transfers, fee, nonce }
-            }
+            },
+            4 => Self::Burn {
+                asset: reader.read_hash()?,
+                amount: reader.read_u64()?,
+                fee: reader.read_u64()?
+            },
             _ => return Err(ReaderError::InvalidValue)
         }) 
     }
@@ -218,10 +249,11 @@ impl Serializer for EntryData {
                 writer.write_u8(0);
                 writer.write_u64(reward);
             },
-            Self::Burn { asset, amount } => {
-                writer.write_u8(1);
+            Self::Burn { asset, amount, fee } => {
+                writer.write_u8(4);
                 writer.write_hash(asset);
                 writer.write_u64(amount);
+                writer.write_u64(fee);
             },
             Self::Incoming { from, transfers } => {
                 writer.write_u8(2);
@@ -246,7 +278,7 @@ impl Serializer for EntryData {
     fn size(&self) -> usize {
         1 + match &self {
             Self::Coinbase { reward } => reward.size(),
-            Self::Burn { asset, amount } => asset.size() + amount.size(),
+            Self::Burn { asset, amount, fee } => asset.size() + amount.size() + fee.size(),
             Self::Incoming { from, transfers } => {
                 from.size() + 2 + transfers.iter().map(|t| t.size()).sum::<usize>()
             },
@@ -297,7 +329,7 @@ impl TransactionEntry {
             topoheight: self.topoheight,
             entry: match self.entry {
                 EntryData::Coinbase { reward } => RPCEntryType::Coinbase { reward },
-                EntryData::Burn { asset, amount } => RPCEntryType::Burn { asset, amount },
+                EntryData::Burn { asset, amount, fee } => RPCEntryType::Burn { asset, amount, fee },
                 EntryData::Incoming { from, transfers } => {
                     let transfers = transfers.into_iter().map(|t| RPCTransferIn {
                         asset: t.asset,
@@ -319,21 +351,27 @@ impl TransactionEntry {
         }
     }
 
-    pub fn summary(&self, mainnet: bool, storage: &EncryptedStorage) -> Result<String> {
+    // Labels from the address book are displayed next to the known addresses
+    pub fn summary(&self, mainnet: bool, storage: &EncryptedStorage, labels: &HashMap<PublicKey, String>) -> Result<String> {
+        let format_key = |key: &PublicKey| match labels.get(key) {
+            Some(label) => format!("{} ({})", label, key.as_address(mainnet)),
+            None => key.as_address(mainnet).to_string()
+        };
+
         let entry_str = match self.get_entry() {
             EntryData::Coinbase { reward } => format!("Coinbase {} XELIS", format_xelis(*reward)),
-            EntryData::Burn { asset, amount } => {
+            EntryData::Burn { asset, amount, fee } => {
                 let decimals = storage.get_asset_decimals(asset)?;
-                format!("Burn {} of {}", format_coin(*amount, decimals), asset)
+                format!("Fee: {}, Burn {} of {}", format_xelis(*fee), format_coin(*amount, decimals), asset)
             },
             EntryData::Incoming { from, transfers } => {
                 let mut str = String::new();
                 for transfer in transfers {
                     if *transfer.get_asset() == XELIS_ASSET {
-                        str.push_str(&format!("Received {} XELIS from {}", format_xelis(transfer.get_amount()), from.as_address(mainnet)));
+                        str.push_str(&format!("Received {} XELIS from {}", format_amount(transfer.get_amount(), COIN_DECIMALS), format_key(from)));
                     } else {
                         let decimals = storage.get_asset_decimals(transfer.get_asset())?;
-                        str.push_str(&format!("Received {} {} from {}", format_coin(transfer.get_amount(), decimals), transfer.get_asset(), from.as_address(mainnet)));
+                        str.push_str(&format!("Received {} {} from {}", format_amount(transfer.get_amount(), decimals), transfer.get_asset(), format_key(from)));
                     }
                 }
                 str
@@ -342,10 +380,10 @@ impl TransactionEntry {
                 let mut str = format!("Fee: {}, Nonce: {} ", format_xelis(*fee), nonce);
                 for transfer in transfers {
                     if *transfer.get_asset() == XELIS_ASSET {
-                        str.push_str(&format!("Sent {} XELIS to {}", format_xelis(transfer.get_amount()), transfer.get_destination().as_address(mainnet)));
+                        str.push_str(&format!("Sent {} XELIS to {}", format_amount(transfer.get_amount(), COIN_DECIMALS), format_key(transfer.get_destination())));
                     } else {
                         let decimals = storage.get_asset_decimals(transfer.get_asset())?;
-                        str.push_str(&format!("Sent {} {} to {}", format_coin(transfer.get_amount(), decimals), transfer.get_asset(), transfer.get_destination().as_address(mainnet)));
+                        str.push_str(&format!("Sent {} {} to {}", format_amount(transfer.get_amount(), decimals), transfer.get_asset(), format_key(transfer.get_destination())));
                     }
                 }
                 str
@@ -394,7 +432,7 @@ impl<'a> Transfer<'a> {
         }
     }
 
-    pub fn get_amount(&self) -> u64 {
+    pub fn get_amount(&self) -> Option<u64> {
         match self {
             Transfer::In(t) => t.amount,
             Transfer::Out(t) => t.amount
@@ -408,3 +446,27 @@ impl<'a> Transfer<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::{
+        config::XELIS_ASSET,
+        serializer::{Serializer, Writer}
+    };
+    use super::EntryData;
+
+    #[test]
+    fn test_burn_serialization() {
+        let burn = EntryData::Burn { asset: XELIS_ASSET, amount: 100, fee: 5 };
+        let bytes = burn.to_bytes();
+        assert_eq!(bytes.len(), burn.size());
+        assert!(matches!(EntryData::from_bytes(&bytes).unwrap(), EntryData::Burn { amount: 100, fee: 5, .. }));
+
+        // Burns saved before the fee was stored
+        let mut writer = Writer::new();
+        writer.write_u8(1);
+        writer.write_hash(&XELIS_ASSET);
+        writer.write_u64(&100);
+        assert!(matches!(EntryData::from_bytes(&writer.bytes()).unwrap(), EntryData::Burn { amount: 100, fee: 0, .. }));
+    }
+}
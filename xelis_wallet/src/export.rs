@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use anyhow::Context;
+use log::trace;
+use xelis_common::{
+    api::wallet::{
+        ExportEntry,
+        ExportEntryType,
+        ExportFormat,
+        ExportTransactionsParams
+    },
+    config::{COIN_DECIMALS, XELIS_ASSET},
+    crypto::Hash,
+    time::TimestampMillis,
+    utils::{format_coin, format_xelis}
+};
+use crate::{
+    entry::{EntryData, TransactionEntry},
+    wallet::{Wallet, WalletError}
+};
+
+const CSV_HEADER: &str = "hash,topoheight,timestamp,type,asset,amount,fee,address,extra_data";
+
+// Convert a transaction entry into one row per transfer
+fn to_export_entries(entry: TransactionEntry, timestamp: Option<TimestampMillis>, decimals: &HashMap<Hash, u8>, mainnet: bool) -> Vec<ExportEntry> {
+    let format_amount = |asset: &Hash, amount: u64| format_coin(amount, decimals.get(asset).copied().unwrap_or(COIN_DECIMALS));
+    let hash = entry.get_hash().clone();
+    let topoheight = entry.get_topoheight();
+    let new_entry = |entry_type, asset: Hash, amount, fee, address, extra_data| ExportEntry {
+        hash: hash.clone(),
+        topoheight,
+        timestamp,
+        entry_type,
+        asset,
+        amount,
+        fee,
+        address,
+        extra_data
+    };
+
+    match entry.get_entry() {
+        EntryData::Coinbase { reward } => vec![new_entry(ExportEntryType::Coinbase, XELIS_ASSET, Some(format_xelis(*reward)), None, None, None)],
+        EntryData::Burn { asset, amount, fee } => vec![new_entry(ExportEntryType::Burn, asset.clone(), Some(format_amount(asset, *amount)), Some(format_xelis(*fee)), None, None)],
+        EntryData::Incoming { from, transfers } => transfers.iter().map(|transfer| new_entry(
+            ExportEntryType::Incoming,
+            transfer.get_asset().clone(),
+            transfer.get_amount().map(|amount| format_amount(transfer.get_asset(), amount)),
+            None,
+            Some(from.as_address(mainnet)),
+            transfer.get_extra_data().clone()
+        )).collect(),
+        EntryData::Outgoing { transfers, fee, .. } => transfers.iter().enumerate().map(|(i, transfer)| new_entry(
+            ExportEntryType::Outgoing,
+            transfer.get_asset().clone(),
+            transfer.get_amount().map(|amount| format_amount(transfer.get_asset(), amount)),
+            // Fee is paid only once per transaction
+            if i == 0 { Some(format_xelis(*fee)) } else { None },
+            Some(transfer.get_destination().as_address(mainnet)),
+            transfer.get_extra_data().clone()
+        )).collect()
+    }
+}
+
+// Quote the CSV field if it contains a special character
+// Fields that could be interpreted as a formula by a spreadsheet are prefixed with a quote
+fn escape_csv(mut value: String) -> String {
+    if value.starts_with(|c| matches!(c, '=' | '+' | '-' | '@' | '\t' | '\r')) {
+        value.insert(0, '\'');
+    }
+
+    if value.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn to_csv(entries: &[ExportEntry]) -> Result<String, WalletError> {
+    let mut content = String::from(CSV_HEADER);
+    content.push('\n');
+    for entry in entries {
+        let extra_data = match &entry.extra_data {
+            Some(data) => serde_json::to_string(data).context("Error while serializing extra data")?,
+            None => String::new()
+        };
+
+        let fields = [
+            entry.hash.to_string(),
+            entry.topoheight.to_string(),
+            entry.timestamp.map(|t| t.to_string()).unwrap_or_default(),
+            entry.entry_type.as_str().to_owned(),
+            entry.asset.to_string(),
+            entry.amount.clone().unwrap_or_else(|| "encrypted".to_owned()),
+            entry.fee.clone().unwrap_or_default(),
+            entry.address.as_ref().map(|a| a.to_string()).unwrap_or_default(),
+            extra_data
+        ];
+
+        content.push_str(&fields.map(escape_csv).join(","));
+        content.push('\n');
+    }
+
+    Ok(content)
+}
+
+// Export the filtered transactions of the wallet in the requested format
+// Block timestamps are retrieved from the daemon when the wallet is online
+pub async fn export_transactions(wallet: &Wallet, params: &ExportTransactionsParams) -> Result<String, WalletError> {
+    trace!("export transactions");
+    let (mut transactions, decimals) = {
+        let storage = wallet.get_storage().read().await;
+        let transactions = storage.get_filtered_transactions(None, params.min_topoheight, params.max_topoheight, params.accept_incoming, params.accept_outgoing, params.accept_coinbase, params.accept_burn, None)?;
+        let decimals: HashMap<Hash, u8> = storage.get_assets_with_decimals().await?.into_iter().collect();
+        (transactions, decimals)
+    };
+    transactions.sort_by_key(|entry| entry.get_topoheight());
+
+    // Network handler is not kept locked while fetching the timestamps
+    let api = {
+        let network_handler = wallet.get_network_handler().await.lock().await;
+        network_handler.as_ref().map(|network_handler| network_handler.get_api())
+    };
+
+    let mut timestamps: HashMap<u64, TimestampMillis> = HashMap::new();
+    match api {
+        Some(api) => {
+            for entry in transactions.iter() {
+                let topoheight = entry.get_topoheight();
+                if !timestamps.contains_key(&topoheight) {
+                    trace!("Retrieving block timestamp at topoheight {}", topoheight);
+                    let block = api.get_block_at_topoheight(topoheight).await?;
+                    timestamps.insert(topoheight, block.timestamp);
+                }
+            }
+        },
+        None if params.min_timestamp.is_some() || params.max_timestamp.is_some() => return Err(WalletError::NotOnlineMode),
+        None => {}
+    };
+
+    let mainnet = wallet.get_network().is_mainnet();
+    let entries: Vec<ExportEntry> = transactions.into_iter()
+        .filter_map(|entry| {
+            let timestamp = timestamps.get(&entry.get_topoheight()).copied();
+            if let Some(t) = timestamp {
+                if params.min_timestamp.is_some_and(|min| t < min) || params.max_timestamp.is_some_and(|max| t > max) {
+                    return None
+                }
+            }
+
+            Some(to_export_entries(entry, timestamp, &decimals, mainnet))
+        })
+        .flatten()
+        .collect();
+
+    match params.format {
+        ExportFormat::Csv => to_csv(&entries),
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(&entries).context("Error while serializing transactions")?)
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use rand::{rngs::OsRng, RngCore};
+use xelis_common::{
+    api::{
+        wallet::{InvoiceEntry, InvoicePaymentEntry, InvoiceStatus},
+        DataElement,
+        DataValue,
+        ValueType
+    },
+    crypto::{Hash, PublicKey, HASH_SIZE},
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    time::TimestampSeconds
+};
+use crate::{
+    entry::{EntryData, TransactionEntry},
+    storage::EncryptedStorage,
+    wallet::WalletError
+};
+
+// Key of the invoice ID in the data of the integrated address
+pub const INVOICE_ID_KEY: &str = "invoice_id";
+// Maximum size in bytes of an invoice description
+pub const MAX_DESCRIPTION_SIZE: usize = 255;
+
+// Payment received for an invoice
+#[derive(Clone, Debug)]
+pub struct InvoicePayment {
+    // Transaction which paid the invoice
+    hash: Hash,
+    topoheight: u64,
+    // Sum of the transfers to the invoice in this transaction
+    amount: u64
+}
+
+impl InvoicePayment {
+    pub fn new(hash: Hash, topoheight: u64, amount: u64) -> Self {
+        Self {
+            hash,
+            topoheight,
+            amount
+        }
+    }
+
+    pub fn get_hash(&self) -> &Hash {
+        &self.hash
+    }
+
+    pub fn get_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    pub fn get_amount(&self) -> u64 {
+        self.amount
+    }
+}
+
+impl Serializer for InvoicePayment {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_hash(&self.hash);
+        writer.write_u64(&self.topoheight);
+        writer.write_u64(&self.amount);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            hash: reader.read_hash()?,
+            topoheight: reader.read_u64()?,
+            amount: reader.read_u64()?
+        })
+    }
+}
+
+// Invoice created by the wallet to request a payment
+// Payer must use the integrated address containing the invoice ID
+// so incoming transfers can be matched to it during the sync
+#[derive(Clone, Debug)]
+pub struct Invoice {
+    // Unique random ID
+    id: Hash,
+    asset: Hash,
+    // Amount requested in atomic units
+    amount: u64,
+    description: Option<String>,
+    created_at: TimestampSeconds,
+    expiry: Option<TimestampSeconds>,
+    payments: Vec<InvoicePayment>
+}
+
+impl Invoice {
+    pub fn new(asset: Hash, amount: u64, description: Option<String>, created_at: TimestampSeconds, expiry: Option<TimestampSeconds>) -> Result<Self, WalletError> {
+        if amount == 0 {
+            return Err(WalletError::InvalidInvoiceAmount)
+        }
+
+        // Empty description is considered as no description to be serialized correctly
+        let description = description.filter(|description| !description.is_empty());
+        if description.as_ref().is_some_and(|description| description.len() > MAX_DESCRIPTION_SIZE) {
+            return Err(WalletError::InvoiceDescriptionTooLong)
+        }
+
+        let mut id = [0; HASH_SIZE];
+        OsRng.fill_bytes(&mut id);
+
+        Ok(Self {
+            id: Hash::new(id),
+            asset,
+            amount,
+            description,
+            created_at,
+            expiry,
+            payments: Vec::new()
+        })
+    }
+
+    pub fn get_id(&self) -> &Hash {
+        &self.id
+    }
+
+    pub fn get_asset(&self) -> &Hash {
+        &self.asset
+    }
+
+    pub fn get_amount(&self) -> u64 {
+        self.amount
+    }
+
+    pub fn get_description(&self) -> Option<&String> {
+        self.description.as_ref()
+    }
+
+    pub fn get_created_at(&self) -> TimestampSeconds {
+        self.created_at
+    }
+
+    pub fn get_expiry(&self) -> Option<TimestampSeconds> {
+        self.expiry
+    }
+
+    pub fn get_payments(&self) -> &Vec<InvoicePayment> {
+        &self.payments
+    }
+
+    // Data to integrate in the address shared with the payer
+    pub fn get_integrated_data(&self) -> DataElement {
+        let mut fields = HashMap::new();
+        fields.insert(DataValue::String(INVOICE_ID_KEY.to_owned()), DataElement::Value(DataValue::Hash(self.id.clone())));
+        DataElement::Fields(fields)
+    }
+
+    pub fn get_amount_paid(&self) -> u64 {
+        self.payments.iter().fold(0, |total, payment| total.saturating_add(payment.amount))
+    }
+
+    pub fn is_expired(&self, now: TimestampSeconds) -> bool {
+        self.expiry.is_some_and(|expiry| now >= expiry)
+    }
+
+    // A payment received after the expiry is still counted
+    pub fn get_status(&self, now: TimestampSeconds) -> InvoiceStatus {
+        let paid = self.get_amount_paid();
+        if paid > self.amount {
+            InvoiceStatus::Overpaid
+        } else if paid == self.amount {
+            InvoiceStatus::Paid
+        } else if self.is_expired(now) {
+            InvoiceStatus::Expired
+        } else if paid > 0 {
+            InvoiceStatus::PartiallyPaid
+        } else {
+            InvoiceStatus::Pending
+        }
+    }
+
+    // Returns false if this transaction was already recorded
+    pub fn add_payment(&mut self, payment: InvoicePayment) -> bool {
+        if self.payments.iter().any(|p| p.hash == payment.hash) {
+            return false
+        }
+
+        self.payments.push(payment);
+        true
+    }
+
+    // Remove the payments that are no longer in the chain after a rescan or a DAG reorg
+    // Returns true if a payment was removed
+    pub fn remove_payments_above_topoheight(&mut self, topoheight: u64) -> bool {
+        let len = self.payments.len();
+        self.payments.retain(|payment| payment.topoheight <= topoheight);
+        self.payments.len() != len
+    }
+
+    pub fn to_entry(&self, public_key: &PublicKey, mainnet: bool, now: TimestampSeconds) -> InvoiceEntry {
+        InvoiceEntry {
+            id: self.id.clone(),
+            address: public_key.clone().to_address_with(mainnet, self.get_integrated_data()),
+            asset: self.asset.clone(),
+            amount: self.amount,
+            description: self.description.clone(),
+            created_at: self.created_at,
+            expiry: self.expiry,
+            amount_paid: self.get_amount_paid(),
+            status: self.get_status(now),
+            payments: self.payments.iter().map(|payment| InvoicePaymentEntry {
+                hash: payment.hash.clone(),
+                topoheight: payment.topoheight,
+                amount: payment.amount
+            }).collect()
+        }
+    }
+}
+
+impl Serializer for Invoice {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_hash(&self.id);
+        writer.write_hash(&self.asset);
+        writer.write_u64(&self.amount);
+        writer.write_optional_string(&self.description);
+        writer.write_u64(&self.created_at);
+        self.expiry.write(writer);
+
+        writer.write_u16(self.payments.len() as u16);
+        for payment in &self.payments {
+            payment.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let id = reader.read_hash()?;
+        let asset = reader.read_hash()?;
+        let amount = reader.read_u64()?;
+        let description = reader.read_optional_string()?;
+        let created_at = reader.read_u64()?;
+        let expiry = Option::read(reader)?;
+
+        let count = reader.read_u16()?;
+        let mut payments = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            payments.push(InvoicePayment::read(reader)?);
+        }
+
+        Ok(Self {
+            id,
+            asset,
+            amount,
+            description,
+            created_at,
+            expiry,
+            payments
+        })
+    }
+}
+
+// Retrieve the invoice ID from the extra data of a transfer
+pub fn get_invoice_id(data: &DataElement) -> Option<&Hash> {
+    data.get_value_by_string_key(INVOICE_ID_KEY.to_owned(), ValueType::Hash)?
+        .as_hash()
+        .ok()
+}
+
+// Record the payments to our invoices made by an incoming transaction
+// Transfers using another asset than the one of the invoice are ignored
+// Returns the invoices updated
+pub fn apply_payments(storage: &mut EncryptedStorage, entry: &TransactionEntry) -> Result<Vec<Invoice>> {
+    let EntryData::Incoming { transfers, .. } = entry.get_entry() else {
+        return Ok(Vec::new())
+    };
+
+    // Sum the transfers per invoice, several transfers can pay the same invoice
+    let mut amounts: HashMap<&Hash, Vec<(&Hash, u64)>> = HashMap::new();
+    for transfer in transfers {
+        let Some(id) = transfer.get_extra_data().as_ref().and_then(get_invoice_id) else {
+            continue
+        };
+
+        // Amount can't be known in watch-only mode
+        if let Some(amount) = transfer.get_amount() {
+            amounts.entry(id).or_default().push((transfer.get_asset(), amount));
+        }
+    }
+
+    let mut updated = Vec::new();
+    for (id, transfers) in amounts {
+        if !storage.has_invoice(id)? {
+            continue
+        }
+
+        let mut invoice = storage.get_invoice(id)?;
+        let amount = transfers.into_iter()
+            .filter(|(asset, _)| *asset == invoice.get_asset())
+            .fold(0u64, |total, (_, amount)| total.saturating_add(amount));
+
+        if amount == 0 {
+            continue
+        }
+
+        if invoice.add_payment(InvoicePayment::new(entry.get_hash().clone(), entry.get_topoheight(), amount)) {
+            storage.set_invoice(&invoice)?;
+            updated.push(invoice);
+        }
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::{
+        api::wallet::InvoiceStatus,
+        config::XELIS_ASSET,
+        crypto::Hash
+    };
+    use super::{get_invoice_id, Invoice, InvoicePayment};
+
+    #[test]
+    fn test_invoice_status() {
+        let mut invoice = Invoice::new(XELIS_ASSET, 100, None, 0, Some(1000)).unwrap();
+        assert_eq!(get_invoice_id(&invoice.get_integrated_data()), Some(invoice.get_id()));
+        assert_eq!(invoice.get_status(0), InvoiceStatus::Pending);
+        assert_eq!(invoice.get_status(1000), InvoiceStatus::Expired);
+
+        assert!(invoice.add_payment(InvoicePayment::new(Hash::zero(), 10, 40)));
+        assert!(!invoice.add_payment(InvoicePayment::new(Hash::zero(), 10, 40)));
+        assert_eq!(invoice.get_status(0), InvoiceStatus::PartiallyPaid);
+
+        assert!(invoice.add_payment(InvoicePayment::new(Hash::max(), 20, 70)));
+        assert_eq!(invoice.get_amount_paid(), 110);
+        assert_eq!(invoice.get_status(1000), InvoiceStatus::Overpaid);
+
+        assert!(invoice.remove_payments_above_topoheight(10));
+        assert_eq!(invoice.get_status(0), InvoiceStatus::PartiallyPaid);
+    }
+}
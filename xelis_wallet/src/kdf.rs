@@ -0,0 +1,196 @@
+use std::fmt;
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::{Deserialize, Serialize};
+use xelis_common::serializer::{Reader, ReaderError, Serializer, Writer};
+use crate::{
+    config::{
+        DEFAULT_KDF_ITERATIONS,
+        DEFAULT_KDF_MEMORY_COST,
+        DEFAULT_KDF_PARALLELISM,
+        PASSWORD_HASH_SIZE
+    },
+    wallet::WalletError
+};
+
+// Algorithm used to derive the key encrypting the master key from the password
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KdfAlgorithm {
+    Argon2d,
+    Argon2i,
+    Argon2id
+}
+
+impl KdfAlgorithm {
+    fn to_argon2(self) -> Algorithm {
+        match self {
+            Self::Argon2d => Algorithm::Argon2d,
+            Self::Argon2i => Algorithm::Argon2i,
+            Self::Argon2id => Algorithm::Argon2id
+        }
+    }
+}
+
+impl fmt::Display for KdfAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Argon2d => write!(f, "Argon2d"),
+            Self::Argon2i => write!(f, "Argon2i"),
+            Self::Argon2id => write!(f, "Argon2id")
+        }
+    }
+}
+
+impl Serializer for KdfAlgorithm {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(match self {
+            Self::Argon2d => 0,
+            Self::Argon2i => 1,
+            Self::Argon2id => 2
+        });
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(match reader.read_u8()? {
+            0 => Self::Argon2d,
+            1 => Self::Argon2i,
+            2 => Self::Argon2id,
+            _ => return Err(ReaderError::InvalidValue)
+        })
+    }
+}
+
+// Parameters of the password KDF, stored in the public storage of the wallet
+// Wallets created before they were stored use the default parameters
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    algorithm: KdfAlgorithm,
+    // Memory cost in KiB
+    memory_cost: u32,
+    iterations: u32,
+    parallelism: u32
+}
+
+impl KdfParams {
+    pub fn new(algorithm: KdfAlgorithm, memory_cost: u32, iterations: u32, parallelism: u32) -> Result<Self, WalletError> {
+        let params = Self {
+            algorithm,
+            memory_cost,
+            iterations,
+            parallelism
+        };
+        // Verify that argon2 accepts these parameters
+        params.build()?;
+
+        Ok(params)
+    }
+
+    pub fn get_algorithm(&self) -> KdfAlgorithm {
+        self.algorithm
+    }
+
+    pub fn get_memory_cost(&self) -> u32 {
+        self.memory_cost
+    }
+
+    pub fn get_iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn get_parallelism(&self) -> u32 {
+        self.parallelism
+    }
+
+    // Check if one of the cost parameters is lower than the other parameters
+    pub fn is_weaker_than(&self, other: &Self) -> bool {
+        self.memory_cost < other.memory_cost || self.iterations < other.iterations
+    }
+
+    fn build(&self) -> Result<Argon2<'static>, WalletError> {
+        let params = Params::new(self.memory_cost, self.iterations, self.parallelism, Some(PASSWORD_HASH_SIZE))
+            .map_err(|e| WalletError::AlgorithmHashingError(e.to_string()))?;
+        Ok(Argon2::new(self.algorithm.to_argon2(), Version::V0x13, params))
+    }
+
+    // Derive the password-based key using these parameters
+    pub fn hash_password(&self, password: String, salt: &[u8]) -> Result<[u8; PASSWORD_HASH_SIZE], WalletError> {
+        let mut output = [0; PASSWORD_HASH_SIZE];
+        self.build()?.hash_password_into(password.as_bytes(), salt, &mut output).map_err(|e| WalletError::AlgorithmHashingError(e.to_string()))?;
+        Ok(output)
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            algorithm: KdfAlgorithm::Argon2id,
+            memory_cost: DEFAULT_KDF_MEMORY_COST,
+            iterations: DEFAULT_KDF_ITERATIONS,
+            parallelism: DEFAULT_KDF_PARALLELISM
+        }
+    }
+}
+
+impl fmt::Display for KdfParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} with {} KiB, {} iterations and {} lanes", self.algorithm, self.memory_cost, self.iterations, self.parallelism)
+    }
+}
+
+impl Serializer for KdfParams {
+    fn write(&self, writer: &mut Writer) {
+        self.algorithm.write(writer);
+        writer.write_u32(&self.memory_cost);
+        writer.write_u32(&self.iterations);
+        writer.write_u32(&self.parallelism);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            algorithm: KdfAlgorithm::read(reader)?,
+            memory_cost: reader.read_u32()?,
+            iterations: reader.read_u32()?,
+            parallelism: reader.read_u32()?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::serializer::Serializer;
+    use super::{KdfAlgorithm, KdfParams};
+
+    #[test]
+    fn test_serialization() {
+        for params in [KdfParams::default(), KdfParams::new(KdfAlgorithm::Argon2d, 8 * 1024, 3, 2).unwrap()] {
+            let bytes = params.to_bytes();
+            assert_eq!(bytes.len(), 13);
+            assert_eq!(KdfParams::from_bytes(&bytes).unwrap(), params);
+        }
+
+        // Unknown algorithm
+        let mut bytes = KdfParams::default().to_bytes();
+        bytes[0] = 3;
+        assert!(KdfParams::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert!(KdfParams::new(KdfAlgorithm::Argon2id, 8 * 1024, 0, 1).is_err());
+        assert!(KdfParams::new(KdfAlgorithm::Argon2id, 8 * 1024, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_hash_password_with_params() {
+        let salt = [1; 32];
+        let params = KdfParams::new(KdfAlgorithm::Argon2i, 8 * 1024, 2, 2).unwrap();
+        let hash = params.hash_password("password".to_owned(), &salt).unwrap();
+        assert_eq!(hash, params.hash_password("password".to_owned(), &salt).unwrap());
+
+        // Each parameter changes the derived key
+        assert_ne!(hash, KdfParams::default().hash_password("password".to_owned(), &salt).unwrap());
+        assert_ne!(hash, KdfParams::new(KdfAlgorithm::Argon2id, 8 * 1024, 2, 2).unwrap().hash_password("password".to_owned(), &salt).unwrap());
+        assert_ne!(hash, KdfParams::new(KdfAlgorithm::Argon2i, 8 * 1024, 3, 2).unwrap().hash_password("password".to_owned(), &salt).unwrap());
+        assert_ne!(hash, params.hash_password("password".to_owned(), &[2; 32]).unwrap());
+    }
+}
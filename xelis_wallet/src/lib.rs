@@ -7,6 +7,16 @@ pub mod network_handler;
 pub mod entry;
 pub mod mnemonics;
 pub mod transaction_builder;
+pub mod offline;
+pub mod payout;
+pub mod backup;
+pub mod export;
+pub mod address_book;
+pub mod pending;
+pub mod kdf;
+pub mod invoice;
+pub mod message;
+pub mod balance_history;
 
 #[cfg(feature = "api_server")]
 pub mod api;
\ No newline at end of file
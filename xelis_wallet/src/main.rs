@@ -1,4 +1,8 @@
+mod cli;
+
 use std::{
+    collections::HashMap,
+    fs,
     ops::ControlFlow,
     path::Path,
     sync::Arc,
@@ -9,6 +13,7 @@ use fern::colors::Color;
 use log::{error, info};
 use clap::Parser;
 use xelis_common::{
+    api::wallet::{ExportFormat, ExportTransactionsParams, PayoutEntry, PayoutStatus},
     async_handler,
     config::{
         COIN_DECIMALS,
@@ -18,7 +23,10 @@ use xelis_common::{
     crypto::{
         ecdlp,
         Address,
-        Hashable
+        Hash,
+        Hashable,
+        PaymentRequest,
+        PAYMENT_REQUEST_SCHEME
     },
     network::Network,
     prompt::{
@@ -39,6 +47,7 @@ use xelis_common::{
         PromptError
     },
     serializer::Serializer,
+    time::get_current_time_in_seconds,
     transaction::{
         builder::{FeeBuilder, TransactionTypeBuilder, TransferBuilder},
         BurnPayload,
@@ -49,9 +58,23 @@ use xelis_common::{
         format_xelis
     }
 };
+use cli::WalletCommand;
 use xelis_wallet::{
+    address_book::Contact,
     wallet::Wallet,
-    config::{DEFAULT_DAEMON_ADDRESS, DIR_PATH}
+    config::{
+        DEFAULT_DAEMON_ADDRESS,
+        DEFAULT_KDF_ITERATIONS,
+        DEFAULT_KDF_MEMORY_COST,
+        DEFAULT_KDF_PARALLELISM,
+        DIR_PATH
+    },
+    daemon_api::DaemonAPI,
+    export,
+    kdf::{KdfAlgorithm, KdfParams},
+    message::Message,
+    offline::UnsignedTransactionRequest,
+    payout::{parse_payout_csv, process_payouts}
 };
 
 #[cfg(feature = "api_server")]
@@ -59,6 +82,7 @@ use {
     xelis_wallet::{
         api::{
             AuthConfig,
+            ApplicationPolicy,
             PermissionResult,
             AppStateShared
         },
@@ -100,6 +124,12 @@ pub struct Config {
     /// Daemon address to use
     #[clap(long, default_value_t = String::from(DEFAULT_DAEMON_ADDRESS))]
     daemon_address: String,
+    /// Backup daemon address to use
+    /// 
+    /// Can be set several times, they are used by order of preference
+    /// when the daemon is unreachable, lagging or on another chain.
+    #[clap(long)]
+    backup_daemon_address: Vec<String>,
     /// Disable online mode
     #[clap(long)]
     offline_mode: bool,
@@ -133,9 +163,33 @@ pub struct Config {
     /// Password used to open wallet
     #[clap(long)]
     password: Option<String>,
+    /// File containing the password used to open wallet
+    /// 
+    /// Only used by subcommands, XELIS_WALLET_PASSWORD env variable can be used instead.
+    #[clap(long)]
+    password_file: Option<String>,
     /// Restore wallet using seed
     #[clap(long)]
     seed: Option<String>,
+    /// Passphrase used with the seed to derive the wallet key
+    /// 
+    /// Same seed with a different passphrase gives a different wallet.
+    #[clap(long)]
+    passphrase: Option<String>,
+    /// Memory cost in KiB of the password KDF (Argon2id) used for new wallets
+    #[clap(long, default_value_t = DEFAULT_KDF_MEMORY_COST)]
+    kdf_memory_cost: u32,
+    /// Iterations of the password KDF used for new wallets
+    #[clap(long, default_value_t = DEFAULT_KDF_ITERATIONS)]
+    kdf_iterations: u32,
+    /// Parallelism of the password KDF used for new wallets
+    #[clap(long, default_value_t = DEFAULT_KDF_PARALLELISM)]
+    kdf_parallelism: u32,
+    /// Create a watch-only wallet for this address
+    /// 
+    /// It can sync the account but can't decrypt amounts or sign transactions.
+    #[clap(long)]
+    watch_address: Option<String>,
     /// Network selected for chain
     #[clap(long, value_enum, default_value_t = Network::Mainnet)]
     network: Network,
@@ -146,7 +200,10 @@ pub struct Config {
     /// XSWD Server configuration
     #[cfg(feature = "api_server")]
     #[clap(long)]
-    enable_xswd: bool
+    enable_xswd: bool,
+    /// Run a single command without the interactive prompt
+    #[command(subcommand)]
+    command: Option<WalletCommand>
 }
 
 /// This struct is used to log the progress of the table generation
@@ -161,7 +218,12 @@ impl ecdlp::ProgressTableGenerationReportFunction for LogProgressTableGeneration
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let config: Config = Config::parse();
+    let mut config: Config = Config::parse();
+    // Non-interactive mode: run the command and exit with its code
+    if let Some(command) = config.command.take() {
+        std::process::exit(cli::run(config, command).await);
+    }
+
     let prompt = Prompt::new(config.log_level, &config.logs_path, &config.filename_log, config.disable_file_logging)?;
 
     #[cfg(feature = "api_server")]
@@ -188,9 +250,17 @@ async fn main() -> Result<()> {
 
     let command_manager = CommandManager::new(prompt.clone());
     command_manager.store_in_context(config.network)?;
+    // KDF parameters used for the wallets created
+    let kdf_params = KdfParams::new(KdfAlgorithm::Argon2id, config.kdf_memory_cost, config.kdf_iterations, config.kdf_parallelism)
+        .context("Invalid KDF parameters")?;
+    command_manager.store_in_context(kdf_params)?;
 
     command_manager.register_default_commands()?;
 
+    // Offline signing commands don't require a wallet to be opened
+    command_manager.add_command(Command::with_optional_arguments("export_unsigned_transaction", "Export an unsigned transaction request for an address to be signed offline", vec![Arg::new("daemon_address", ArgType::String)], CommandHandler::Async(async_handler!(export_unsigned_transaction))))?;
+    command_manager.add_command(Command::with_optional_arguments("broadcast_signed_transaction", "Verify and broadcast a transaction signed offline", vec![Arg::new("daemon_address", ArgType::String)], CommandHandler::Async(async_handler!(broadcast_signed_transaction))))?;
+
     if let Some(path) = config.wallet_path {
         // read password from option or ask him
         let password = if let Some(password) = config.password {
@@ -203,9 +273,13 @@ async fn main() -> Result<()> {
         let wallet = if Path::new(&path).is_dir() {
             info!("Opening wallet {}", path);
             Wallet::open(path, password, config.network, precomputed_tables)?
+        } else if let Some(address) = config.watch_address {
+            info!("Creating a new watch-only wallet at {}", path);
+            let address = Address::from_string(&address).context("Invalid address to watch")?;
+            Wallet::create_watch_only(path, password, address, kdf_params, config.network, precomputed_tables)?
         } else {
             info!("Creating a new wallet at {}", path);
-            Wallet::create(path, password, config.seed, config.network, precomputed_tables)?
+            Wallet::create(path, password, config.seed, config.passphrase, kdf_params, config.network, precomputed_tables)?
         };
 
         apply_config(&wallet, #[cfg(feature = "api_server")] &prompt).await;
@@ -214,6 +288,8 @@ async fn main() -> Result<()> {
         command_manager.add_command(Command::new("open", "Open a wallet", CommandHandler::Async(async_handler!(open_wallet))))?;
         command_manager.add_command(Command::new("create", "Create a new wallet", CommandHandler::Async(async_handler!(create_wallet))))?;
         command_manager.add_command(Command::new("recover", "Recover a wallet using a seed", CommandHandler::Async(async_handler!(recover_wallet))))?;
+        command_manager.add_command(Command::new("watch", "Create a watch-only wallet from an address", CommandHandler::Async(async_handler!(watch_wallet))))?;
+        command_manager.add_command(Command::new("import_backup", "Restore a wallet from a backup file", CommandHandler::Async(async_handler!(import_backup))))?;
 
         // Display available commands
         command_manager.display_commands()?;
@@ -252,8 +328,8 @@ async fn xswd_handler(mut receiver: UnboundedReceiver<XSWDEvent>, prompt: Sharea
                     }
                 });
             },
-            XSWDEvent::RequestPermission(app_state, request, callback) => {
-                let res = xswd_handle_request_permission(&prompt, app_state, request).await;
+            XSWDEvent::RequestPermission(app_state, request, policy, callback) => {
+                let res = xswd_handle_request_permission(&prompt, app_state, request, policy).await;
                 if callback.send(res).is_err() {
                     error!("Error while sending permission response back to XSWD");
                 }
@@ -277,7 +353,7 @@ async fn xswd_handle_request_application(prompt: &ShareablePrompt, app_state: Ap
 }
 
 #[cfg(feature = "api_server")]
-async fn xswd_handle_request_permission(prompt: &ShareablePrompt, app_state: AppStateShared, request: RpcRequest) -> Result<PermissionResult, Error> {
+async fn xswd_handle_request_permission(prompt: &ShareablePrompt, app_state: AppStateShared, request: RpcRequest, policy: ApplicationPolicy) -> Result<PermissionResult, Error> {
     let params = if let Some(params) = request.params {
         params.to_string()
     } else {
@@ -285,10 +361,11 @@ async fn xswd_handle_request_permission(prompt: &ShareablePrompt, app_state: App
     };
 
     let message = format!(
-        "XSWD: Request from {}: {}\r\nParams: {}\r\nDo you want to allow this request ?\r\n([A]llow / [D]eny / [AA] Always Allow / [AD] Always Deny): ",
+        "XSWD: Request from {}: {}\r\nParams: {}\r\nPolicy: {}\r\nDo you want to allow this request ?\r\n([A]llow / [D]eny / [AA] Always Allow / [AD] Always Deny): ",
         app_state.get_name(),
         request.method,
-        params
+        params,
+        policy
     );
 
     let answer = prompt.read_valid_str_value(colorize_string(Color::Blue, &message), vec!["a", "d", "aa", "ad"]).await?;
@@ -307,7 +384,9 @@ async fn apply_config(wallet: &Arc<Wallet>, #[cfg(feature = "api_server")] promp
 
     if !config.offline_mode {
         info!("Trying to connect to daemon at '{}'", config.daemon_address);
-        if let Err(e) = wallet.set_online_mode(&config.daemon_address).await {
+        let mut daemon_addresses = vec![config.daemon_address];
+        daemon_addresses.extend(config.backup_daemon_address);
+        if let Err(e) = wallet.set_online_mode_with_failover(daemon_addresses).await {
             error!("Couldn't connect to daemon: {}", e);
             info!("You can activate online mode using 'online_mode [daemon_address]'");
         } else {
@@ -355,20 +434,39 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
     command_manager.remove_command("open")?;
     command_manager.remove_command("recover")?;
     command_manager.remove_command("create")?;
+    command_manager.remove_command("watch")?;
+    command_manager.remove_command("import_backup")?;
 
     // Add wallet commands
-    command_manager.add_command(Command::new("change_password", "Set a new password to open your wallet", CommandHandler::Async(async_handler!(change_password))))?;
+    command_manager.add_command(Command::with_optional_arguments("change_password", "Set a new password to open your wallet, KDF parameters can be upgraded at the same time", vec![Arg::new("memory_cost", ArgType::Number), Arg::new("iterations", ArgType::Number)], CommandHandler::Async(async_handler!(change_password))))?;
+    command_manager.add_command(Command::new("export_backup", "Export your wallet in a single encrypted backup file", CommandHandler::Async(async_handler!(export_backup))))?;
     command_manager.add_command(Command::with_optional_arguments("transfer", "Send asset to a specified address", vec![Arg::new("asset", ArgType::Hash)], CommandHandler::Async(async_handler!(transfer))))?;
+    command_manager.add_command(Command::new("batch_payout", "Pay all recipients from a CSV or JSON file and write a resumable report", CommandHandler::Async(async_handler!(batch_payout))))?;
     command_manager.add_command(Command::with_required_arguments("burn", "Burn amount of asset", vec![Arg::new("asset", ArgType::Hash), Arg::new("amount", ArgType::Number)], CommandHandler::Async(async_handler!(burn))))?;
     command_manager.add_command(Command::new("display_address", "Show your wallet address", CommandHandler::Async(async_handler!(display_address))))?;
+    command_manager.add_command(Command::new("payment_request", "Create a payment request to share with a payer", CommandHandler::Async(async_handler!(payment_request))))?;
     command_manager.add_command(Command::with_optional_arguments("balance", "List all non-zero balances or show the selected one", vec![Arg::new("asset", ArgType::Hash)], CommandHandler::Async(async_handler!(balance))))?;
+    command_manager.add_command(Command::with_arguments("balance_history", "Show the running balance of an asset after each transaction", vec![Arg::new("asset", ArgType::Hash)], vec![Arg::new("min_topoheight", ArgType::Number), Arg::new("max_topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(balance_history))))?;
     command_manager.add_command(Command::with_optional_arguments("history", "Show all your transactions", vec![Arg::new("page", ArgType::Number)], CommandHandler::Async(async_handler!(history))))?;
+    command_manager.add_command(Command::new("pending_transactions", "Show your transactions not yet included in a block", CommandHandler::Async(async_handler!(pending_transactions))))?;
+    command_manager.add_command(Command::new("clear_failed_transactions", "Stop tracking your failed transactions", CommandHandler::Async(async_handler!(clear_failed_transactions))))?;
+    command_manager.add_command(Command::with_arguments("export_history", "Export your transactions in a CSV or JSON file", vec![Arg::new("format", ArgType::String)], vec![Arg::new("min_topoheight", ArgType::Number), Arg::new("max_topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(export_history))))?;
     command_manager.add_command(Command::with_optional_arguments("online_mode", "Set your wallet in online mode", vec![Arg::new("daemon_address", ArgType::String)], CommandHandler::Async(async_handler!(online_mode))))?;
     command_manager.add_command(Command::new("offline_mode", "Set your wallet in offline mode", CommandHandler::Async(async_handler!(offline_mode))))?;
     command_manager.add_command(Command::with_optional_arguments("rescan", "Rescan balance and transactions", vec![Arg::new("topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(rescan))))?;
     command_manager.add_command(Command::with_optional_arguments("seed", "Show seed of selected language", vec![Arg::new("language", ArgType::Number)], CommandHandler::Async(async_handler!(seed))))?;
     command_manager.add_command(Command::new("nonce", "Show current nonce", CommandHandler::Async(async_handler!(nonce))))?;
     command_manager.add_command(Command::new("set_nonce", "Set new nonce", CommandHandler::Async(async_handler!(set_nonce))))?;
+    command_manager.add_command(Command::new("create_account", "Create a new account derived from your seed", CommandHandler::Async(async_handler!(create_account))))?;
+    command_manager.add_command(Command::new("list_accounts", "List all accounts derived from your seed", CommandHandler::Async(async_handler!(list_accounts))))?;
+    command_manager.add_command(Command::with_required_arguments("switch_account", "Switch to another account", vec![Arg::new("index", ArgType::Number)], CommandHandler::Async(async_handler!(switch_account))))?;
+    command_manager.add_command(Command::with_required_arguments("add_contact", "Add a contact in your address book", vec![Arg::new("label", ArgType::String)], CommandHandler::Async(async_handler!(add_contact))))?;
+    command_manager.add_command(Command::new("list_contacts", "List all contacts of your address book", CommandHandler::Async(async_handler!(list_contacts))))?;
+    command_manager.add_command(Command::with_required_arguments("remove_contact", "Remove a contact from your address book", vec![Arg::new("label", ArgType::String)], CommandHandler::Async(async_handler!(remove_contact))))?;
+    command_manager.add_command(Command::with_required_arguments("payment_proof", "Show the payment proof of a transfer you sent", vec![Arg::new("hash", ArgType::Hash), Arg::new("index", ArgType::Number)], CommandHandler::Async(async_handler!(payment_proof))))?;
+    command_manager.add_command(Command::new("send_message", "Send an encrypted message to an address or a contact", CommandHandler::Async(async_handler!(send_message))))?;
+    command_manager.add_command(Command::new("inbox", "Show the messages received per sender", CommandHandler::Async(async_handler!(inbox))))?;
+    command_manager.add_command(Command::new("sign_unsigned_transaction", "Sign an unsigned transaction request exported by an online process", CommandHandler::Async(async_handler!(sign_unsigned_transaction))))?;
 
     #[cfg(feature = "api_server")]
     {
@@ -380,6 +478,9 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
         ], CommandHandler::Async(async_handler!(start_rpc_server))))?;
 
         command_manager.add_command(Command::new("start_xswd", "Start the XSWD Server",  CommandHandler::Async(async_handler!(start_xswd))))?;
+        command_manager.add_command(Command::new("list_xswd_apps", "List all applications approved through XSWD", CommandHandler::Async(async_handler!(list_xswd_apps))))?;
+        command_manager.add_command(Command::with_required_arguments("revoke_xswd_app", "Revoke an application approved through XSWD", vec![Arg::new("id", ArgType::String)], CommandHandler::Async(async_handler!(revoke_xswd_app))))?;
+        command_manager.add_command(Command::with_required_arguments("set_xswd_policy", "Set the spend limits and restrictions of an XSWD application", vec![Arg::new("id", ArgType::String)], CommandHandler::Async(async_handler!(set_xswd_policy))))?;
 
         // Stop API Server (RPC or XSWD)
         command_manager.add_command(Command::new("stop_api_server", "Stop the API (XSWD/RPC) Server", CommandHandler::Async(async_handler!(stop_api_server))))?;
@@ -409,10 +510,15 @@ async fn prompt_message_builder(_: &Prompt, command_manager: Option<&CommandMana
                 prompt::colorize_str(Color::Yellow, "TopoHeight"),
                 prompt::colorize_string(Color::Green, &format!("{}", storage.get_synced_topoheight().unwrap_or(0)))
             );
+            let balance = if wallet.is_watch_only() {
+                "encrypted".to_owned()
+            } else {
+                format_xelis(storage.get_plaintext_balance_for(&XELIS_ASSET).await.unwrap_or(0))
+            };
             let balance = format!(
                 "{}: {}",
                 prompt::colorize_str(Color::Yellow, "Balance"),
-                prompt::colorize_string(Color::Green, &format_xelis(storage.get_plaintext_balance_for(&XELIS_ASSET).await.unwrap_or(0))),
+                prompt::colorize_string(Color::Green, &balance),
             );
             let status = if wallet.is_online().await {
                 prompt::colorize_str(Color::Green, "Online")
@@ -515,11 +621,16 @@ async fn create_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(
         return Ok(())
     }
 
+    let Some(passphrase) = read_passphrase(manager).await? else {
+        return Ok(())
+    };
+
     let wallet = {
         let context = manager.get_context().lock()?;
         let network = context.get::<Network>()?;
+        let kdf_params = context.get::<KdfParams>()?;
         let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
-        Wallet::create(dir, password, None, *network, precomputed_tables)?
+        Wallet::create(dir, password, None, Some(passphrase), *kdf_params, *network, precomputed_tables)?
     };
  
     manager.message("Wallet sucessfully created");
@@ -527,7 +638,7 @@ async fn create_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(
 
     // Display the seed in prompt
     {
-        let seed = wallet.get_seed(0)?; // TODO language index
+        let seed = wallet.get_seed(0).await?; // TODO language index
         prompt.read_input(format!("Seed: {}\r\nPress ENTER to continue", seed), false)
             .await.context("Error while displaying seed")?;
     }
@@ -576,12 +687,16 @@ async fn recover_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<
         return Ok(())
     }
 
+    let Some(passphrase) = read_passphrase(manager).await? else {
+        return Ok(())
+    };
 
     let wallet = {
         let context = manager.get_context().lock()?;
         let network = context.get::<Network>()?;
+        let kdf_params = context.get::<KdfParams>()?;
         let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
-        Wallet::create(dir, password, Some(seed), *network, precomputed_tables)?
+        Wallet::create(dir, password, Some(seed), Some(passphrase), *kdf_params, *network, precomputed_tables)?
     };
 
     manager.message("Wallet sucessfully recovered");
@@ -592,13 +707,179 @@ async fn recover_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<
     Ok(())
 }
 
+// Ask the optional passphrase used with the seed
+// Returns None if the confirmation doesn't match
+async fn read_passphrase(manager: &CommandManager) -> Result<Option<String>, CommandError> {
+    let prompt = manager.get_prompt();
+    let passphrase = prompt.read_input("Passphrase (leave empty for none): ".into(), true)
+        .await.context("Error while reading passphrase")?;
+
+    if !passphrase.is_empty() {
+        let confirm_passphrase = prompt.read_input("Confirm Passphrase: ".into(), true)
+            .await.context("Error while reading passphrase")?;
+
+        if passphrase != confirm_passphrase {
+            manager.message("Confirm passphrase doesn't match passphrase");
+            return Ok(None)
+        }
+
+        manager.warn("The passphrase will be required with the seed to recover this wallet");
+    }
+
+    Ok(Some(passphrase))
+}
+
+// Create a watch-only wallet from an address
+async fn watch_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+
+    let address = prompt.read_input("Address to watch: ".into(), false)
+        .await.context("Error while reading address")?;
+    let address = match Address::from_string(&address) {
+        Ok(address) => address,
+        Err(e) => {
+            manager.error(format!("Invalid address: {}", e));
+            return Ok(())
+        }
+    };
+
+    let name = prompt.read_input("Wallet name: ".into(), false)
+        .await.context("Error while reading wallet name")?;
+
+    if name.is_empty() {
+        manager.error("Wallet name cannot be empty");
+        return Ok(())
+    }
+
+    let dir = format!("{}{}", DIR_PATH, name);
+    // check if it doesn't exists yet
+    if Path::new(&dir).is_dir() {
+        manager.message("Wallet already exist with this name!");
+        return Ok(())
+    }
+
+    // ask and verify password
+    let password = prompt.read_input("Password: ".into(), true)
+        .await.context("Error while reading password")?;
+    let confirm_password = prompt.read_input("Confirm Password: ".into(), true)
+        .await.context("Error while reading password")?;
+
+    if password != confirm_password {
+        manager.message("Confirm password doesn't match password");
+        return Ok(())
+    }
+
+    let wallet = {
+        let context = manager.get_context().lock()?;
+        let network = context.get::<Network>()?;
+        let kdf_params = context.get::<KdfParams>()?;
+        let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
+        Wallet::create_watch_only(dir, password, address, *kdf_params, *network, precomputed_tables)?
+    };
+
+    manager.message("Watch-only wallet sucessfully created");
+    apply_config(&wallet, #[cfg(feature = "api_server")] prompt).await;
+
+    setup_wallet_command_manager(wallet, manager).await?;
+
+    Ok(())
+}
+
+// Restore a wallet from a backup file
+async fn import_backup(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+
+    let path = prompt.read_input("Backup file: ".into(), false)
+        .await.context("Error while reading backup file")?;
+    let content = fs::read(&path).context("Error while reading backup file")?;
+
+    let name = prompt.read_input("Wallet name: ".into(), false)
+        .await.context("Error while reading wallet name")?;
+
+    if name.is_empty() {
+        manager.error("Wallet name cannot be empty");
+        return Ok(())
+    }
+
+    let dir = format!("{}{}", DIR_PATH, name);
+    // check if it doesn't exists yet
+    if Path::new(&dir).is_dir() {
+        manager.message("Wallet already exist with this name!");
+        return Ok(())
+    }
+
+    let password = prompt.read_input("Password of the backup: ".into(), true)
+        .await.context("Error while reading password")?;
+
+    let wallet = {
+        let context = manager.get_context().lock()?;
+        let network = context.get::<Network>()?;
+        let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
+        Wallet::import_backup(dir, password, &content, *network, precomputed_tables)?
+    };
+
+    manager.message("Wallet sucessfully restored from backup");
+    apply_config(&wallet, #[cfg(feature = "api_server")] prompt).await;
+
+    setup_wallet_command_manager(wallet, manager).await?;
+
+    Ok(())
+}
+
+// Export the wallet in a backup file encrypted with the wallet password
+async fn export_backup(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let password = prompt.read_input("Password: ".into(), true)
+        .await.context("Error while reading password")?;
+    let path = prompt.read_input("Output file: ".into(), false)
+        .await.context("Error while reading output file")?;
+
+    let content = wallet.export_backup(password).await.context("Error while exporting backup")?;
+    fs::write(&path, content).context("Error while writing backup file")?;
+    manager.message(format!("Backup written to {}, it can be restored using the current wallet password", path));
+
+    Ok(())
+}
+
 // Change wallet password
-async fn change_password(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+async fn change_password(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
 
     let prompt = manager.get_prompt();
 
+    // New KDF parameters if the user wants to upgrade them
+    let kdf_params = if arguments.has_argument("memory_cost") || arguments.has_argument("iterations") {
+        let current = wallet.get_kdf_params().await?;
+        let memory_cost = if arguments.has_argument("memory_cost") {
+            u32::try_from(arguments.get_value("memory_cost")?.to_number()?)
+                .map_err(|_| CommandError::InvalidArgument("Memory cost is too high".to_string()))?
+        } else {
+            current.get_memory_cost()
+        };
+
+        let iterations = if arguments.has_argument("iterations") {
+            u32::try_from(arguments.get_value("iterations")?.to_number()?)
+                .map_err(|_| CommandError::InvalidArgument("Iterations count is too high".to_string()))?
+        } else {
+            current.get_iterations()
+        };
+
+        let params = KdfParams::new(current.get_algorithm(), memory_cost, iterations, current.get_parallelism())
+            .map_err(|e| CommandError::InvalidArgument(e.to_string()))?;
+        if params.is_weaker_than(&current) {
+            manager.warn(format!("New KDF parameters are weaker than the current ones: {}", current));
+        }
+
+        manager.message(format!("KDF parameters: {}", params));
+        Some(params)
+    } else {
+        None
+    };
+
     let old_password = prompt.read_input(prompt::colorize_str(Color::BrightRed, "Current Password: "), true)
         .await
         .context("Error while asking old password")?;
@@ -608,7 +889,7 @@ async fn change_password(manager: &CommandManager, _: ArgumentManager) -> Result
         .context("Error while asking new password")?;
 
     manager.message("Changing password...");
-    wallet.set_password(old_password, new_password).await?;
+    wallet.set_password(old_password, new_password, kdf_params).await?;
     manager.message("Your password has been changed!");
     Ok(())
 }
@@ -619,18 +900,39 @@ async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), Co
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
 
-    // read address
+    // read address, contact label or payment request
     let str_address = prompt.read_input(
-        prompt::colorize_str(Color::Green, "Address: "),
+        prompt::colorize_str(Color::Green, "Address, contact or payment request: "),
         false
     ).await.context("Error while reading address")?;
-    let address = Address::from_string(&str_address).context("Invalid address")?;
 
-    let asset = prompt.read_hash(
-        prompt::colorize_str(Color::Green, "Asset (default XELIS): ")
-    ).await.ok();
+    let (address, default_asset, request) = if str_address.starts_with(&format!("{}:", PAYMENT_REQUEST_SCHEME)) {
+        let request = PaymentRequest::from_uri(&str_address).context("Invalid payment request")?;
+        if request.is_expired(get_current_time_in_seconds()) {
+            return Err(CommandError::InvalidArgument("Payment request has expired".to_string()));
+        }
+        (request.address.clone(), request.asset.clone().unwrap_or(XELIS_ASSET), Some(request))
+    } else {
+        match Address::from_string(&str_address) {
+            Ok(address) => (address, XELIS_ASSET, None),
+            Err(_) => {
+                let contact = wallet.get_contact(&str_address).await.context("Invalid address or unknown contact")?;
+                let asset = contact.get_asset().cloned().unwrap_or(XELIS_ASSET);
+                (contact.get_address().clone(), asset, None)
+            }
+        }
+    };
 
-    let asset = asset.unwrap_or(XELIS_ASSET);
+    // Asset requested by the payment request can't be changed
+    let asset = if request.as_ref().is_some_and(|request| request.asset.is_some()) {
+        default_asset
+    } else {
+        let asset = prompt.read_hash(
+            prompt::colorize_string(Color::Green, &format!("Asset (default {}): ", if default_asset == XELIS_ASSET { "XELIS".to_owned() } else { default_asset.to_string() }))
+        ).await.ok();
+
+        asset.unwrap_or(default_asset)
+    };
 
     let (max_balance, decimals) = {
         let storage = wallet.get_storage().read().await;
@@ -639,12 +941,22 @@ async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), Co
         (balance, decimals)
     };
 
-    // read amount
-    let float_amount: f64 = prompt.read(
-        prompt::colorize_string(Color::Green, &format!("Amount (max: {}): ", format_coin(max_balance, decimals)))
-    ).await.context("Error while reading amount")?;
+    let amount = match request.as_ref().and_then(|request| request.amount) {
+        Some(amount) => amount,
+        None => {
+            // read amount
+            let float_amount: f64 = prompt.read(
+                prompt::colorize_string(Color::Green, &format!("Amount (max: {}): ", format_coin(max_balance, decimals)))
+            ).await.context("Error while reading amount")?;
 
-    let amount = (float_amount * 10u32.pow(decimals as u32) as f64) as u64;
+            (float_amount * 10u32.pow(decimals as u32) as f64) as u64
+        }
+    };
+
+    let extra_data = request.as_ref().and_then(PaymentRequest::get_extra_data);
+    if let Some(memo) = request.as_ref().and_then(|request| request.memo.as_ref()) {
+        manager.message(format!("Memo: {}", memo));
+    }
     manager.message(format!("Sending {} of {} to {}", format_coin(amount, decimals), asset, address.to_string()));
 
     if !prompt.ask_confirmation().await.context("Error while confirming action")? {
@@ -658,7 +970,7 @@ async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), Co
         destination: address,
         amount,
         asset,
-        extra_data: None
+        extra_data
     };
     let tx = wallet.create_transaction(TransactionTypeBuilder::Transfers(vec![transfer]), FeeBuilder::default()).await
         .context("Error while creating transaction")?;
@@ -667,6 +979,76 @@ async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), Co
     Ok(())
 }
 
+// Pay many recipients at once from a CSV (address,asset,amount[,extra data]) or JSON file
+// The JSON report written can be used again as input to resume the payout
+async fn batch_payout(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let path = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Payout file (CSV or JSON report): "),
+        false
+    ).await.context("Error while reading payout file")?;
+    let content = fs::read_to_string(&path).context("Error while reading payout file")?;
+    let mut entries: Vec<PayoutEntry> = if path.ends_with(".csv") {
+        parse_payout_csv(&content)?
+    } else {
+        serde_json::from_str(&content).context("Invalid payout file")?
+    };
+
+    let report_path = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Report file: "),
+        false
+    ).await.context("Error while reading report file")?;
+
+    // Show the total to pay for each asset
+    let mut totals: HashMap<Hash, (u64, usize)> = HashMap::new();
+    for entry in entries.iter().filter(|entry| entry.status.can_be_sent()) {
+        let (amount, count) = totals.entry(entry.asset.clone()).or_default();
+        *amount += entry.amount;
+        *count += 1;
+    }
+
+    // Their transaction is checked with the daemon before sending them again
+    let broadcasting = entries.iter().filter(|entry| matches!(entry.status, PayoutStatus::Broadcasting { .. })).count();
+    if broadcasting > 0 {
+        manager.warn(format!("{} recipients are in a transaction that may not have been broadcasted", broadcasting));
+    }
+
+    if totals.is_empty() && broadcasting == 0 {
+        manager.message("No pending entry to pay");
+        return Ok(())
+    }
+
+    {
+        let storage = wallet.get_storage().read().await;
+        for (asset, (amount, count)) in totals.iter() {
+            let decimals = storage.get_asset_decimals(asset).unwrap_or(COIN_DECIMALS);
+            manager.message(format!("Sending {} of {} to {} recipients", format_coin(*amount, decimals), asset, count));
+        }
+    }
+
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Payout has been aborted");
+        return Ok(())
+    }
+
+    process_payouts(wallet, &mut entries, FeeBuilder::default(), |entries| {
+        let content = serde_json::to_string_pretty(entries).context("Error while serializing payout report")?;
+        fs::write(&report_path, content).context("Error while writing payout report")?;
+        Ok(())
+    }).await.context("Error while processing payouts")?;
+
+    let submitted = entries.iter().filter(|entry| matches!(entry.status, PayoutStatus::Submitted(_))).count();
+    manager.message(format!("{} of {} recipients paid, report written to {}", submitted, entries.len(), report_path));
+    if submitted != entries.len() {
+        manager.warn("Some recipients were not paid, you can resume the payout using the report file");
+    }
+
+    Ok(())
+}
+
 async fn burn(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
     let amount = arguments.get_value("amount")?.to_number()?;
     let asset = arguments.get_value("asset")?.to_hash()?;
@@ -697,12 +1079,82 @@ async fn display_address(manager: &CommandManager, _: ArgumentManager) -> Result
     Ok(())
 }
 
+// Create a payment request URI for this wallet
+// The URI is displayed alone on its line so it can be copied as is or encoded in a QR code
+async fn payment_request(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let asset = prompt.read_hash(
+        prompt::colorize_str(Color::Green, "Asset (default XELIS): ")
+    ).await.ok();
+
+    let decimals = match &asset {
+        Some(asset) => {
+            let storage = wallet.get_storage().read().await;
+            storage.get_asset_decimals(asset).unwrap_or(COIN_DECIMALS)
+        },
+        None => COIN_DECIMALS
+    };
+
+    let str_amount = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Amount (optional): "),
+        false
+    ).await.context("Error while reading amount")?;
+    let amount = if str_amount.is_empty() {
+        None
+    } else {
+        let float_amount: f64 = str_amount.parse().context("Invalid amount")?;
+        Some((float_amount * 10u32.pow(decimals as u32) as f64) as u64)
+    };
+
+    let memo = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Memo (optional): "),
+        false
+    ).await.context("Error while reading memo")?;
+
+    let str_expiry = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Expire in minutes (optional): "),
+        false
+    ).await.context("Error while reading expiry")?;
+    let expiry = if str_expiry.is_empty() {
+        None
+    } else {
+        let minutes: u64 = str_expiry.parse().context("Invalid expiry")?;
+        Some(get_current_time_in_seconds() + minutes * 60)
+    };
+
+    let request = PaymentRequest::new(
+        wallet.get_address(),
+        asset,
+        amount,
+        Some(memo).filter(|memo| !memo.is_empty()),
+        expiry
+    ).context("Invalid payment request")?;
+    let uri = request.to_uri().context("Error while formatting payment request")?;
+
+    manager.message("Payment request:");
+    manager.message(uri);
+    Ok(())
+}
+
 // Show current balance for specified asset or list all non-zero balances
 async fn balance(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
     let storage = wallet.get_storage().read().await;
 
+    // Balances can't be decrypted without the private key
+    if wallet.is_watch_only() {
+        for asset in storage.get_assets().await? {
+            if storage.has_balance_for(&asset).await? {
+                manager.message(format!("Balance for asset {}: encrypted", asset));
+            }
+        }
+        return Ok(())
+    }
+
     if arguments.has_argument("asset") {
         let asset = arguments.get_value("asset")?.to_hash()?;
         let balance = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
@@ -720,6 +1172,101 @@ async fn balance(manager: &CommandManager, mut arguments: ArgumentManager) -> Re
     Ok(())
 }
 
+// Show the ledger of an asset and compare the computed balance to the wallet balance
+async fn balance_history(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let asset = arguments.get_value("asset")?.to_hash()?;
+    let min_topoheight = if arguments.has_argument("min_topoheight") {
+        Some(arguments.get_value("min_topoheight")?.to_number()?)
+    } else {
+        None
+    };
+    let max_topoheight = if arguments.has_argument("max_topoheight") {
+        Some(arguments.get_value("max_topoheight")?.to_number()?)
+    } else {
+        None
+    };
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let result = wallet.get_balance_history(Some(&asset), min_topoheight, max_topoheight).await
+        .context("Error while computing balance history")?;
+
+    let decimals = {
+        let storage = wallet.get_storage().read().await;
+        storage.get_asset_decimals(&asset).unwrap_or(0)
+    };
+
+    if result.history.is_empty() {
+        manager.message("No balance changes found");
+    } else {
+        manager.message(format!("{:<12} {:<64} {:>20} {:>20} {:>20}", "Topoheight", "Hash", "Received", "Sent", "Balance"));
+        for entry in result.history {
+            manager.message(format!("{:<12} {:<64} {:>20} {:>20} {:>20}",
+                entry.topoheight,
+                entry.hash,
+                format_coin(entry.received, decimals),
+                format_coin(entry.sent, decimals),
+                format_coin(entry.balance, decimals)
+            ));
+        }
+    }
+
+    for reconciliation in result.reconciliation {
+        let computed = format_coin(reconciliation.computed, decimals);
+        match reconciliation.balance {
+            Some(balance) if balance == reconciliation.computed => manager.message(format!("Computed balance matches the wallet balance: {}", computed)),
+            Some(balance) => manager.warn(format!("Computed balance {} doesn't match the wallet balance {}, history may be incomplete", computed, format_coin(balance, decimals))),
+            None => manager.message(format!("Computed balance: {} (wallet balance is encrypted)", computed))
+        }
+    }
+
+    Ok(())
+}
+
+// Export the transactions in a file for accounting
+async fn export_history(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let format = match arguments.get_value("format")?.to_string_value()?.to_lowercase().as_str() {
+        "csv" => ExportFormat::Csv,
+        "json" => ExportFormat::Json,
+        _ => return Err(CommandError::InvalidArgument("Format must be csv or json".to_string()))
+    };
+
+    let min_topoheight = if arguments.has_argument("min_topoheight") {
+        Some(arguments.get_value("min_topoheight")?.to_number()?)
+    } else {
+        None
+    };
+    let max_topoheight = if arguments.has_argument("max_topoheight") {
+        Some(arguments.get_value("max_topoheight")?.to_number()?)
+    } else {
+        None
+    };
+
+    let prompt = manager.get_prompt();
+    let path = prompt.read_input("Output file: ".into(), false)
+        .await.context("Error while reading output file")?;
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let params = ExportTransactionsParams {
+        format,
+        min_topoheight,
+        max_topoheight,
+        min_timestamp: None,
+        max_timestamp: None,
+        accept_incoming: true,
+        accept_outgoing: true,
+        accept_coinbase: true,
+        accept_burn: true
+    };
+
+    let content = export::export_transactions(wallet, &params).await.context("Error while exporting transactions")?;
+    fs::write(&path, content).context("Error while writing export file")?;
+    manager.message(format!("Transactions exported to {}", path));
+
+    Ok(())
+}
+
 // Show all transactions
 const TXS_PER_PAGE: usize = 10;
 async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
@@ -737,6 +1284,10 @@ async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Re
     let wallet: &Arc<Wallet> = context.get()?;
     let storage = wallet.get_storage().read().await;
     let mut transactions = storage.get_transactions()?;
+    let labels: HashMap<_, _> = storage.get_contacts()?
+        .into_iter()
+        .map(|contact| (contact.get_public_key().clone(), contact.get_label().clone()))
+        .collect();
 
     // if we don't have any txs, no need proceed further
     if transactions.is_empty() {
@@ -757,12 +1308,50 @@ async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Re
 
     manager.message(format!("Transactions (total {}) page {}/{}:", transactions.len(), page, max_pages));
     for tx in transactions.iter().skip((page - 1) * TXS_PER_PAGE).take(TXS_PER_PAGE) {
-        manager.message(format!("- {}", tx.summary(wallet.get_network().is_mainnet(), &*storage)?));
+        manager.message(format!("- {}", tx.summary(wallet.get_network().is_mainnet(), &*storage, &labels)?));
     }
 
     Ok(())
 }
 
+// Show all outgoing transactions not yet included in a block
+async fn pending_transactions(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let storage = wallet.get_storage().read().await;
+    let transactions = storage.get_pending_transactions()?;
+    if transactions.is_empty() {
+        manager.message("No pending transactions");
+        return Ok(())
+    }
+
+    manager.message(format!("Pending transactions ({}):", transactions.len()));
+    for pending in transactions {
+        let status = match pending.get_failure() {
+            Some(reason) => format!("failed ({})", reason),
+            None => format!("pending (rebroadcasted {} times)", pending.get_rebroadcasts())
+        };
+        manager.message(format!("- {} with nonce {}: {}", pending.get_hash(), pending.get_nonce(), status));
+    }
+
+    Ok(())
+}
+
+// Delete all pending transactions marked as failed
+async fn clear_failed_transactions(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let mut storage = wallet.get_storage().write().await;
+    let mut count = 0;
+    for pending in storage.get_pending_transactions()?.into_iter().filter(|pending| pending.is_failed()) {
+        storage.delete_pending_transaction(pending.get_hash())?;
+        count += 1;
+    }
+
+    manager.message(format!("{} failed transactions cleared", count));
+    Ok(())
+}
+
 // Set your wallet in online mode
 async fn online_mode(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
@@ -770,12 +1359,7 @@ async fn online_mode(manager: &CommandManager, mut arguments: ArgumentManager) -
     if wallet.is_online().await {
         manager.error("Wallet is already online");
     } else {
-        let daemon_address = if arguments.has_argument("daemon_address") {
-            arguments.get_value("daemon_address")?.to_string_value()?
-        } else {
-            DEFAULT_DAEMON_ADDRESS.to_string()
-        };
-
+        let daemon_address = get_daemon_address(&mut arguments)?;
         wallet.set_online_mode(&daemon_address).await.context("Couldn't enable online mode")?;
         manager.message("Wallet is now online");
     }
@@ -826,7 +1410,11 @@ async fn seed(manager: &CommandManager, mut arguments: ArgumentManager) -> Resul
         0
     };
 
-    let seed = wallet.get_seed(language as usize)?;
+    let seed = wallet.get_seed(language as usize).await?;
+    if wallet.has_passphrase().await? {
+        manager.warn("This wallet is protected by a passphrase, it is required with the seed to recover it");
+    }
+
     prompt.read_input(
         prompt::colorize_string(Color::Green, &format!("Seed: {}\r\nPress ENTER to continue", seed)),
         false
@@ -854,6 +1442,175 @@ async fn set_nonce(manager: &CommandManager, _: ArgumentManager) -> Result<(), C
     Ok(())
 }
 
+// Create a new account derived from the seed
+async fn create_account(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let account = wallet.create_account().await.context("Error while creating account")?;
+    manager.message(format!("Account #{} created: {}", account.get_index(), account.get_public_key().to_address(wallet.get_network().is_mainnet())));
+    Ok(())
+}
+
+// Show all accounts derived from the seed
+async fn list_accounts(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let selected = wallet.get_account().get_index();
+    let mainnet = wallet.get_network().is_mainnet();
+    for account in wallet.get_accounts().await.context("Error while retrieving accounts")? {
+        let address = account.get_public_key().to_address(mainnet);
+        if account.get_index() == selected {
+            manager.message(format!("* Account #{}: {}", account.get_index(), address));
+        } else {
+            manager.message(format!("  Account #{}: {}", account.get_index(), address));
+        }
+    }
+    Ok(())
+}
+
+// Switch to another account
+async fn switch_account(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let index = arguments.get_value("index")?.to_number()?;
+    let index = u32::try_from(index).context("Invalid account index")?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.switch_account(index).await.context("Error while switching account")?;
+    manager.message(format!("Switched to account #{}: {}", index, wallet.get_address()));
+    Ok(())
+}
+
+// Add a contact in the address book
+// Address can be an integrated address to keep its data for each transfer
+async fn add_contact(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let label = arguments.get_value("label")?.to_string_value()?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let str_address = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Address: "),
+        false
+    ).await.context("Error while reading address")?;
+    let address = Address::from_string(&str_address).context("Invalid address")?;
+
+    let asset = prompt.read_hash(
+        prompt::colorize_str(Color::Green, "Default asset (optional): ")
+    ).await.ok();
+
+    let notes = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Notes (optional): "),
+        false
+    ).await.context("Error while reading notes")?;
+
+    let contact = Contact::new(label, address, asset, Some(notes)).context("Invalid contact")?;
+    wallet.add_contact(contact).await.context("Error while adding contact")?;
+    manager.message("Contact has been added");
+    Ok(())
+}
+
+// List all contacts of the address book
+async fn list_contacts(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let storage = wallet.get_storage().read().await;
+    let contacts = storage.get_contacts().context("Error while retrieving contacts")?;
+    if contacts.is_empty() {
+        manager.message("No contacts available");
+        return Ok(())
+    }
+
+    manager.message(format!("Contacts ({}):", contacts.len()));
+    for contact in contacts {
+        let mut line = format!("- {}: {}", contact.get_label(), contact.get_address());
+        if let Some(asset) = contact.get_asset() {
+            line.push_str(&format!(" (asset: {})", asset));
+        }
+        if let Some(notes) = contact.get_notes() {
+            line.push_str(&format!(" - {}", notes));
+        }
+        manager.message(line);
+    }
+
+    Ok(())
+}
+
+// Remove a contact from the address book
+async fn remove_contact(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let label = arguments.get_value("label")?.to_string_value()?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.remove_contact(&label).await.context("Error while removing contact")?;
+    manager.message(format!("Contact {} has been removed", label));
+    Ok(())
+}
+
+async fn payment_proof(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let hash = arguments.get_value("hash")?.to_hash()?;
+    let index = arguments.get_value("index")?.to_number()?;
+    let index = u8::try_from(index).context("Invalid transfer index")?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let proof = wallet.get_payment_proof(&hash, index).await.context("Error while retrieving payment proof")?;
+    let content = serde_json::to_string(&proof).context("Error while serializing payment proof")?;
+    manager.message(format!("Payment proof for transfer #{} of {}: {}", index, hash, content));
+    Ok(())
+}
+
+// Send a message in the extra data of a transfer without any amount
+async fn send_message(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let str_address = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Address or contact: "),
+        false
+    ).await.context("Error while reading address")?;
+    let address = match Address::from_string(&str_address) {
+        Ok(address) => address,
+        Err(_) => wallet.get_contact(&str_address).await.context("Invalid address or unknown contact")?.get_address().clone()
+    };
+
+    let text = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Message: "),
+        false
+    ).await.context("Error while reading message")?;
+    let message = Message::new(text, None).map_err(|e| CommandError::InvalidArgument(e.to_string()))?;
+
+    manager.message(format!("Sending message to {}", address));
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Message has been aborted");
+        return Ok(())
+    }
+
+    manager.message("Building transaction...");
+    let tx = wallet.create_message_transaction(address, &message, XELIS_ASSET, 0, FeeBuilder::default()).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// Show the messages received grouped per sender
+async fn inbox(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let inbox = wallet.get_inbox().await.context("Error while retrieving inbox")?;
+    if inbox.is_empty() {
+        manager.message("No messages received");
+        return Ok(())
+    }
+
+    for entry in inbox {
+        manager.message(format!("From {} ({} messages):", entry.counterparty, entry.messages.len()));
+        for message in entry.messages {
+            manager.message(format!("- [{}] {}: {}", message.topoheight, message.hash, message.message));
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(feature = "api_server")]
 async fn stop_api_server(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
@@ -881,6 +1638,106 @@ async fn start_rpc_server(manager: &CommandManager, mut arguments: ArgumentManag
     Ok(())
 }
 
+#[cfg(feature = "api_server")]
+async fn list_xswd_apps(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let storage = wallet.get_storage().read().await;
+    let applications = storage.get_xswd_applications().context("Error while retrieving XSWD applications")?;
+    if applications.is_empty() {
+        manager.message("No XSWD applications approved");
+        return Ok(())
+    }
+
+    manager.message(format!("XSWD applications ({}):", applications.len()));
+    for application in applications {
+        let data = application.get_data();
+        let permissions = data.get_permissions()
+            .iter()
+            .map(|(method, permission)| format!("{}: {:?}", method, permission))
+            .collect::<Vec<_>>()
+            .join(", ");
+        manager.message(format!("- {} ({}) {} [{}]", data.get_name(), data.get_id(), data.get_url().as_deref().unwrap_or(""), permissions));
+        manager.message(format!("  Policy: {}", application.get_policy()));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "api_server")]
+async fn revoke_xswd_app(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let id = arguments.get_value("id")?.to_string_value()?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.revoke_xswd_application(&id).await.context("Error while revoking XSWD application")?;
+    manager.message(format!("XSWD application {} has been revoked", id));
+    Ok(())
+}
+
+// Configure the restrictions enforced on the requests of an XSWD application
+// The previous policy of the application is replaced
+#[cfg(feature = "api_server")]
+async fn set_xswd_policy(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let id = arguments.get_value("id")?.to_string_value()?;
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let mut policy = ApplicationPolicy::default();
+
+    let limits = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Daily spend limits as asset=amount, comma separated (optional): "),
+        false
+    ).await.context("Error while reading spend limits")?;
+    for limit in limits.split(',').map(str::trim).filter(|limit| !limit.is_empty()) {
+        let (asset, amount) = limit.split_once('=').context("Invalid spend limit format")?;
+        let asset = Hash::from_hex(asset.trim().to_owned()).context("Invalid asset")?;
+        let decimals = {
+            let storage = wallet.get_storage().read().await;
+            storage.get_asset_decimals(&asset).unwrap_or(COIN_DECIMALS)
+        };
+        let float_amount: f64 = amount.trim().parse().context("Invalid amount")?;
+        policy.spend_limits.insert(asset, (float_amount * 10u32.pow(decimals as u32) as f64) as u64);
+    }
+
+    let destinations = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Allowed destinations, comma separated (optional): "),
+        false
+    ).await.context("Error while reading allowed destinations")?;
+    for destination in destinations.split(',').map(str::trim).filter(|destination| !destination.is_empty()) {
+        let address = Address::from_string(&destination.to_owned()).context("Invalid address")?;
+        if address.is_mainnet() != wallet.get_network().is_mainnet() {
+            return Err(CommandError::InvalidArgument("Address is not for this network".to_owned()))
+        }
+        policy.allowed_destinations.insert(address.to_public_key());
+    }
+
+    let max_fee = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Maximum fee in XELIS (optional): "),
+        false
+    ).await.context("Error while reading maximum fee")?;
+    if !max_fee.is_empty() {
+        let float_fee: f64 = max_fee.parse().context("Invalid maximum fee")?;
+        policy.max_fee = Some((float_fee * 10u32.pow(COIN_DECIMALS as u32) as f64) as u64);
+    }
+
+    let trees = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Read only trees, comma separated (optional, * for none): "),
+        false
+    ).await.context("Error while reading trees")?;
+    if !trees.is_empty() {
+        policy.read_only_trees = Some(trees.split(',')
+            .map(str::trim)
+            .filter(|tree| !tree.is_empty() && *tree != "*")
+            .map(str::to_owned)
+            .collect()
+        );
+    }
+
+    wallet.set_xswd_policy(&id, policy).await.context("Error while setting XSWD policy")?;
+    manager.message(format!("Policy of XSWD application {} has been updated", id));
+    Ok(())
+}
+
 #[cfg(feature = "api_server")]
 async fn start_xswd(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
@@ -897,6 +1754,147 @@ async fn start_xswd(manager: &CommandManager, _: ArgumentManager) -> Result<(),
     Ok(())
 }
 
+// Read the daemon address from arguments or use the default one
+fn get_daemon_address(arguments: &mut ArgumentManager) -> Result<String, CommandError> {
+    Ok(if arguments.has_argument("daemon_address") {
+        arguments.get_value("daemon_address")?.to_string_value()?
+    } else {
+        DEFAULT_DAEMON_ADDRESS.to_string()
+    })
+}
+
+// Export an unsigned transaction request for an address
+// No private key is needed, only the address and a connection to a daemon
+async fn export_unsigned_transaction(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let network = {
+        let context = manager.get_context().lock()?;
+        *context.get::<Network>()?
+    };
+    let daemon_address = get_daemon_address(&mut arguments)?;
+
+    let str_source = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Source address: "),
+        false
+    ).await.context("Error while reading source address")?;
+    let source = Address::from_string(&str_source).context("Invalid source address")?;
+
+    let str_address = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Destination address: "),
+        false
+    ).await.context("Error while reading address")?;
+    let address = Address::from_string(&str_address).context("Invalid address")?;
+
+    let asset = prompt.read_hash(
+        prompt::colorize_str(Color::Green, "Asset (default XELIS): ")
+    ).await.ok();
+    let asset = asset.unwrap_or(XELIS_ASSET);
+
+    let api = DaemonAPI::new(daemon_address).await.context("Couldn't connect to daemon")?;
+    let decimals = api.get_asset(&asset).await.context("Error while retrieving asset")?.get_decimals();
+
+    let float_amount: f64 = prompt.read(
+        prompt::colorize_str(Color::Green, "Amount: ")
+    ).await.context("Error while reading amount")?;
+    let amount = (float_amount * 10u32.pow(decimals as u32) as f64) as u64;
+
+    let path = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Output file: "),
+        false
+    ).await.context("Error while reading output file")?;
+
+    let transfer = TransferBuilder {
+        destination: address,
+        amount,
+        asset,
+        extra_data: None
+    };
+    let request = UnsignedTransactionRequest::fetch(&api, network, source, TransactionTypeBuilder::Transfers(vec![transfer]), FeeBuilder::default()).await
+        .context("Error while creating unsigned transaction request")?;
+
+    let content = serde_json::to_string_pretty(&request).context("Error while serializing unsigned transaction request")?;
+    fs::write(&path, content).context("Error while writing unsigned transaction request")?;
+    manager.message(format!("Unsigned transaction request with nonce {} written to {}", request.nonce, path));
+
+    Ok(())
+}
+
+// Sign an unsigned transaction request and write the transaction in hex format
+async fn sign_unsigned_transaction(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let path = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Unsigned request file: "),
+        false
+    ).await.context("Error while reading request file")?;
+    let content = fs::read_to_string(&path).context("Error while reading unsigned transaction request")?;
+    let request: UnsignedTransactionRequest = serde_json::from_str(&content).context("Invalid unsigned transaction request")?;
+
+    {
+        let storage = wallet.get_storage().read().await;
+        match &request.tx_type {
+            TransactionTypeBuilder::Transfers(transfers) => for transfer in transfers {
+                let decimals = storage.get_asset_decimals(&transfer.asset).unwrap_or(COIN_DECIMALS);
+                manager.message(format!("Sending {} of {} to {}", format_coin(transfer.amount, decimals), transfer.asset, transfer.destination));
+            },
+            TransactionTypeBuilder::Burn(payload) => {
+                let decimals = storage.get_asset_decimals(&payload.asset).unwrap_or(COIN_DECIMALS);
+                manager.message(format!("Burning {} of {}", format_coin(payload.amount, decimals), payload.asset));
+            }
+        };
+    }
+    manager.message(format!("Nonce: {}, reference: {} at topoheight {}", request.nonce, request.reference.hash, request.reference.topoheight));
+
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    let output = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Output file: "),
+        false
+    ).await.context("Error while reading output file")?;
+
+    let tx = wallet.sign_unsigned_request(request).await
+        .context("Error while signing transaction")?;
+
+    fs::write(&output, tx.to_hex()).context("Error while writing signed transaction")?;
+    manager.message(format!("Transaction {} (fee {}) written to {}", tx.hash(), format_xelis(tx.get_fee()), output));
+
+    Ok(())
+}
+
+// Verify that the signed transaction matches its unsigned request and broadcast it
+async fn broadcast_signed_transaction(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let daemon_address = get_daemon_address(&mut arguments)?;
+
+    let path = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Unsigned request file: "),
+        false
+    ).await.context("Error while reading request file")?;
+    let content = fs::read_to_string(&path).context("Error while reading unsigned transaction request")?;
+    let request: UnsignedTransactionRequest = serde_json::from_str(&content).context("Invalid unsigned transaction request")?;
+
+    let path = prompt.read_input(
+        prompt::colorize_str(Color::Green, "Signed transaction file: "),
+        false
+    ).await.context("Error while reading signed transaction file")?;
+    let hex = fs::read_to_string(&path).context("Error while reading signed transaction")?;
+    let tx = Transaction::from_hex(hex.trim().to_owned()).context("Invalid signed transaction")?;
+
+    request.verify_signed_transaction(&tx).context("Invalid signed transaction")?;
+    manager.message(format!("Transaction hash: {}", tx.hash()));
+
+    let api = DaemonAPI::new(daemon_address).await.context("Couldn't connect to daemon")?;
+    api.submit_transaction(&tx).await.context("Couldn't submit transaction")?;
+    manager.message("Transaction submitted successfully!");
+
+    Ok(())
+}
+
 // broadcast tx if possible
 // submit_transaction increase the local nonce in storage in case of success
 async fn broadcast_tx(wallet: &Wallet, manager: &CommandManager, tx: Transaction) {
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use xelis_common::{
+    api::{
+        wallet::{InboxEntry, MessageEntry},
+        DataElement,
+        DataValue,
+        ValueType
+    },
+    crypto::{Address, Hash, PublicKey},
+    transaction::builder::TransferBuilder
+};
+use crate::{
+    entry::EntryData,
+    storage::EncryptedStorage,
+    wallet::WalletError
+};
+
+// Key of the text in the extra data of a transfer
+pub const MESSAGE_KEY: &str = "message";
+// Key of the transaction hash of the message replied to
+pub const REPLY_TO_KEY: &str = "reply_to";
+// Maximum size in bytes of a message, strings are serialized with a u8 length
+pub const MAX_MESSAGE_SIZE: usize = u8::MAX as usize;
+
+// Text message sent in the extra data of a transfer
+// Extra data is encrypted for the sender and the receiver only
+#[derive(Clone, Debug)]
+pub struct Message {
+    text: String,
+    reply_to: Option<Hash>
+}
+
+impl Message {
+    pub fn new(text: String, reply_to: Option<Hash>) -> Result<Self, WalletError> {
+        if text.is_empty() {
+            return Err(WalletError::EmptyMessage)
+        }
+
+        if text.len() > MAX_MESSAGE_SIZE {
+            return Err(WalletError::MessageTooLong)
+        }
+
+        Ok(Self {
+            text,
+            reply_to
+        })
+    }
+
+    pub fn get_text(&self) -> &String {
+        &self.text
+    }
+
+    pub fn get_reply_to(&self) -> Option<&Hash> {
+        self.reply_to.as_ref()
+    }
+
+    pub fn to_data(&self) -> DataElement {
+        let mut fields = HashMap::new();
+        fields.insert(DataValue::String(MESSAGE_KEY.to_owned()), DataElement::Value(DataValue::String(self.text.clone())));
+        if let Some(reply_to) = &self.reply_to {
+            fields.insert(DataValue::String(REPLY_TO_KEY.to_owned()), DataElement::Value(DataValue::Hash(reply_to.clone())));
+        }
+
+        DataElement::Fields(fields)
+    }
+
+    // Returns None if the extra data doesn't contain a message
+    pub fn from_data(data: &DataElement) -> Option<Self> {
+        let text = data.get_value_by_string_key(MESSAGE_KEY.to_owned(), ValueType::String)?
+            .as_string()
+            .ok()?
+            .clone();
+        let reply_to = data.get_value_by_string_key(REPLY_TO_KEY.to_owned(), ValueType::Hash)
+            .and_then(|value| value.as_hash().ok())
+            .cloned();
+
+        Some(Self {
+            text,
+            reply_to
+        })
+    }
+
+    // Transfer carrying this message, amount can be zero
+    pub fn to_transfer(&self, destination: Address, asset: Hash, amount: u64) -> TransferBuilder {
+        TransferBuilder {
+            destination,
+            amount,
+            asset,
+            extra_data: Some(self.to_data())
+        }
+    }
+}
+
+// Retrieve the messages sent and received, oldest first
+// If a counterparty is set, only the messages exchanged with it are returned
+pub fn get_messages(storage: &EncryptedStorage, counterparty: Option<&PublicKey>, min_topoheight: Option<u64>, max_topoheight: Option<u64>, accept_incoming: bool, accept_outgoing: bool, mainnet: bool) -> Result<Vec<MessageEntry>> {
+    let mut transactions = storage.get_filtered_transactions(counterparty, min_topoheight, max_topoheight, accept_incoming, accept_outgoing, false, false, None)?;
+    transactions.sort_by_key(|entry| entry.get_topoheight());
+
+    let mut messages = Vec::new();
+    for entry in transactions {
+        let mut add_message = |counterparty: &PublicKey, outgoing: bool, asset: &Hash, amount: Option<u64>, data: &Option<DataElement>| {
+            let Some(message) = data.as_ref().and_then(Message::from_data) else {
+                return
+            };
+
+            messages.push(MessageEntry {
+                hash: entry.get_hash().clone(),
+                topoheight: entry.get_topoheight(),
+                counterparty: counterparty.as_address(mainnet),
+                outgoing,
+                asset: asset.clone(),
+                amount,
+                message: message.text,
+                reply_to: message.reply_to
+            });
+        };
+
+        match entry.get_entry() {
+            EntryData::Incoming { from, transfers } => for transfer in transfers {
+                add_message(from, false, transfer.get_asset(), transfer.get_amount(), transfer.get_extra_data());
+            },
+            EntryData::Outgoing { transfers, .. } => for transfer in transfers {
+                // An outgoing transaction can have transfers to several destinations
+                if counterparty.is_some_and(|key| key != transfer.get_destination()) {
+                    continue
+                }
+                add_message(transfer.get_destination(), true, transfer.get_asset(), transfer.get_amount(), transfer.get_extra_data());
+            },
+            _ => {}
+        }
+    }
+
+    Ok(messages)
+}
+
+// Received messages grouped per sender, most recent conversation first
+pub fn get_inbox(storage: &EncryptedStorage, mainnet: bool) -> Result<Vec<InboxEntry>> {
+    let mut inbox: Vec<InboxEntry> = Vec::new();
+    for message in get_messages(storage, None, None, None, true, false, mainnet)? {
+        match inbox.iter_mut().find(|entry| entry.counterparty == message.counterparty) {
+            Some(entry) => entry.messages.push(message),
+            None => inbox.push(InboxEntry {
+                counterparty: message.counterparty.clone(),
+                messages: vec![message]
+            })
+        }
+    }
+
+    // Messages are sorted by topoheight, so the last one is the most recent
+    inbox.sort_by_key(|entry| std::cmp::Reverse(entry.messages.last().map(|message| message.topoheight)));
+
+    Ok(inbox)
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::crypto::Hash;
+    use super::Message;
+
+    #[test]
+    fn test_message_data() {
+        let message = Message::new("hello".to_owned(), Some(Hash::zero())).unwrap();
+        let decoded = Message::from_data(&message.to_data()).unwrap();
+        assert_eq!(decoded.get_text(), message.get_text());
+        assert_eq!(decoded.get_reply_to(), message.get_reply_to());
+
+        assert!(Message::new(String::new(), None).is_err());
+        assert!(Message::new("a".repeat(256), None).is_err());
+    }
+}
@@ -9,6 +9,7 @@ use xelis_common::{
     serializer::Serializer
 };
 use languages::*;
+use crate::config::{PASSPHRASE_ALGORITHM, PASSPHRASE_HASH_SIZE, PASSPHRASE_SALT_DOMAIN};
 
 const KEY_SIZE: usize = 32;
 const SEED_LENGTH: usize = 24;
@@ -120,6 +121,30 @@ pub fn words_to_key(words: &Vec<String>) -> Result<PrivateKey> {
     Ok(PrivateKey::from_bytes(&dest)?)
 }
 
+// Derive the private key protected by a passphrase from the one encoded in the seed
+// Passphrase is hashed using its own fixed Argon2 parameters, salted with the seed key,
+// to slow down brute force attacks against a stolen seed
+pub fn apply_passphrase(key: &PrivateKey, passphrase: &str) -> Result<PrivateKey> {
+    let mut salt = PASSPHRASE_SALT_DOMAIN.to_vec();
+    salt.extend_from_slice(&key.to_bytes());
+
+    let mut output = [0; PASSPHRASE_HASH_SIZE];
+    PASSPHRASE_ALGORITHM.hash_password_into(passphrase.as_bytes(), &salt, &mut output)
+        .map_err(|e| anyhow!("Error while hashing passphrase: {}", e))?;
+
+    Ok(key.derive_with_passphrase(&output))
+}
+
+// convert a words list and an optional passphrase to a Private Key
+// An empty passphrase is the same as no passphrase
+pub fn words_to_key_with_passphrase(words: &Vec<String>, passphrase: Option<&str>) -> Result<PrivateKey> {
+    let key = words_to_key(words)?;
+    match passphrase {
+        Some(passphrase) if !passphrase.is_empty() => apply_passphrase(&key, passphrase),
+        _ => Ok(key)
+    }
+}
+
 pub fn key_to_words(key: &PrivateKey, language_index: usize) -> Result<Vec<String>> {
     let language = LANGUAGES.get(language_index).context("Invalid language index")?;
     key_to_words_with_language(key, language)
@@ -155,7 +180,10 @@ pub fn key_to_words_with_language(key: &PrivateKey, language: &Language) -> Resu
 
 #[cfg(test)]
 mod tests {
-    use xelis_common::crypto::KeyPair;
+    use xelis_common::{
+        crypto::{KeyPair, PrivateKey},
+        serializer::Serializer
+    };
 
     #[test]
     fn test_languages() {
@@ -169,4 +197,29 @@ mod tests {
             assert_eq!(words, words2);
         }
     }
+
+    #[test]
+    fn test_passphrase() {
+        let (_, key) = KeyPair::new().split();
+        let words = super::key_to_words(&key, 0).unwrap();
+
+        let nkey = super::words_to_key_with_passphrase(&words, Some("")).unwrap();
+        assert_eq!(key.as_scalar(), nkey.as_scalar());
+
+        let first = super::words_to_key_with_passphrase(&words, Some("passphrase")).unwrap();
+        let second = super::words_to_key_with_passphrase(&words, Some("passphrase")).unwrap();
+        assert_eq!(first.as_scalar(), second.as_scalar());
+        assert_ne!(first.as_scalar(), key.as_scalar());
+
+        let other = super::words_to_key_with_passphrase(&words, Some("other")).unwrap();
+        assert_ne!(first.as_scalar(), other.as_scalar());
+    }
+
+    // Derived key must never change, otherwise funds can't be recovered from the seed
+    #[test]
+    fn test_passphrase_known_answer() {
+        let key = PrivateKey::from_bytes(&[1; 32]).unwrap();
+        let derived = super::apply_passphrase(&key, "passphrase").unwrap();
+        assert_eq!(hex::encode(derived.to_bytes()), "9701efaebe73ae6e47a2b6aeb71dd42408ebadd2a4f9c6938ecdbfbd935cc108");
+    }
 }
\ No newline at end of file
@@ -1,35 +1,50 @@
 use std::{
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        PoisonError,
+        RwLock as StdRwLock
+    },
     collections::{
         HashMap,
         HashSet
-    }
+    },
+    time::Duration
 };
 use thiserror::Error;
-use anyhow::Error;
-use log::{debug, error, trace, warn};
-use tokio::{task::JoinHandle, sync::Mutex};
+use anyhow::{anyhow, Error};
+use log::{debug, error, info, trace, warn};
+use tokio::{
+    task::JoinHandle,
+    sync::Mutex,
+    time::{interval_at, Instant, Interval}
+};
 use xelis_common::{
     account::CiphertextCache,
     api::{
         daemon::{
             BlockResponse,
+            GetBlockAtTopoHeightParams,
+            GetInfoResult,
             NewBlockEvent
         },
         wallet::BalanceChanged,
         RPCTransactionType
     },
     asset::AssetWithData,
-    config::XELIS_ASSET,
+    config::{MAINNET_GENESIS_BLOCK_HASH, TESTNET_GENESIS_BLOCK_HASH, XELIS_ASSET},
     crypto::{
         elgamal::Ciphertext,
         Address,
         Hash
     },
+    json_rpc::JsonRPCClient,
+    network::Network,
     serializer::Serializer,
     utils::sanitize_daemon_address
 };
 use crate::{
+    config::{DAEMON_HEALTH_CHECK_INTERVAL, MAX_DAEMON_TOPOHEIGHT_LAG},
     daemon_api::DaemonAPI,
     entry::{
         EntryData,
@@ -37,6 +52,8 @@ use crate::{
         TransferIn,
         TransferOut
     },
+    invoice,
+    pending::{FailureReason, PendingTransaction},
     storage::Balance,
     wallet::{
         Event, Wallet
@@ -57,7 +74,9 @@ pub enum NetworkError {
     #[error(transparent)]
     DaemonAPIError(#[from] Error),
     #[error("Network mismatch")]
-    NetworkMismatch
+    NetworkMismatch,
+    #[error("Genesis block mismatch")]
+    GenesisMismatch
 }
 
 pub struct NetworkHandler {
@@ -68,16 +87,62 @@ pub struct NetworkHandler {
     // api to communicate with daemon
     // It is behind a Arc to be shared across several wallets
     // in case someone make a custom service and don't want to create a new connection
-    api: Arc<DaemonAPI>
+    // It is replaced when we switch to another daemon
+    api: StdRwLock<Arc<DaemonAPI>>,
+    // Daemon addresses that can be used, by order of preference
+    // Empty if the network handler was created with an API
+    daemons: Vec<String>,
+    // HTTP clients of the daemons, used for the health checks
+    // so we don't open a websocket connection to each daemon every time
+    probes: Vec<JsonRPCClient>,
+    // Index of the daemon address currently used
+    current_daemon: AtomicUsize,
+    // Hash of the genesis block to detect a daemon on another chain
+    genesis_hash: Hash
+}
+
+// Address of the daemon RPC over HTTP
+fn http_rpc_address(address: &str) -> String {
+    let address = sanitize_daemon_address(address);
+    let address = match address.strip_prefix("wss://") {
+        Some(host) => format!("https://{}", host),
+        None => address.replacen("ws://", "http://", 1)
+    };
+
+    format!("{}/json_rpc", address)
 }
 
 impl NetworkHandler {
     // Create a new network handler with a wallet and a daemon address
     // This will create itself a DaemonAPI and verify if connection is possible
     pub async fn new<S: ToString>(wallet: Arc<Wallet>, daemon_address: S) -> Result<SharedNetworkHandler, Error> {
-        let s = daemon_address.to_string();
-        let api = DaemonAPI::new(format!("{}/json_rpc", sanitize_daemon_address(s.as_str()))).await?;
-        Self::with_api(wallet, Arc::new(api)).await
+        Self::with_failover(wallet, vec![daemon_address.to_string()]).await
+    }
+
+    // Create a new network handler with several daemon addresses
+    // The first daemon available is used, the others are used in case of failure
+    pub async fn with_failover(wallet: Arc<Wallet>, daemons: Vec<String>) -> Result<SharedNetworkHandler, Error> {
+        let mut last_error = None;
+        for (index, address) in daemons.iter().enumerate() {
+            match Self::connect_daemon(address).await {
+                Ok(api) => match Self::create(wallet.clone(), api.clone(), daemons.clone(), index).await {
+                    Ok(handler) => return Ok(handler),
+                    Err(e) => {
+                        warn!("Daemon {} can't be used: {}", address, e);
+                        if let Err(e) = api.disconnect().await {
+                            debug!("Error while closing websocket connection: {}", e);
+                        }
+                        last_error = Some(e);
+                    }
+                },
+                Err(e) => {
+                    warn!("Couldn't connect to daemon {}: {}", address, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow!("No daemon address provided")))
     }
 
     // Create a new network handler with an already created daemon API
@@ -86,13 +151,47 @@ impl NetworkHandler {
         let version = api.get_version().await?;
         debug!("Connected to daemon running version {}", version);
 
+        Self::create(wallet, api, Vec::new(), 0).await
+    }
+
+    async fn create(wallet: Arc<Wallet>, api: Arc<DaemonAPI>, daemons: Vec<String>, current_daemon: usize) -> Result<SharedNetworkHandler, Error> {
+        let info = api.get_info().await?;
+        if info.network != *wallet.get_network() {
+            return Err(NetworkError::NetworkMismatch.into())
+        }
+
+        let genesis_hash = api.get_block_at_topoheight(0).await?.hash.into_owned();
+        // Dev network has no fixed genesis block, the first daemon used is the reference
+        let expected_genesis_hash = match info.network {
+            Network::Mainnet => Some(MAINNET_GENESIS_BLOCK_HASH),
+            Network::Testnet => Some(TESTNET_GENESIS_BLOCK_HASH),
+            Network::Dev => None
+        };
+        if expected_genesis_hash.is_some_and(|expected| genesis_hash != expected) {
+            return Err(NetworkError::GenesisMismatch.into())
+        }
+
+        let probes = daemons.iter().map(|address| JsonRPCClient::new(http_rpc_address(address))).collect();
         Ok(Arc::new(Self {
             task: Mutex::new(None),
             wallet,
-            api
+            api: StdRwLock::new(api),
+            daemons,
+            probes,
+            current_daemon: AtomicUsize::new(current_daemon),
+            genesis_hash
         }))
     }
 
+    // Connect to a daemon and check that we can correctly get its version
+    async fn connect_daemon(address: &str) -> Result<Arc<DaemonAPI>, Error> {
+        let api = DaemonAPI::new(format!("{}/json_rpc", sanitize_daemon_address(address))).await?;
+        let version = api.get_version().await?;
+        debug!("Connected to daemon {} running version {}", address, version);
+
+        Ok(Arc::new(api))
+    }
+
     // Start the internal loop to sync all missed blocks and all newly added blocks
     pub async fn start(self: &Arc<Self>) -> Result<(), NetworkError> {
         trace!("Starting network handler");
@@ -101,9 +200,9 @@ impl NetworkHandler {
             return Err(NetworkError::AlreadyRunning)
         }
 
-        if !self.api.is_online() {
+        if !self.get_api().is_online() {
             debug!("API is offline, trying to reconnect");
-            if !self.api.reconnect().await? {
+            if !self.get_api().reconnect().await? {
                 error!("Couldn't reconnect to server");
                 return Err(NetworkError::NotRunning)
             }
@@ -117,7 +216,7 @@ impl NetworkHandler {
             }
 
             // Turn off the websocket connection
-            if let Err(e) = zelf.api.disconnect().await {
+            if let Err(e) = zelf.get_api().disconnect().await {
                 debug!("Error while closing websocket connection: {}", e);
             }
 
@@ -149,7 +248,7 @@ impl NetworkHandler {
             }
 
             // Turn off the websocket connection
-            if let Err(e) = self.api.disconnect().await {
+            if let Err(e) = self.get_api().disconnect().await {
                 debug!("Error while closing websocket connection: {}", e);
             }
 
@@ -160,15 +259,116 @@ impl NetworkHandler {
     }
 
     // Retrieve the daemon API used
-    pub fn get_api(&self) -> &DaemonAPI {
-        &self.api
+    pub fn get_api(&self) -> Arc<DaemonAPI> {
+        self.api.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    // Retrieve the address of the daemon used
+    // None if the network handler was created with an API
+    pub fn get_daemon_address(&self) -> Option<&String> {
+        self.daemons.get(self.current_daemon.load(Ordering::SeqCst))
+    }
+
+    // Verify that the daemon is on the same chain as us and returns its topoheight
+    async fn check_daemon(&self, api: &DaemonAPI) -> Result<u64, Error> {
+        let info = api.get_info().await?;
+        let genesis = api.get_block_at_topoheight(0).await?;
+        self.check_chain(&info, &genesis)?;
+
+        Ok(info.topoheight)
+    }
+
+    // Same as check_daemon, but through a HTTP request
+    async fn probe_daemon(&self, client: &JsonRPCClient) -> Result<u64, Error> {
+        let info: GetInfoResult = client.call("get_info").await?;
+        let genesis: BlockResponse = client.call_with("get_block_at_topoheight", &GetBlockAtTopoHeightParams {
+            topoheight: 0,
+            include_txs: false
+        }).await?;
+        self.check_chain(&info, &genesis)?;
+
+        Ok(info.topoheight)
+    }
+
+    fn check_chain(&self, info: &GetInfoResult, genesis: &BlockResponse) -> Result<(), NetworkError> {
+        if info.network != *self.wallet.get_network() {
+            return Err(NetworkError::NetworkMismatch)
+        }
+
+        if *genesis.hash != self.genesis_hash {
+            return Err(NetworkError::GenesisMismatch)
+        }
+
+        Ok(())
+    }
+
+    // Search the daemon with the highest topoheight, excluding the one currently used
+    // Only daemons at or above the minimum topoheight requested are selected
+    // Daemons are probed over HTTP, only the one selected is connected
+    async fn find_daemon(&self, min_topoheight: u64) -> Option<(usize, Arc<DaemonAPI>)> {
+        let current = self.current_daemon.load(Ordering::SeqCst);
+        let mut candidates = Vec::new();
+        for (index, (address, probe)) in self.daemons.iter().zip(self.probes.iter()).enumerate() {
+            if index == current {
+                continue;
+            }
+
+            match self.probe_daemon(probe).await {
+                Ok(topoheight) if topoheight >= min_topoheight => candidates.push((index, topoheight)),
+                Ok(topoheight) => debug!("Daemon {} is at topoheight {}, skipping it", address, topoheight),
+                Err(e) => debug!("Daemon {} can't be used: {}", address, e)
+            };
+        }
+
+        // Highest topoheight first, by order of preference for the same topoheight
+        candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (index, _) in candidates {
+            let address = &self.daemons[index];
+            match Self::connect_daemon(address).await {
+                Ok(api) => return Some((index, api)),
+                Err(e) => debug!("Couldn't connect to daemon {}: {}", address, e)
+            };
+        }
+
+        None
+    }
+
+    // Verify the daemon used and switch to another one if it's unreachable,
+    // on another chain or lagging behind the others
+    // Returns true if we switched to another daemon
+    async fn check_daemons_health(&self) -> bool {
+        trace!("check daemons health");
+        let min_topoheight = match self.check_daemon(&self.get_api()).await {
+            Ok(topoheight) => topoheight + MAX_DAEMON_TOPOHEIGHT_LAG + 1,
+            Err(e) => {
+                warn!("Daemon used is unhealthy: {}", e);
+                0
+            }
+        };
+
+        let Some((index, api)) = self.find_daemon(min_topoheight).await else {
+            return false
+        };
+
+        let previous = {
+            let mut lock = self.api.write().unwrap_or_else(PoisonError::into_inner);
+            std::mem::replace(&mut *lock, api)
+        };
+        self.current_daemon.store(index, Ordering::SeqCst);
+        info!("Switched to daemon {}", self.daemons[index]);
+
+        if let Err(e) = previous.disconnect().await {
+            debug!("Error while closing websocket connection: {}", e);
+        }
+
+        true
     }
 
     // check if the network handler is running (that we have a task and its not finished)
     pub async fn is_running(&self) -> bool {
         let task = self.task.lock().await;
         if let Some(handle) = task.as_ref() {
-            !handle.is_finished() && self.api.is_online()
+            !handle.is_finished() && self.get_api().is_online()
         } else {
             false
         }
@@ -231,7 +431,7 @@ impl NetworkHandler {
                 RPCTransactionType::Burn(payload) => {
                     let payload = payload.into_owned();
                     if is_owner {
-                        Some(EntryData::Burn { asset: payload.asset, amount: payload.amount })
+                        Some(EntryData::Burn { asset: payload.asset, amount: payload.amount, fee: tx.fee })
                     } else {
                         None
                     }
@@ -275,7 +475,7 @@ impl NetworkHandler {
 
                             debug!("Decrypting amount from TX {}", tx.hash);
                             let ciphertext = Ciphertext::new(commitment, handle);
-                            let amount = Arc::clone(&self.wallet).decrypt_ciphertext(ciphertext).await?;
+                            let amount = Arc::clone(&self.wallet).try_decrypt_ciphertext(ciphertext).await?;
 
                             let asset = transfer.asset.into_owned();
                             assets_changed.insert(asset.clone());
@@ -302,7 +502,7 @@ impl NetworkHandler {
 
             if let Some(entry) = entry {
                 // New transaction entry that may be linked to us, check if TX was executed
-                if !self.api.is_tx_executed_in_block(&tx.hash, &block_hash).await? {
+                if !self.get_api().is_tx_executed_in_block(&tx.hash, &block_hash).await? {
                     debug!("Transaction {} was a good candidate but was not executed in block {}, skipping", tx.hash, block_hash);
                     continue;
                 }
@@ -313,26 +513,34 @@ impl NetworkHandler {
                 }
 
                 let entry = TransactionEntry::new(tx.hash.into_owned(), topoheight, entry);
-                let propagate = {
+                let (propagate, invoices) = {
                     let mut storage = self.wallet.get_storage().write().await;
                     let found = storage.has_transaction(entry.get_hash())?;
+                    let mut invoices = Vec::new();
                     // Even if we probably scanned it before and a DAG reorg happened,
                     // It shouldn't be found because it got deleted from storage
                     if !found {
                         storage.save_transaction(entry.get_hash(), &entry)?;
+                        // Record the payments made to our invoices
+                        invoices = invoice::apply_payments(&mut storage, &entry)?;
                         // Store the changes for history
                         if !changes_stored {
                             storage.add_topoheight_to_changes(topoheight, &block_hash)?;
                             changes_stored = true;
                         }
                     }
-                    !found
+                    (!found, invoices)
                 };
 
                 if propagate {
                     // Propagate the event to the wallet
                     self.wallet.propagate_event(Event::NewTransaction(entry.serializable(self.wallet.get_network().is_mainnet()))).await;
                 }
+
+                for invoice in invoices {
+                    debug!("Invoice {} received a payment in TX {}", invoice.get_id(), entry.get_hash());
+                    self.wallet.propagate_event(Event::InvoiceUpdated(self.wallet.get_invoice_entry(&invoice))).await;
+                }
             }
         }
 
@@ -348,7 +556,7 @@ impl NetworkHandler {
     // When the block is requested, we don't limit the syncing to asset in parameter
     async fn get_balance_and_transactions(&self, topoheight_processed: &mut HashSet<u64>, address: &Address, asset: &Hash, min_topoheight: u64, balances: bool, highest_nonce: &mut Option<u64>) -> Result<(), Error> {
         // Retrieve the highest version
-        let (mut topoheight, mut version) = self.api.get_balance(address, asset).await.map(|res| (res.topoheight, res.version))?;
+        let (mut topoheight, mut version) = self.get_api().get_balance(address, asset).await.map(|res| (res.topoheight, res.version))?;
         // don't sync already synced blocks
         if min_topoheight >= topoheight {
             return Ok(())
@@ -362,7 +570,7 @@ impl NetworkHandler {
             // add this topoheight in cache to not re-process it (blocks are independant of asset to have faster sync)
             // if its not already processed, do it
             if topoheight_processed.insert(topoheight) {
-                let response = self.api.get_block_with_txs_at_topoheight(topoheight).await?;
+                let response = self.get_api().get_block_with_txs_at_topoheight(topoheight).await?;
                 let changes = self.process_block(address, response, topoheight).await?;
 
                 // Check if a change occured, we are the highest version and update balances is requested
@@ -387,21 +595,24 @@ impl NetworkHandler {
                     if store {
                         debug!("Storing balance for asset {}", asset);
                         let plaintext_balance = if let Some(plaintext_balance) = storage.get_unconfirmed_balance_decoded_for(&asset, &balance.compressed()).await? {
-                            plaintext_balance
+                            Some(plaintext_balance)
                         } else {
                             trace!("Decrypting balance for asset {}", asset);
                             let ciphertext = balance.decompressed()?;
-                            Arc::clone(&self.wallet).decrypt_ciphertext(ciphertext.clone()).await?
+                            Arc::clone(&self.wallet).try_decrypt_ciphertext(ciphertext.clone()).await?
                         };
 
                         // Store the new balance
-                        storage.set_balance_for(asset, Balance::new(plaintext_balance, balance)).await?;
+                        // In watch-only mode, only the ciphertext is tracked
+                        storage.set_balance_for(asset, Balance::new(plaintext_balance.unwrap_or(0), balance)).await?;
 
                         // Propagate the event
-                        self.wallet.propagate_event(Event::BalanceChanged(BalanceChanged {
-                            asset: asset.clone(),
-                            balance: plaintext_balance
-                        })).await;
+                        if let Some(plaintext_balance) = plaintext_balance {
+                            self.wallet.propagate_event(Event::BalanceChanged(BalanceChanged {
+                                asset: asset.clone(),
+                                balance: plaintext_balance
+                            })).await;
+                        }
                     }
                 }
             }
@@ -414,7 +625,7 @@ impl NetworkHandler {
                 }
 
                 topoheight = previous;
-                version = self.api.get_balance_at_topoheight(address, asset, previous).await?;
+                version = self.get_api().get_balance_at_topoheight(address, asset, previous).await?;
             } else {
                 return Ok(())
             }
@@ -429,7 +640,7 @@ impl NetworkHandler {
     // All transactions / changes above the last valid topoheight will be deleted
     // Returns daemon topoheight along wallet stable topoheight and if back sync is needed
     async fn locate_sync_topoheight_and_clean(&self) -> Result<(u64, Hash, u64, bool), NetworkError> {
-        let info = self.api.get_info().await?;
+        let info = self.get_api().get_info().await?;
         let daemon_topoheight = info.topoheight;
         let daemon_block_hash = info.top_block_hash;
         let pruned_topoheight = info.pruned_topoheight.unwrap_or(0);
@@ -466,7 +677,7 @@ impl NetworkHandler {
 
                 if synced_topoheight > pruned_topoheight {
                     // Check if it's still a correct block
-                    let header = self.api.get_block_at_topoheight(synced_topoheight).await?;
+                    let header = self.get_api().get_block_at_topoheight(synced_topoheight).await?;
                     let block_hash = header.hash.into_owned();
                     if block_hash == top_block_hash {
                         // topoheight and block hash are equal, we are still on right chain
@@ -508,7 +719,7 @@ impl NetworkHandler {
 
             // Check if we are on the same chain
             debug!("Checking if we are on the same chain at topoheight {}", maximum);
-            let header = self.api.get_block_at_topoheight(maximum).await?;
+            let header = self.get_api().get_block_at_topoheight(maximum).await?;
             let block_hash = header.hash.into_owned();
             if block_hash == local_hash {
                 break Some(local_hash);
@@ -522,7 +733,7 @@ impl NetworkHandler {
         let block_hash = if let Some(block_hash) = block_hash {
             block_hash
         } else {
-            let response = self.api.get_block_at_topoheight(maximum).await?;
+            let response = self.get_api().get_block_at_topoheight(maximum).await?;
             response.hash.into_owned()
         };
 
@@ -560,7 +771,7 @@ impl NetworkHandler {
             nonce
         } else if sync_nonce {
             trace!("no nonce provided, fetching it from daemon");
-            match self.api.get_nonce(&address).await.map(|v| v.version) {
+            match self.get_api().get_nonce(&address).await.map(|v| v.version) {
                 Ok(v) => Some(v.get_nonce()),
                 Err(e) => {
                     debug!("Error while fetching last nonce: {}", e);
@@ -584,7 +795,7 @@ impl NetworkHandler {
             assets
         } else {
             trace!("no assets provided, fetching all assets");
-            self.api.get_account_assets(address).await?
+            self.get_api().get_account_assets(address).await?
         };
 
         trace!("assets: {}", assets.len());
@@ -599,7 +810,7 @@ impl NetworkHandler {
                 let storage = self.wallet.get_storage().read().await;
                 storage.contains_asset(&asset).await?
             } {
-                let data = self.api.get_asset(&asset).await?;
+                let data = self.get_api().get_asset(&asset).await?;
                 
                 // Add the asset to the storage
                 {
@@ -612,7 +823,7 @@ impl NetworkHandler {
             }
 
             // get the balance for this asset
-            let result = self.api.get_balance(&address, &asset).await?;
+            let result = self.get_api().get_balance(&address, &asset).await?;
             trace!("found balance at topoheight: {}", result.topoheight);
             balances.insert(asset, result.version.take_balance());
         }
@@ -651,21 +862,24 @@ impl NetworkHandler {
                 if must_update {
                     trace!("must update balance for asset: {}, ct: {:?}", asset, ciphertext.to_bytes());
                     let value = if let Some(cache) = balance_cache {
-                        cache
+                        Some(cache)
                     } else {
                         trace!("Decrypting balance for asset {}", asset);
-                        Arc::clone(&self.wallet).decrypt_ciphertext(ciphertext.decompressed()?.clone()).await?
+                        Arc::clone(&self.wallet).try_decrypt_ciphertext(ciphertext.decompressed()?.clone()).await?
                     };
 
                     // Inform the change of the balance
-                    self.wallet.propagate_event(Event::BalanceChanged(BalanceChanged {
-                        asset: asset.clone(),
-                        balance: value
-                    })).await;
+                    if let Some(value) = value {
+                        self.wallet.propagate_event(Event::BalanceChanged(BalanceChanged {
+                            asset: asset.clone(),
+                            balance: value
+                        })).await;
+                    }
 
                     // Update the balance
+                    // In watch-only mode, only the ciphertext is tracked
                     let mut storage = self.wallet.get_storage().write().await;
-                    storage.set_balance_for(asset, Balance::new(value, ciphertext)).await?;
+                    storage.set_balance_for(asset, Balance::new(value.unwrap_or(0), ciphertext)).await?;
 
                     // We should sync new blocks to get the TXs
                     should_sync_blocks = true;
@@ -733,25 +947,157 @@ impl NetworkHandler {
         // Propagate the event
         self.wallet.propagate_event(Event::NewTopoHeight { topoheight: daemon_topoheight }).await;
         debug!("Synced to topoheight {}", daemon_topoheight);
+
+        // A failure here must not stop the syncing
+        if let Err(e) = self.check_pending_transactions(address).await {
+            warn!("Error while checking pending transactions: {}", e);
+        }
+
+        Ok(())
+    }
+
+    // Verify the state of our pending transactions against the daemon
+    // Transactions included in a block are no longer tracked,
+    // the ones dropped from mempool are rebroadcasted,
+    // and the ones that can't be included anymore are marked as failed
+    async fn check_pending_transactions(&self, address: &Address) -> Result<(), Error> {
+        trace!("check pending transactions");
+        let pending_transactions = {
+            let storage = self.wallet.get_storage().read().await;
+            storage.get_pending_transactions()?
+        };
+
+        if pending_transactions.iter().all(PendingTransaction::is_failed) {
+            return Ok(())
+        }
+
+        // No mempool cache means that we don't have any TX in mempool
+        let mempool_txs: HashSet<Hash> = match self.get_api().get_mempool_cache_if_any(address).await? {
+            Some(cache) => cache.get_txs().iter().cloned().collect(),
+            None => HashSet::new()
+        };
+        // An account without nonce never executed any transaction
+        let chain_nonce = if self.get_api().has_nonce(address).await? {
+            self.get_api().get_nonce(address).await?.version.get_nonce()
+        } else {
+            0
+        };
+
+        // Lowest nonce that failed, all pending TXs above it are failing too
+        let mut failed_nonce: Option<u64> = None;
+        for mut pending in pending_transactions.into_iter().filter(|pending| !pending.is_failed()) {
+            let hash = pending.get_hash().clone();
+            let included = {
+                let storage = self.wallet.get_storage().read().await;
+                storage.has_transaction(&hash)?
+            };
+
+            if included {
+                debug!("Pending transaction {} is now included in a block", hash);
+                let mut storage = self.wallet.get_storage().write().await;
+                storage.delete_pending_transaction(&hash)?;
+                continue;
+            }
+
+            let failure = if failed_nonce.is_some_and(|nonce| nonce < pending.get_nonce()) {
+                Some(FailureReason::PreviousFailed)
+            } else if mempool_txs.contains(&hash) {
+                None
+            } else if pending.get_nonce() < chain_nonce {
+                Some(FailureReason::NonceConsumed)
+            } else {
+                // Reference block must still be in the chain for the TX to be accepted
+                let reference = pending.get_transaction().get_reference();
+                let block = self.get_api().get_block_at_topoheight(reference.topoheight).await?;
+                if *block.hash != reference.hash {
+                    Some(FailureReason::ReferenceExpired)
+                } else {
+                    debug!("Pending transaction {} is not in mempool, rebroadcasting it", hash);
+                    if let Err(e) = self.get_api().submit_transaction(pending.get_transaction()).await {
+                        warn!("Error while rebroadcasting transaction {}: {}", hash, e);
+                    }
+                    pending.increment_rebroadcasts();
+                    None
+                }
+            };
+
+            let mut storage = self.wallet.get_storage().write().await;
+            if let Some(reason) = failure {
+                warn!("Pending transaction {} has failed: {}", hash, reason);
+                pending.set_failed(reason);
+                if failed_nonce.is_none() {
+                    failed_nonce = Some(pending.get_nonce());
+                }
+
+                // Only the unconfirmed balances created by this TX are removed
+                // Balances of the pending TXs with a lower nonce are kept to build the next TX on top of them
+                storage.delete_unconfirmed_balances_of(&hash).await?;
+            }
+
+            storage.set_pending_transaction(&pending)?;
+        }
+
+        // Restore the nonce as it was before building the failed transactions
+        if let Some(nonce) = failed_nonce {
+            let mut storage = self.wallet.get_storage().write().await;
+            storage.set_nonce(nonce.max(chain_nonce))?;
+        }
+
         Ok(())
     }
 
     // Runs an infinite loop to sync on each new block added in chain
     // Because of potential forks and DAG reorg during attacks,
     // we verify the last valid topoheight where changes happened
+    // If several daemons are available, we switch to another one
+    // when the connection is lost, when a request fails or when the daemon is unhealthy
     async fn start_syncing(self: &Arc<Self>) -> Result<(), Error> {
         // Generate only one time the address
         let address = self.wallet.get_address();
+        // Health checks are only done if we have another daemon to switch to
+        let failover = self.daemons.len() > 1;
+        let period = Duration::from_secs(DAEMON_HEALTH_CHECK_INTERVAL);
+        let mut health_check = interval_at(Instant::now() + period, period);
+
+        loop {
+            let offline_notified = match self.sync_with_daemon(&address, failover, &mut health_check).await {
+                Ok(offline_notified) => offline_notified,
+                // The daemon may have died in the middle of a request, try another one before giving up
+                Err(e) if failover => {
+                    warn!("Error while syncing, checking the other daemons: {}", e);
+                    if !self.check_daemons_health().await {
+                        return Err(e)
+                    }
+
+                    self.wallet.propagate_event(Event::Offline).await;
+                    true
+                },
+                Err(e) => return Err(e)
+            };
+
+            // We switched to another daemon after losing the connection
+            // A switch done by a health check is not visible
+            if offline_notified {
+                self.wallet.propagate_event(Event::Online).await;
+            }
+        }
+    }
+
+    // Sync with the daemon used until we switch to another one
+    // Returns true if the Offline event was already propagated before the switch
+    async fn sync_with_daemon(self: &Arc<Self>, address: &Address, failover: bool, health_check: &mut Interval) -> Result<bool, Error> {
+        let api = self.get_api();
         // Do a first sync to be up-to-date with the daemon
-        self.sync(&address, None).await?;
+        // After a switch, it also verifies that we are still on the right chain
+        self.sync(address, None).await?;
 
         // Thanks to websocket, we can be notified when a new block is added in chain
         // this allows us to have a instant sync of each new block instead of polling periodically
-        let mut receiver = self.api.on_new_block_event().await?;
+        let mut receiver = api.on_new_block_event().await?;
 
         // Network events to detect if we are online or offline
-        let mut on_connection = self.api.on_connection().await;
-        let mut on_connection_lost = self.api.on_connection_lost().await;
+        let mut on_connection = api.on_connection().await;
+        let mut on_connection_lost = api.on_connection_lost().await;
 
         loop {
             tokio::select! {
@@ -760,14 +1106,14 @@ impl NetworkHandler {
                 res = receiver.next() => {
                     trace!("on_new_block_event");
                     let event = res?;
-                    self.sync(&address, Some(event)).await?;
+                    self.sync(address, Some(event)).await?;
                 },
                 // Detect network events
                 res = on_connection.recv() => {
                     trace!("on_connection");
                     res?;
                     // We are connected again, make sure we are still up-to-date with node 
-                    self.sync(&address, None).await?;
+                    self.sync(address, None).await?;
 
                     self.wallet.propagate_event(Event::Online).await;
                 },
@@ -775,6 +1121,16 @@ impl NetworkHandler {
                     trace!("on_connection_lost");
                     res?;
                     self.wallet.propagate_event(Event::Offline).await;
+
+                    if failover && self.check_daemons_health().await {
+                        return Ok(true)
+                    }
+                },
+                _ = health_check.tick(), if failover => {
+                    trace!("health_check");
+                    if self.check_daemons_health().await {
+                        return Ok(false)
+                    }
                 }
             }
         }
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use log::trace;
+use serde::{Deserialize, Serialize};
+use xelis_common::{
+    account::CiphertextCache,
+    crypto::{Address, Hash, SIGNATURE_SIZE},
+    network::Network,
+    serializer::Serializer,
+    transaction::{
+        builder::{FeeBuilder, TransactionTypeBuilder},
+        Reference,
+        Transaction,
+        TransactionType
+    }
+};
+use crate::{
+    daemon_api::DaemonAPI,
+    wallet::WalletError
+};
+
+// Request exported by an online (watch only) process
+// It contains everything needed by the TransactionBuilderState
+// so an offline wallet can build and sign the transaction
+// without any connection to a daemon
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnsignedTransactionRequest {
+    pub network: Network,
+    // Account that will sign the transaction
+    pub source: Address,
+    pub tx_type: TransactionTypeBuilder,
+    pub fee: FeeBuilder,
+    pub nonce: u64,
+    pub reference: Reference,
+    // Encrypted balances for each asset used by the transaction
+    pub balances: HashMap<Hash, CiphertextCache>,
+    // Destinations already registered on chain in stable height
+    pub registered_keys: Vec<Address>
+}
+
+impl UnsignedTransactionRequest {
+    // Fetch from the daemon the current state of the source account
+    // If the account has pending transactions in mempool,
+    // the cached balances and nonce from mempool are used
+    pub async fn fetch(api: &DaemonAPI, network: Network, source: Address, tx_type: TransactionTypeBuilder, fee: FeeBuilder) -> Result<Self, WalletError> {
+        trace!("fetch unsigned transaction request for {}", source);
+        if source.is_mainnet() != network.is_mainnet() {
+            return Err(WalletError::InvalidAddressParams)
+        }
+
+        let info = api.get_info().await?;
+        let reference = Reference {
+            topoheight: info.topoheight,
+            hash: info.top_block_hash
+        };
+
+        let mempool = api.get_mempool_cache_if_any(&source).await?;
+        let nonce = match mempool.as_ref() {
+            Some(cache) => cache.get_max_nonce() + 1,
+            None => api.get_nonce(&source).await?.version.get_nonce()
+        };
+
+        let mut balances = HashMap::new();
+        for asset in tx_type.used_assets() {
+            trace!("Fetching balance for asset {}", asset);
+            let ciphertext = match mempool.as_ref().and_then(|cache| cache.get_balances().get(&asset)) {
+                Some(ciphertext) => ciphertext.clone(),
+                None => api.get_balance(&source, &asset).await?.version.take_balance()
+            };
+            balances.insert(asset, ciphertext);
+        }
+
+        let mut registered_keys = Vec::new();
+        for key in tx_type.used_keys() {
+            let addr = key.to_address(network.is_mainnet());
+            trace!("Checking if {} is registered in stable height", addr);
+            if api.is_account_registered(&addr, true).await? {
+                registered_keys.push(addr);
+            }
+        }
+
+        Ok(Self {
+            network,
+            source,
+            tx_type,
+            fee,
+            nonce,
+            reference,
+            balances,
+            registered_keys
+        })
+    }
+
+    // Verify that the signed transaction is the one requested
+    // Amounts of the transfers are encrypted and can't be checked here
+    pub fn verify_signed_transaction(&self, tx: &Transaction) -> Result<(), WalletError> {
+        trace!("verify signed transaction");
+        if tx.get_source() != self.source.get_public_key() {
+            return Err(WalletError::SignedTransactionMismatch("source"))
+        }
+
+        if tx.get_nonce() != self.nonce {
+            return Err(WalletError::SignedTransactionMismatch("nonce"))
+        }
+
+        if *tx.get_reference() != self.reference {
+            return Err(WalletError::SignedTransactionMismatch("reference"))
+        }
+
+        if let FeeBuilder::Value(value) = self.fee {
+            if tx.get_fee() != value {
+                return Err(WalletError::SignedTransactionMismatch("fee"))
+            }
+        }
+
+        match (&self.tx_type, tx.get_data()) {
+            (TransactionTypeBuilder::Transfers(expected), TransactionType::Transfers(transfers)) => {
+                if expected.len() != transfers.len() {
+                    return Err(WalletError::SignedTransactionMismatch("transfers count"))
+                }
+
+                for (expected, transfer) in expected.iter().zip(transfers) {
+                    if expected.asset != *transfer.get_asset() || expected.destination.get_public_key() != transfer.get_destination() {
+                        return Err(WalletError::SignedTransactionMismatch("transfer"))
+                    }
+                }
+            },
+            (TransactionTypeBuilder::Burn(expected), TransactionType::Burn(payload)) => {
+                if expected.asset != payload.asset || expected.amount != payload.amount {
+                    return Err(WalletError::SignedTransactionMismatch("burn"))
+                }
+            },
+            _ => return Err(WalletError::SignedTransactionMismatch("transaction type"))
+        };
+
+        let owner = tx.get_source().decompress()
+            .map_err(|_| WalletError::SignedTransactionMismatch("source"))?;
+        let bytes = tx.to_bytes();
+        if !tx.get_signature().verify(&bytes[..bytes.len() - SIGNATURE_SIZE], &owner) {
+            return Err(WalletError::InvalidSignature)
+        }
+
+        Ok(())
+    }
+}
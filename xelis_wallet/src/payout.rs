@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace, warn};
+use xelis_common::{
+    api::{
+        wallet::{PayoutEntry, PayoutStatus},
+        DataElement,
+        DataValue
+    },
+    config::MAX_TRANSACTION_SIZE,
+    crypto::{Address, AddressType, Hash, Hashable, PublicKey},
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    transaction::{
+        builder::{FeeBuilder, TransactionBuilder, TransactionTypeBuilder, TransferBuilder},
+        EXTRA_DATA_LIMIT_SIZE,
+        MAX_TRANSFER_COUNT
+    }
+};
+use crate::wallet::{Wallet, WalletError};
+
+// Report of a batch payout saved in the wallet storage
+// so a payout can be resumed even if the report file or the RPC response is lost
+pub struct PayoutReport(pub Vec<PayoutEntry>);
+
+impl Serializer for PayoutReport {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u32(&(self.0.len() as u32));
+        for entry in &self.0 {
+            writer.write_bool(entry.destination.is_mainnet());
+            entry.destination.get_public_key().write(writer);
+            entry.destination.get_type().write(writer);
+            writer.write_hash(&entry.asset);
+            writer.write_u64(&entry.amount);
+            entry.extra_data.write(writer);
+            match &entry.status {
+                PayoutStatus::Pending => writer.write_u8(0),
+                PayoutStatus::Broadcasting { hash, nonce } => {
+                    writer.write_u8(1);
+                    writer.write_hash(hash);
+                    writer.write_u64(nonce);
+                },
+                PayoutStatus::Submitted(hash) => {
+                    writer.write_u8(2);
+                    writer.write_hash(hash);
+                },
+                PayoutStatus::Failed(reason) => {
+                    writer.write_u8(3);
+                    // Reason may be longer than a string length prefix allows
+                    let reason = &reason.as_bytes()[..reason.len().min(u16::MAX as usize)];
+                    writer.write_u16(reason.len() as u16);
+                    writer.write_bytes(reason);
+                }
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let count = reader.read_u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let mainnet = reader.read_bool()?;
+            let key = PublicKey::read(reader)?;
+            let addr_type = AddressType::read(reader)?;
+            let asset = reader.read_hash()?;
+            let amount = reader.read_u64()?;
+            let extra_data = Option::read(reader)?;
+            let status = match reader.read_u8()? {
+                0 => PayoutStatus::Pending,
+                1 => PayoutStatus::Broadcasting {
+                    hash: reader.read_hash()?,
+                    nonce: reader.read_u64()?
+                },
+                2 => PayoutStatus::Submitted(reader.read_hash()?),
+                3 => {
+                    let size = reader.read_u16()? as usize;
+                    PayoutStatus::Failed(String::from_utf8_lossy(reader.read_bytes_ref(size)?).into_owned())
+                },
+                _ => return Err(ReaderError::InvalidValue)
+            };
+
+            entries.push(PayoutEntry {
+                destination: Address::new(mainnet, addr_type, key),
+                asset,
+                amount,
+                extra_data,
+                status
+            });
+        }
+
+        Ok(Self(entries))
+    }
+}
+
+// Bytes added to the extra data once encrypted:
+// u16 length prefix and the Poly1305 tag
+const EXTRA_DATA_OVERHEAD: usize = 2 + 16;
+
+// Size of the extra data once encrypted in the transfer
+// Data integrated in the destination address is also sent as extra data
+fn extra_data_size(entry: &PayoutEntry) -> usize {
+    let (integrated_data, _) = entry.destination.clone().extract_data();
+    entry.extra_data.as_ref()
+        .or(integrated_data.as_ref())
+        .map(|data| data.size() + EXTRA_DATA_OVERHEAD)
+        .unwrap_or(0)
+}
+
+fn to_transfer(entry: &PayoutEntry) -> TransferBuilder {
+    TransferBuilder {
+        destination: entry.destination.clone(),
+        asset: entry.asset.clone(),
+        amount: entry.amount,
+        extra_data: entry.extra_data.clone()
+    }
+}
+
+// Select the next pending entries that can fit in one transaction
+// Entries that can never be included in a transaction are marked as failed
+fn next_batch(source: &PublicKey, mainnet: bool, entries: &mut [PayoutEntry], fee: &FeeBuilder) -> Vec<usize> {
+    let mut batch = Vec::new();
+    let mut total_extra_data_size = 0;
+    for (i, entry) in entries.iter_mut().enumerate() {
+        if !matches!(entry.status, PayoutStatus::Pending) {
+            continue;
+        }
+
+        if entry.destination.get_public_key() == source {
+            entry.status = PayoutStatus::Failed("Destination is the wallet itself".into());
+            continue;
+        }
+
+        if entry.destination.is_mainnet() != mainnet {
+            entry.status = PayoutStatus::Failed("Destination is not on the same network".into());
+            continue;
+        }
+
+        if entry.extra_data.is_some() && !entry.destination.is_normal() {
+            entry.status = PayoutStatus::Failed("Extra data can't be set with an integrated address".into());
+            continue;
+        }
+
+        let size = extra_data_size(entry);
+        if size > EXTRA_DATA_LIMIT_SIZE {
+            entry.status = PayoutStatus::Failed("Extra data is too large".into());
+            continue;
+        }
+
+        if batch.len() >= MAX_TRANSFER_COUNT || total_extra_data_size + size > EXTRA_DATA_LIMIT_SIZE {
+            break;
+        }
+
+        total_extra_data_size += size;
+        batch.push(i);
+    }
+
+    // Remove the last transfers until the transaction fits in the size limit
+    while batch.len() > 1 {
+        let transfers = batch.iter().map(|i| to_transfer(&entries[*i])).collect();
+        let builder = TransactionBuilder::new(0, source.clone(), TransactionTypeBuilder::Transfers(transfers), fee.clone());
+        if builder.estimate_size() <= MAX_TRANSACTION_SIZE {
+            break;
+        }
+        batch.pop();
+    }
+
+    batch
+}
+
+// Find out if a transaction built for a payout was accepted by the daemon
+// Returns Submitted if it is known, Pending if its nonce was used by another transaction
+// or if it was never accepted so it can never be executed, and None if it can't be known yet
+async fn resolve_broadcasting(wallet: &Wallet, hash: &Hash, nonce: u64) -> Result<Option<PayoutStatus>, WalletError> {
+    if wallet.get_storage().read().await.has_transaction(hash)? {
+        return Ok(Some(PayoutStatus::Submitted(hash.clone())))
+    }
+
+    let network_handler = wallet.get_network_handler().await.lock().await;
+    let Some(network_handler) = network_handler.as_ref() else {
+        return Err(WalletError::NotOnlineMode)
+    };
+
+    let api = network_handler.get_api();
+    match api.get_transaction_with_status(hash).await {
+        Ok(tx) if tx.in_mempool || tx.executed_in_block.is_some() => return Ok(Some(PayoutStatus::Submitted(hash.clone()))),
+        Ok(_) => {},
+        Err(e) => debug!("Payout transaction {} not found: {}", hash, e)
+    };
+
+    // An account without nonce never executed any transaction
+    let account_nonce = match api.get_nonce(&wallet.get_address()).await {
+        Ok(result) => result.version.get_nonce(),
+        Err(e) => {
+            debug!("Error while retrieving account nonce: {}", e);
+            return Ok(None)
+        }
+    };
+
+    if account_nonce > nonce {
+        return Ok(Some(PayoutStatus::Pending))
+    }
+
+    if account_nonce < nonce {
+        return Ok(None)
+    }
+
+    // Its nonce is the next one to be used, so it was never accepted if it's not in mempool
+    let in_mempool = api.get_mempool_cache_if_any(&wallet.get_address()).await?
+        .is_some_and(|cache| cache.get_txs().contains(hash));
+    if in_mempool {
+        return Ok(Some(PayoutStatus::Submitted(hash.clone())))
+    }
+
+    // Forget the transaction and build the next ones from its nonce
+    debug!("Payout transaction {} was never accepted by the daemon", hash);
+    let mut storage = wallet.get_storage().write().await;
+    storage.delete_pending_transaction(hash)?;
+    storage.delete_unconfirmed_balances_of(hash).await?;
+    storage.set_nonce(nonce)?;
+
+    Ok(Some(PayoutStatus::Pending))
+}
+
+// Save the report in the wallet storage before calling the callback
+async fn save_report<F>(wallet: &Wallet, entries: &[PayoutEntry], on_report: &mut F) -> Result<(), WalletError>
+where
+    F: FnMut(&[PayoutEntry]) -> Result<(), WalletError>
+{
+    {
+        let mut storage = wallet.get_storage().write().await;
+        storage.set_payout_report(&PayoutReport(entries.to_vec()))?;
+    }
+    on_report(entries)
+}
+
+// Pay all the pending entries using as few transactions as possible
+// Nonces are chained through the unconfirmed balances of the wallet storage
+// The report is saved in the storage and the callback is called before broadcasting
+// each transaction and once it is submitted, once a transaction fails,
+// remaining entries are kept as pending
+// Entries that failed in a previous report are retried, entries of a transaction
+// being broadcasted are only retried once it is known that it will never be executed
+pub async fn process_payouts<F>(wallet: &Wallet, entries: &mut [PayoutEntry], fee: FeeBuilder, mut on_report: F) -> Result<(), WalletError>
+where
+    F: FnMut(&[PayoutEntry]) -> Result<(), WalletError>
+{
+    trace!("process payouts");
+    if !wallet.is_online().await {
+        return Err(WalletError::NotOnlineMode)
+    }
+
+    let mut resolved: HashMap<Hash, Option<PayoutStatus>> = HashMap::new();
+    for entry in entries.iter_mut() {
+        match &entry.status {
+            PayoutStatus::Failed(_) => entry.status = PayoutStatus::Pending,
+            PayoutStatus::Broadcasting { hash, nonce } => {
+                let status = match resolved.get(hash) {
+                    Some(status) => status.clone(),
+                    None => {
+                        let status = resolve_broadcasting(wallet, hash, *nonce).await?;
+                        resolved.insert(hash.clone(), status.clone());
+                        status
+                    }
+                };
+
+                if let Some(status) = status {
+                    entry.status = status;
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let unresolved = entries.iter().filter(|entry| matches!(entry.status, PayoutStatus::Broadcasting { .. })).count();
+    if unresolved > 0 {
+        warn!("{} payout entries are in a transaction not yet seen by the daemon, they will not be sent again", unresolved);
+    }
+    save_report(wallet, entries, &mut on_report).await?;
+
+    let source = wallet.get_public_key();
+    let mainnet = wallet.get_network().is_mainnet();
+    loop {
+        let batch = next_batch(&source, mainnet, entries, &fee);
+        if batch.is_empty() {
+            break;
+        }
+
+        debug!("Building payout transaction with {} transfers", batch.len());
+        let transfers = batch.iter().map(|i| to_transfer(&entries[*i])).collect();
+        let tx = match wallet.create_transaction(TransactionTypeBuilder::Transfers(transfers), fee.clone()).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                let status = PayoutStatus::Failed(e.to_string());
+                for i in batch {
+                    entries[i].status = status.clone();
+                }
+                break;
+            }
+        };
+
+        // Save the transaction hash before broadcasting it,
+        // so these entries are not paid again if the wallet stops in between
+        let hash = tx.hash();
+        let status = PayoutStatus::Broadcasting {
+            hash: hash.clone(),
+            nonce: tx.get_nonce()
+        };
+        for i in batch.iter() {
+            entries[*i].status = status.clone();
+        }
+        save_report(wallet, entries, &mut on_report).await?;
+
+        // The daemon may have accepted the transaction even if an error is returned,
+        // so entries are kept as broadcasting until resolved when resuming
+        if let Err(e) = wallet.submit_transaction(&tx).await {
+            warn!("Error while submitting payout transaction {}: {}", hash, e);
+            break;
+        }
+
+        for i in batch {
+            entries[i].status = PayoutStatus::Submitted(hash.clone());
+        }
+        save_report(wallet, entries, &mut on_report).await?;
+    }
+
+    save_report(wallet, entries, &mut on_report).await
+}
+
+// Parse a CSV payout file with the following columns:
+// address,asset,amount[,extra data as string]
+// Amount is in atomic units, a header line starting with "address" is skipped
+pub fn parse_payout_csv(content: &str) -> Result<Vec<PayoutEntry>> {
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.starts_with("address")) {
+            continue;
+        }
+
+        let mut columns = line.splitn(4, ',').map(str::trim);
+        let (Some(address), Some(asset), Some(amount)) = (columns.next(), columns.next(), columns.next()) else {
+            return Err(anyhow!("Invalid payout entry at line {}", i + 1))
+        };
+
+        let extra_data = columns.next()
+            .filter(|data| !data.is_empty())
+            .map(|data| DataElement::Value(DataValue::String(data.to_owned())));
+
+        entries.push(PayoutEntry {
+            destination: Address::from_string(&address.to_owned()).context(format!("Invalid address at line {}", i + 1))?,
+            asset: Hash::from_hex(asset.to_owned()).context(format!("Invalid asset at line {}", i + 1))?,
+            amount: amount.parse().context(format!("Invalid amount at line {}", i + 1))?,
+            extra_data,
+            status: PayoutStatus::Pending
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use serde::Serialize;
+use xelis_common::{
+    crypto::{Hash, Hashable},
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    time::TimestampSeconds,
+    transaction::Transaction
+};
+
+// Why a pending transaction will never be included in a block
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    // Nonce was used by another transaction
+    NonceConsumed,
+    // Block used as reference is no longer in the chain
+    ReferenceExpired,
+    // A transaction with a lower nonce failed
+    PreviousFailed
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonceConsumed => write!(f, "nonce consumed by another transaction"),
+            Self::ReferenceExpired => write!(f, "reference block expired"),
+            Self::PreviousFailed => write!(f, "previous transaction failed")
+        }
+    }
+}
+
+impl Serializer for FailureReason {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(match self {
+            Self::NonceConsumed => 0,
+            Self::ReferenceExpired => 1,
+            Self::PreviousFailed => 2
+        });
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(match reader.read_u8()? {
+            0 => Self::NonceConsumed,
+            1 => Self::ReferenceExpired,
+            2 => Self::PreviousFailed,
+            _ => return Err(ReaderError::InvalidValue)
+        })
+    }
+}
+
+// Outgoing transaction submitted to the daemon but not yet included in a block
+// It is kept until we see it in a block or it gets marked as failed
+pub struct PendingTransaction {
+    hash: Hash,
+    transaction: Transaction,
+    // When it was submitted for the first time
+    submitted_at: TimestampSeconds,
+    // How many times it was rebroadcasted after being dropped from mempool
+    rebroadcasts: u32,
+    failure: Option<FailureReason>
+}
+
+impl PendingTransaction {
+    pub fn new(transaction: Transaction, submitted_at: TimestampSeconds) -> Self {
+        Self {
+            hash: transaction.hash(),
+            transaction,
+            submitted_at,
+            rebroadcasts: 0,
+            failure: None
+        }
+    }
+
+    pub fn get_hash(&self) -> &Hash {
+        &self.hash
+    }
+
+    pub fn get_transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+
+    pub fn get_nonce(&self) -> u64 {
+        self.transaction.get_nonce()
+    }
+
+    pub fn get_submitted_at(&self) -> TimestampSeconds {
+        self.submitted_at
+    }
+
+    pub fn get_rebroadcasts(&self) -> u32 {
+        self.rebroadcasts
+    }
+
+    pub fn increment_rebroadcasts(&mut self) {
+        self.rebroadcasts = self.rebroadcasts.saturating_add(1);
+    }
+
+    pub fn get_failure(&self) -> Option<FailureReason> {
+        self.failure
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    pub fn set_failed(&mut self, reason: FailureReason) {
+        self.failure = Some(reason);
+    }
+}
+
+impl Serializer for PendingTransaction {
+    fn write(&self, writer: &mut Writer) {
+        self.transaction.write(writer);
+        writer.write_u64(&self.submitted_at);
+        writer.write_u32(&self.rebroadcasts);
+        self.failure.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let transaction = Transaction::read(reader)?;
+        let submitted_at = reader.read_u64()?;
+        let rebroadcasts = reader.read_u32()?;
+        let failure = Option::read(reader)?;
+
+        Ok(Self {
+            hash: transaction.hash(),
+            transaction,
+            submitted_at,
+            rebroadcasts,
+            failure
+        })
+    }
+}
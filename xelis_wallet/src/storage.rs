@@ -6,6 +6,7 @@ use indexmap::IndexMap;
 use log::trace;
 use lru::LruCache;
 use sled::{
+    Batch,
     Tree,
     Db
 };
@@ -27,6 +28,7 @@ use xelis_common::{
         PublicKey
     },
     network::Network,
+    transaction::payment_proof::PaymentProof,
     serializer::{
         Reader,
         ReaderError,
@@ -40,6 +42,8 @@ use anyhow::{
     anyhow
 };
 use crate::{
+    address_book::Contact,
+    backup::BackupTree,
     cipher::Cipher,
     config::SALT_SIZE,
     entry::{
@@ -47,18 +51,33 @@ use crate::{
         TransactionEntry,
         Transfer
     },
+    invoice::Invoice,
+    kdf::KdfParams,
+    payout::PayoutReport,
+    pending::PendingTransaction,
     wallet::WalletError
 };
 use log::error;
 
+#[cfg(feature = "api_server")]
+use crate::api::TrustedApplication;
+
 // keys used to retrieve from storage
 const NONCE_KEY: &[u8] = b"NONCE";
 const SALT_KEY: &[u8] = b"SALT";
 // Password + salt is necessary to decrypt master key
 const PASSWORD_SALT_KEY: &[u8] = b"PSALT";
+// Parameters of the KDF used to derive the password-based key
+const KDF_PARAMS_KEY: &[u8] = b"KDF";
 // Master key to encrypt/decrypt while interacting with the storage 
 const MASTER_KEY: &[u8] = b"MKEY";
 const PRIVATE_KEY: &[u8] = b"PKEY";
+// Private key encoded in the seed when a passphrase is used
+const SEED_KEY: &[u8] = b"SKEY";
+// Public key watched by a watch-only wallet
+const WATCH_ONLY_KEY: &[u8] = b"WKEY";
+// Key signing the applications approved through XSWD
+const XSWD_KEY: &[u8] = b"XKEY";
 
 // const used for online mode
 // represent the daemon topoheight
@@ -66,6 +85,12 @@ const TOPOHEIGHT_KEY: &[u8] = b"TOPH";
 // represent the daemon top block hash
 const TOP_BLOCK_HASH_KEY: &[u8] = b"TOPBH";
 const NETWORK: &[u8] = b"NET";
+// Number of accounts derived from the seed
+const ACCOUNTS_COUNT_KEY: &[u8] = b"ACCS";
+// Account currently selected
+const SELECTED_ACCOUNT_KEY: &[u8] = b"SACC";
+// Report of the last batch payout of the account
+const PAYOUT_REPORT_KEY: &[u8] = b"PAYOUT";
 
 // Default cache size
 const DEFAULT_CACHE_SIZE: usize = 100;
@@ -114,12 +139,24 @@ pub struct EncryptedStorage {
     transactions: Tree,
     // balances for each asset
     balances: Tree,
-    // extra data (network, topoheight, etc)
+    // extra data (network, private key, accounts, etc)
     extra: Tree,
+    // extra data of the selected account (nonce, topoheight, top block hash)
+    account_extra: Tree,
     // all assets tracked by the wallet
     assets: Tree,
     // This tree is used to store all topoheight where a change in the wallet occured
     changes_topoheight: Tree,
+    // Payment proofs of the outgoing transfers, stored by TX hash
+    payment_proofs: Tree,
+    // Outgoing transactions submitted but not yet included in a block, stored by TX hash
+    pending_transactions: Tree,
+    // Invoices created by the selected account, stored by invoice ID
+    invoices: Tree,
+    // Contacts of the address book, shared by all accounts
+    contacts: Tree,
+    // XSWD applications approved by the user, stored by application ID
+    xswd_applications: Tree,
     // The inner storage
     inner: Storage,
     // Caches
@@ -128,27 +165,49 @@ pub struct EncryptedStorage {
     // it is used to store the balance before the transaction is confirmed
     // so we can build several txs without having to wait for the confirmation
     // We store it in a VecDeque so for each TX we have an entry and can just retrieve it
-    unconfirmed_balances_cache: Mutex<HashMap<Hash, VecDeque<Balance>>>,
+    // Each entry is linked to the hash of the TX that created it
+    unconfirmed_balances_cache: Mutex<HashMap<Hash, VecDeque<(Hash, Balance)>>>,
     assets_cache: Mutex<LruCache<Hash, u8>>,
     // Cache for the synced topoheight
-    synced_topoheight: Option<u64>
+    synced_topoheight: Option<u64>,
+    // Index of the account selected
+    account: u32
+}
+
+// Open the tree for the requested account
+// Account 0 keeps the original tree names to stay compatible with existing wallets
+fn open_account_tree(db: &Db, cipher: &Cipher, name: &str, account: u32) -> Result<Tree> {
+    let key = if account == 0 {
+        cipher.hash_key(name)
+    } else {
+        cipher.hash_key(format!("{}_{}", name, account))
+    };
+
+    Ok(db.open_tree(&key)?)
 }
 
 impl EncryptedStorage {
     pub fn new(inner: Storage, key: &[u8], salt: [u8; SALT_SIZE], network: Network) -> Result<Self> {
         let cipher = Cipher::new(key, Some(salt))?;
         let mut storage = Self {
-            transactions: inner.db.open_tree(&cipher.hash_key("transactions"))?,
-            balances: inner.db.open_tree(&cipher.hash_key("balances"))?,
+            transactions: open_account_tree(&inner.db, &cipher, "transactions", 0)?,
+            balances: open_account_tree(&inner.db, &cipher, "balances", 0)?,
             extra: inner.db.open_tree(&cipher.hash_key("extra"))?,
-            assets: inner.db.open_tree(&cipher.hash_key("assets"))?,
-            changes_topoheight: inner.db.open_tree(&cipher.hash_key("changes_topoheight"))?,
+            account_extra: open_account_tree(&inner.db, &cipher, "extra", 0)?,
+            assets: open_account_tree(&inner.db, &cipher, "assets", 0)?,
+            changes_topoheight: open_account_tree(&inner.db, &cipher, "changes_topoheight", 0)?,
+            payment_proofs: open_account_tree(&inner.db, &cipher, "payment_proofs", 0)?,
+            pending_transactions: open_account_tree(&inner.db, &cipher, "pending_transactions", 0)?,
+            invoices: open_account_tree(&inner.db, &cipher, "invoices", 0)?,
+            contacts: inner.db.open_tree(&cipher.hash_key("contacts"))?,
+            xswd_applications: inner.db.open_tree(&cipher.hash_key("xswd_applications"))?,
             cipher,
             inner,
             balances_cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_CACHE_SIZE).unwrap())),
             unconfirmed_balances_cache: Mutex::new(HashMap::new()),
             assets_cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_CACHE_SIZE).unwrap())),
             synced_topoheight: None,
+            account: 0
         };
 
         if storage.has_network()? {
@@ -160,9 +219,75 @@ impl EncryptedStorage {
             storage.set_network(&network)?;
         }
 
+        // Load the trees of the account previously selected
+        let account = storage.get_selected_account()?;
+        if account != 0 {
+            storage.open_account(account)?;
+        }
+
         Ok(storage)
     }
 
+    // Open all the trees of the requested account and reset the caches
+    fn open_account(&mut self, account: u32) -> Result<()> {
+        trace!("open account {}", account);
+        let db = &self.inner.db;
+        self.transactions = open_account_tree(db, &self.cipher, "transactions", account)?;
+        self.balances = open_account_tree(db, &self.cipher, "balances", account)?;
+        self.account_extra = open_account_tree(db, &self.cipher, "extra", account)?;
+        self.assets = open_account_tree(db, &self.cipher, "assets", account)?;
+        self.changes_topoheight = open_account_tree(db, &self.cipher, "changes_topoheight", account)?;
+        self.payment_proofs = open_account_tree(db, &self.cipher, "payment_proofs", account)?;
+        self.pending_transactions = open_account_tree(db, &self.cipher, "pending_transactions", account)?;
+        self.invoices = open_account_tree(db, &self.cipher, "invoices", account)?;
+
+        self.balances_cache.get_mut().clear();
+        self.unconfirmed_balances_cache.get_mut().clear();
+        self.assets_cache.get_mut().clear();
+        self.synced_topoheight = None;
+        self.account = account;
+
+        Ok(())
+    }
+
+    // Select the account to use for balances, transactions, nonce and sync state
+    pub fn switch_account(&mut self, account: u32) -> Result<()> {
+        trace!("switch to account {}", account);
+        self.open_account(account)?;
+        self.save_to_disk(&self.extra, SELECTED_ACCOUNT_KEY, &account.to_be_bytes())
+    }
+
+    // Index of the account currently selected
+    pub fn get_account(&self) -> u32 {
+        self.account
+    }
+
+    // Retrieve the account selected last time
+    fn get_selected_account(&self) -> Result<u32> {
+        trace!("get selected account");
+        if !self.contains_data(&self.extra, SELECTED_ACCOUNT_KEY)? {
+            return Ok(0)
+        }
+
+        self.load_from_disk(&self.extra, SELECTED_ACCOUNT_KEY)
+    }
+
+    // Number of accounts created, there is always at least the account 0
+    pub fn get_accounts_count(&self) -> Result<u32> {
+        trace!("get accounts count");
+        if !self.contains_data(&self.extra, ACCOUNTS_COUNT_KEY)? {
+            return Ok(1)
+        }
+
+        self.load_from_disk(&self.extra, ACCOUNTS_COUNT_KEY)
+    }
+
+    // Set the number of accounts created
+    pub fn set_accounts_count(&mut self, count: u32) -> Result<()> {
+        trace!("set accounts count to {}", count);
+        self.save_to_disk(&self.extra, ACCOUNTS_COUNT_KEY, &count.to_be_bytes())
+    }
+
     // Await for the storage to be flushed
     pub async fn stop(&mut self) {
         if let Err(e) = self.inner.db.flush_async().await {
@@ -472,7 +597,7 @@ impl EncryptedStorage {
         let cache = self.unconfirmed_balances_cache.lock().await;
         if let Some(balances) = cache.get(asset) {
             // get the latest unconfirmed balance
-            if let Some(balance) = balances.back() {
+            if let Some((_, balance)) = balances.back() {
                 return Ok(Balance {
                     amount: balance.amount,
                     ciphertext: balance.ciphertext.clone()
@@ -488,7 +613,7 @@ impl EncryptedStorage {
     pub async fn get_unconfirmed_balance_decoded_for(&self, asset: &Hash, compressed_ct: &CompressedCiphertext) -> Result<Option<u64>> {
         let mut cache = self.unconfirmed_balances_cache.lock().await;
         if let Some(balances) = cache.get_mut(asset) {
-            for balance in balances.iter_mut() {
+            for (_, balance) in balances.iter_mut() {
                 if *balance.ciphertext.compressed() == *compressed_ct {
                     return Ok(Some(balance.amount));
                 }
@@ -498,12 +623,24 @@ impl EncryptedStorage {
         Ok(None)
     }
 
-    // Set the unconfirmed balance for this asset
-    pub async fn set_unconfirmed_balance_for(&self, asset: Hash, balance: Balance) -> Result<()> {
-        trace!("set unconfirmed balance for {}", asset);
+    // Set the unconfirmed balance for this asset created by the TX
+    pub async fn set_unconfirmed_balance_for(&self, asset: Hash, tx_hash: Hash, balance: Balance) -> Result<()> {
+        trace!("set unconfirmed balance for {} from {}", asset, tx_hash);
         let mut cache = self.unconfirmed_balances_cache.lock().await;
         let balances = cache.entry(asset).or_insert_with(VecDeque::new);
-        balances.push_back(balance);
+        balances.push_back((tx_hash, balance));
+
+        Ok(())
+    }
+
+    // Remove the unconfirmed balances created by this TX
+    // Used when the transaction can't be included anymore
+    pub async fn delete_unconfirmed_balances_of(&self, tx_hash: &Hash) -> Result<()> {
+        trace!("delete unconfirmed balances of {}", tx_hash);
+        let mut cache = self.unconfirmed_balances_cache.lock().await;
+        for balances in cache.values_mut() {
+            balances.retain(|(hash, _)| hash != tx_hash);
+        }
 
         Ok(())
     }
@@ -535,7 +672,7 @@ impl EncryptedStorage {
         {
             let mut cache = self.unconfirmed_balances_cache.lock().await;
             if let Some(balances) = cache.get_mut(asset) {
-                while let Some(mut b) = balances.pop_front() {
+                while let Some((_, mut b)) = balances.pop_front() {
                     if *b.ciphertext.compressed() == *balance.ciphertext.compressed() {
                         trace!("unconfirmed balance previously stored found for {}", asset);
                         break;
@@ -572,7 +709,8 @@ impl EncryptedStorage {
             }
         }
 
-        Ok(())
+        // Payments from deleted transactions will be recorded again during the sync
+        self.delete_invoice_payments_above_topoheight(topoheight)
     }
 
     // Filter when the data is deserialized to not load all transactions in memory
@@ -654,7 +792,8 @@ impl EncryptedStorage {
     // Delete all transactions from this wallet
     pub fn delete_transactions(&mut self) -> Result<()> {
         self.transactions.clear()?;
-        Ok(())
+        // All payments will be recorded again during the sync
+        self.delete_invoice_payments_above_topoheight(0)
     }
 
     // Delete all balances from this wallet
@@ -692,16 +831,227 @@ impl EncryptedStorage {
         self.contains_data(&self.transactions, hash.as_bytes())
     }
 
+    // Save the payment proofs of all transfers of an outgoing transaction
+    pub fn set_payment_proofs(&mut self, hash: &Hash, proofs: &Vec<PaymentProof>) -> Result<()> {
+        trace!("set payment proofs for {}", hash);
+        self.save_to_disk(&self.payment_proofs, hash.as_bytes(), &proofs.to_bytes())
+    }
+
+    // Retrieve the payment proofs of an outgoing transaction
+    pub fn get_payment_proofs(&self, hash: &Hash) -> Result<Vec<PaymentProof>> {
+        trace!("get payment proofs for {}", hash);
+        self.load_from_disk(&self.payment_proofs, hash.as_bytes())
+    }
+
+    // Check if we have the payment proofs of an outgoing transaction
+    pub fn has_payment_proofs(&self, hash: &Hash) -> Result<bool> {
+        trace!("has payment proofs for {}", hash);
+        self.contains_data(&self.payment_proofs, hash.as_bytes())
+    }
+
+    // Save a pending transaction, replacing the previous version if any
+    pub fn set_pending_transaction(&mut self, pending: &PendingTransaction) -> Result<()> {
+        trace!("set pending transaction {}", pending.get_hash());
+        self.save_to_disk(&self.pending_transactions, pending.get_hash().as_bytes(), &pending.to_bytes())
+    }
+
+    // Check if a transaction is still tracked as pending
+    pub fn has_pending_transaction(&self, hash: &Hash) -> Result<bool> {
+        trace!("has pending transaction {}", hash);
+        self.contains_data(&self.pending_transactions, hash.as_bytes())
+    }
+
+    // Stop tracking a pending transaction
+    pub fn delete_pending_transaction(&mut self, hash: &Hash) -> Result<()> {
+        trace!("delete pending transaction {}", hash);
+        self.delete_from_disk(&self.pending_transactions, hash.as_bytes())
+    }
+
+    // Retrieve all pending transactions ordered by nonce
+    pub fn get_pending_transactions(&self) -> Result<Vec<PendingTransaction>> {
+        trace!("get pending transactions");
+        let mut transactions = Vec::new();
+        for el in self.pending_transactions.iter().values() {
+            let value = el?;
+            let decrypted = self.cipher.decrypt_value(&value)?;
+            transactions.push(PendingTransaction::from_bytes(&decrypted)?);
+        }
+        transactions.sort_by_key(|pending| pending.get_nonce());
+
+        Ok(transactions)
+    }
+
+    // Save an invoice, replacing the previous version if any
+    pub fn set_invoice(&mut self, invoice: &Invoice) -> Result<()> {
+        trace!("set invoice {}", invoice.get_id());
+        self.save_to_disk(&self.invoices, invoice.get_id().as_bytes(), &invoice.to_bytes())
+    }
+
+    // Retrieve an invoice using its ID
+    pub fn get_invoice(&self, id: &Hash) -> Result<Invoice> {
+        trace!("get invoice {}", id);
+        self.load_from_disk(&self.invoices, id.as_bytes())
+    }
+
+    // Check if an invoice exists with this ID
+    pub fn has_invoice(&self, id: &Hash) -> Result<bool> {
+        trace!("has invoice {}", id);
+        self.contains_data(&self.invoices, id.as_bytes())
+    }
+
+    // Retrieve all invoices sorted by creation date
+    pub fn get_invoices(&self) -> Result<Vec<Invoice>> {
+        trace!("get invoices");
+        let mut invoices = Vec::new();
+        for el in self.invoices.iter().values() {
+            let value = el?;
+            let decrypted = self.cipher.decrypt_value(&value)?;
+            invoices.push(Invoice::from_bytes(&decrypted)?);
+        }
+        invoices.sort_by_key(|invoice| invoice.get_created_at());
+
+        Ok(invoices)
+    }
+
+    // Remove the invoice payments made above the topoheight
+    pub fn delete_invoice_payments_above_topoheight(&mut self, topoheight: u64) -> Result<()> {
+        trace!("delete invoice payments above topoheight {}", topoheight);
+        for mut invoice in self.get_invoices()? {
+            if invoice.remove_payments_above_topoheight(topoheight) {
+                self.set_invoice(&invoice)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Save a contact in the address book, replacing any contact with the same label
+    pub fn set_contact(&mut self, contact: &Contact) -> Result<()> {
+        trace!("set contact {}", contact.get_label());
+        self.save_to_disk(&self.contacts, contact.get_label().as_bytes(), &contact.to_bytes())
+    }
+
+    // Retrieve a contact using its label
+    pub fn get_contact(&self, label: &str) -> Result<Contact> {
+        trace!("get contact {}", label);
+        self.load_from_disk(&self.contacts, label.as_bytes())
+    }
+
+    // Check if a contact exists with this label
+    pub fn has_contact(&self, label: &str) -> Result<bool> {
+        trace!("has contact {}", label);
+        self.contains_data(&self.contacts, label.as_bytes())
+    }
+
+    // Delete a contact from the address book
+    pub fn delete_contact(&mut self, label: &str) -> Result<()> {
+        trace!("delete contact {}", label);
+        self.delete_from_disk(&self.contacts, label.as_bytes())
+    }
+
+    // Retrieve all contacts sorted by label
+    pub fn get_contacts(&self) -> Result<Vec<Contact>> {
+        trace!("get contacts");
+        let mut contacts = Vec::new();
+        for el in self.contacts.iter().values() {
+            let value = el?;
+            let decrypted = self.cipher.decrypt_value(&value)?;
+            contacts.push(Contact::from_bytes(&decrypted)?);
+        }
+        contacts.sort_by(|a, b| a.get_label().cmp(b.get_label()));
+
+        Ok(contacts)
+    }
+
+    // Save an application approved through XSWD
+    #[cfg(feature = "api_server")]
+    pub fn set_xswd_application(&mut self, application: &TrustedApplication) -> Result<()> {
+        trace!("set xswd application {}", application.get_data().get_id());
+        self.save_to_disk(&self.xswd_applications, application.get_data().get_id().as_bytes(), &application.to_bytes())
+    }
+
+    // Retrieve an application approved through XSWD using its ID
+    #[cfg(feature = "api_server")]
+    pub fn get_xswd_application(&self, id: &str) -> Result<TrustedApplication> {
+        trace!("get xswd application {}", id);
+        self.load_from_disk(&self.xswd_applications, id.as_bytes())
+    }
+
+    // Check if an application was approved through XSWD
+    pub fn has_xswd_application(&self, id: &str) -> Result<bool> {
+        trace!("has xswd application {}", id);
+        self.contains_data(&self.xswd_applications, id.as_bytes())
+    }
+
+    // Revoke an application approved through XSWD
+    pub fn delete_xswd_application(&mut self, id: &str) -> Result<()> {
+        trace!("delete xswd application {}", id);
+        self.delete_from_disk(&self.xswd_applications, id.as_bytes())
+    }
+
+    // Retrieve all applications approved through XSWD
+    #[cfg(feature = "api_server")]
+    pub fn get_xswd_applications(&self) -> Result<Vec<TrustedApplication>> {
+        trace!("get xswd applications");
+        let mut applications = Vec::new();
+        for el in self.xswd_applications.iter().values() {
+            let value = el?;
+            let decrypted = self.cipher.decrypt_value(&value)?;
+            applications.push(TrustedApplication::from_bytes(&decrypted)?);
+        }
+
+        Ok(applications)
+    }
+
+    // Save the key signing the applications approved through XSWD
+    // It is shared by all the accounts and also available in watch-only mode
+    #[cfg(feature = "api_server")]
+    pub fn set_xswd_key(&mut self, key: &PrivateKey) -> Result<()> {
+        trace!("set xswd key");
+        self.save_to_disk(&self.extra, XSWD_KEY, &key.to_bytes())
+    }
+
+    // Retrieve the key signing the applications approved through XSWD
+    #[cfg(feature = "api_server")]
+    pub fn get_xswd_key(&self) -> Result<PrivateKey> {
+        trace!("get xswd key");
+        self.load_from_disk(&self.extra, XSWD_KEY)
+    }
+
+    // Check if the key signing the applications was already generated
+    #[cfg(feature = "api_server")]
+    pub fn has_xswd_key(&self) -> Result<bool> {
+        trace!("has xswd key");
+        self.contains_data(&self.extra, XSWD_KEY)
+    }
+
     // Retrieve the nonce used to create new transactions
     pub fn get_nonce(&self) -> Result<u64> {
         trace!("get nonce");
-        self.load_from_disk(&self.extra, NONCE_KEY)
+        self.load_from_disk(&self.account_extra, NONCE_KEY)
     }
 
     // Set the new nonce uised to create new transactions
     pub fn set_nonce(&mut self, nonce: u64) -> Result<()> {
         trace!("set nonce to {}", nonce);
-        self.save_to_disk(&self.extra, NONCE_KEY, &nonce.to_be_bytes())
+        self.save_to_disk(&self.account_extra, NONCE_KEY, &nonce.to_be_bytes())
+    }
+
+    // Save the report of the batch payout in progress
+    pub fn set_payout_report(&mut self, report: &PayoutReport) -> Result<()> {
+        trace!("set payout report");
+        self.save_to_disk(&self.account_extra, PAYOUT_REPORT_KEY, &report.to_bytes())
+    }
+
+    // Retrieve the report of the last batch payout
+    pub fn get_payout_report(&self) -> Result<PayoutReport> {
+        trace!("get payout report");
+        self.load_from_disk(&self.account_extra, PAYOUT_REPORT_KEY)
+    }
+
+    pub fn has_payout_report(&self) -> Result<bool> {
+        trace!("has payout report");
+        self.contains_data(&self.account_extra, PAYOUT_REPORT_KEY)
     }
 
     // Store the private key
@@ -716,11 +1066,49 @@ impl EncryptedStorage {
         self.load_from_disk(&self.extra, PRIVATE_KEY)
     }
 
+    // Check if the private key is stored
+    // A wallet without private key is a watch-only wallet
+    pub fn has_private_key(&self) -> Result<bool> {
+        trace!("has private key");
+        self.contains_data(&self.extra, PRIVATE_KEY)
+    }
+
+    // Store the private key encoded in the seed
+    // Only set when the wallet key is derived from it using a passphrase
+    pub fn set_seed_key(&mut self, seed_key: &PrivateKey) -> Result<()> {
+        trace!("set seed key");
+        self.save_to_disk(&self.extra, SEED_KEY, &seed_key.to_bytes())
+    }
+
+    // Retrieve the private key encoded in the seed
+    pub fn get_seed_key(&self) -> Result<PrivateKey> {
+        trace!("get seed key");
+        self.load_from_disk(&self.extra, SEED_KEY)
+    }
+
+    // Check if the wallet key is protected by a passphrase
+    pub fn has_seed_key(&self) -> Result<bool> {
+        trace!("has seed key");
+        self.contains_data(&self.extra, SEED_KEY)
+    }
+
+    // Store the public key watched by this wallet
+    pub fn set_watch_only_key(&mut self, key: &PublicKey) -> Result<()> {
+        trace!("set watch only key");
+        self.save_to_disk(&self.extra, WATCH_ONLY_KEY, &key.to_bytes())
+    }
+
+    // Retrieve the public key watched by this wallet
+    pub fn get_watch_only_key(&self) -> Result<PublicKey> {
+        trace!("get watch only key");
+        self.load_from_disk(&self.extra, WATCH_ONLY_KEY)
+    }
+
     // Set the topoheight until which the wallet is synchronized
     pub fn set_synced_topoheight(&mut self, topoheight: u64) -> Result<()> {
         trace!("set synced topoheight to {}", topoheight);
         self.synced_topoheight = Some(topoheight);
-        self.save_to_disk(&self.extra, TOPOHEIGHT_KEY, &topoheight.to_be_bytes())
+        self.save_to_disk(&self.account_extra, TOPOHEIGHT_KEY, &topoheight.to_be_bytes())
     }
 
     // Get the topoheight until which the wallet is synchronized
@@ -732,32 +1120,32 @@ impl EncryptedStorage {
             return Ok(topoheight);
         }
 
-        let synced_topoheight = self.load_from_disk(&self.extra, TOPOHEIGHT_KEY)?;
+        let synced_topoheight = self.load_from_disk(&self.account_extra, TOPOHEIGHT_KEY)?;
         Ok(synced_topoheight)
     }
 
     // Delete the top block hash
     pub fn delete_top_block_hash(&mut self) -> Result<()> {
         trace!("delete top block hash");
-        self.delete_from_disk(&self.extra, TOP_BLOCK_HASH_KEY)
+        self.delete_from_disk(&self.account_extra, TOP_BLOCK_HASH_KEY)
     }
 
     // Set the top block hash until which the wallet is synchronized
     pub fn set_top_block_hash(&mut self, hash: &Hash) -> Result<()> {
         trace!("set top block hash to {}", hash);
-        self.save_to_disk(&self.extra, TOP_BLOCK_HASH_KEY, hash.as_bytes())
+        self.save_to_disk(&self.account_extra, TOP_BLOCK_HASH_KEY, hash.as_bytes())
     }
 
     // Check if a top block hash is set 
     pub fn has_top_block_hash(&self) -> Result<bool> {
         trace!("has top block hash");
-        self.contains_data(&self.extra, TOP_BLOCK_HASH_KEY)
+        self.contains_data(&self.account_extra, TOP_BLOCK_HASH_KEY)
     }
 
     // Top block hash until which the wallet is synchronized 
     pub fn get_top_block_hash(&self) -> Result<Hash> {
         trace!("get top block hash");
-        self.load_from_disk(&self.extra, TOP_BLOCK_HASH_KEY)
+        self.load_from_disk(&self.account_extra, TOP_BLOCK_HASH_KEY)
     }
 
     pub fn get_public_storage(&self) -> &Storage {
@@ -862,14 +1250,6 @@ impl Storage {
         })
     }
 
-    // save the encrypted form of the master key
-    // it can only be decrypted using the password-based key
-    pub fn set_encrypted_master_key(&mut self, encrypted_key: &[u8]) -> Result<()> {
-        trace!("set encrypted master key");
-        self.db.insert(MASTER_KEY, encrypted_key)?;
-        Ok(())
-    }
-
     // retrieve the encrypted form of the master key
     pub fn get_encrypted_master_key(&self) -> Result<Vec<u8>> {
         trace!("get encrypted master key");
@@ -883,13 +1263,6 @@ impl Storage {
         }
     }
 
-    // set password salt used to derive the password-based key
-    pub fn set_password_salt(&mut self, salt: &[u8]) -> Result<()> {
-        trace!("set password salt");
-        self.db.insert(PASSWORD_SALT_KEY, salt)?;
-        Ok(())
-    }
-
     // retrieve password salt used to derive the password-based key
     pub fn get_password_salt(&self) -> Result<[u8; SALT_SIZE]> {
         trace!("get password salt");
@@ -910,6 +1283,16 @@ impl Storage {
         Ok(salt)
     }
 
+    // retrieve the KDF parameters used to derive the password-based key
+    // Wallets created before they were stored use the default parameters
+    pub fn get_kdf_params(&self) -> Result<KdfParams> {
+        trace!("get kdf params");
+        match self.db.get(KDF_PARAMS_KEY)? {
+            Some(value) => Ok(KdfParams::from_bytes(&value)?),
+            None => Ok(KdfParams::default())
+        }
+    }
+
     // get the salt used for encrypted storage
     pub fn get_encrypted_storage_salt(&self) -> Result<Vec<u8>> {
         trace!("get encrypted storage salt");
@@ -920,10 +1303,53 @@ impl Storage {
         Ok(encrypted_salt)
     }
 
-    // set the salt used for encrypted storage
-    pub fn set_encrypted_storage_salt(&mut self, salt: &[u8]) -> Result<()> {
-        trace!("set encrypted storage salt");
-        self.db.insert(SALT_KEY, salt)?;
+    // Save the password salt, the KDF parameters, the encrypted master key and the encrypted storage salt at once
+    // The master key can only be decrypted using the password-based key
+    // They are applied in a single batch so the wallet can't be left with a key encrypted using other parameters
+    pub fn set_password_keys(&mut self, salt: &[u8], params: &KdfParams, encrypted_key: &[u8], encrypted_storage_salt: &[u8]) -> Result<()> {
+        trace!("set password keys");
+        let mut batch = Batch::default();
+        batch.insert(PASSWORD_SALT_KEY, salt);
+        batch.insert(KDF_PARAMS_KEY, params.to_bytes());
+        batch.insert(MASTER_KEY, encrypted_key);
+        batch.insert(SALT_KEY, encrypted_storage_salt);
+        self.db.apply_batch(batch)?;
+        Ok(())
+    }
+
+    // Dump all the trees of the database for a backup
+    // Data is kept in its encrypted form
+    pub fn export_trees(&self) -> Result<Vec<BackupTree>> {
+        trace!("export trees");
+        let mut trees = Vec::new();
+        for name in self.db.tree_names() {
+            let tree = self.db.open_tree(&name)?;
+            let mut entries = Vec::new();
+            for el in tree.iter() {
+                let (key, value) = el?;
+                entries.push((key.to_vec(), value.to_vec()));
+            }
+
+            trees.push(BackupTree {
+                name: name.to_vec(),
+                entries
+            });
+        }
+
+        Ok(trees)
+    }
+
+    // Restore all the trees from a backup
+    pub fn import_trees(&mut self, trees: Vec<BackupTree>) -> Result<()> {
+        trace!("import trees");
+        for backup in trees {
+            let tree = self.db.open_tree(&backup.name)?;
+            for (key, value) in backup.entries {
+                tree.insert(key, value)?;
+            }
+        }
+
+        self.db.flush()?;
         Ok(())
     }
 }
\ No newline at end of file
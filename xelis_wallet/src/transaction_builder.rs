@@ -2,7 +2,11 @@ use std::collections::{HashMap, HashSet};
 use xelis_common::{
     account::CiphertextCache,
     crypto::{elgamal::Ciphertext, Hash, PublicKey},
-    transaction::{builder::{AccountState, FeeHelper}, Reference}
+    transaction::{
+        builder::{AccountState, FeeHelper},
+        payment_proof::PaymentProof,
+        Reference
+    }
 };
 use crate::{storage::{Balance, EncryptedStorage}, wallet::WalletError};
 
@@ -46,6 +50,8 @@ pub struct TransactionBuilderState {
     balances: HashMap<Hash, Balance>,
     reference: Reference,
     nonce: u64,
+    // Payment proofs of the transfers built, saved with the TX hash
+    payment_proofs: Option<(Hash, Vec<PaymentProof>)>
 }
 
 impl TransactionBuilderState {
@@ -57,7 +63,8 @@ impl TransactionBuilderState {
             mainnet,
             balances: HashMap::new(),
             reference,
-            nonce
+            nonce,
+            payment_proofs: None
         }
     }
 
@@ -77,12 +84,20 @@ impl TransactionBuilderState {
         self.inner.registered_keys.insert(key);
     }
 
-    pub async fn apply_changes(&mut self, storage: &mut EncryptedStorage) -> Result<(), WalletError> {
+    pub fn set_payment_proofs(&mut self, hash: Hash, proofs: Vec<PaymentProof>) {
+        self.payment_proofs = Some((hash, proofs));
+    }
+
+    pub async fn apply_changes(&mut self, storage: &mut EncryptedStorage, tx_hash: &Hash) -> Result<(), WalletError> {
         for (asset, balance) in self.balances.drain() {
-            storage.set_unconfirmed_balance_for(asset, balance).await?;
+            storage.set_unconfirmed_balance_for(asset, tx_hash.clone(), balance).await?;
         }
         storage.set_nonce(self.nonce)?;
 
+        if let Some((hash, proofs)) = self.payment_proofs.take() {
+            storage.set_payment_proofs(&hash, &proofs)?;
+        }
+
         Ok(())
     }
 }
@@ -1,8 +1,8 @@
 use std::{
-    fs::{create_dir_all, File},
+    fs::{create_dir_all, remove_dir_all, File},
     io::{Read, Write},
     path::Path,
-    sync::Arc
+    sync::{Arc, PoisonError, RwLock as StdRwLock}
 };
 use anyhow::{Error, Context};
 use serde::Serialize;
@@ -18,6 +18,10 @@ use xelis_common::{
     api::{
         wallet::{
             BalanceChanged,
+            GetBalanceHistoryResult,
+            InboxEntry,
+            InvoiceEntry,
+            MessageEntry,
             NotifyEvent,
             TransactionEntry
         },
@@ -29,12 +33,14 @@ use xelis_common::{
         elgamal::{Ciphertext, DecryptHandle, PublicKey as DecompressedPublicKey},
         Address,
         Hash,
+        Hashable,
         KeyPair,
         PublicKey,
         Signature
     },
     network::Network,
     serializer::Serializer,
+    time::get_current_time_in_seconds,
     transaction::{
         aead::{self, AEADCipher},
         builder::{
@@ -42,6 +48,7 @@ use xelis_common::{
             TransactionBuilder,
             TransactionTypeBuilder
         },
+        payment_proof::{PaymentProof, PaymentProofError},
         Reference,
         Transaction
     },
@@ -51,20 +58,29 @@ use xelis_common::{
     }
 };
 use crate::{
-    cipher::Cipher,
-    config::{
-        PASSWORD_ALGORITHM,
-        PASSWORD_HASH_SIZE,
-        SALT_SIZE
+    address_book::{
+        Contact,
+        MAX_LABEL_SIZE,
+        MAX_NOTES_SIZE
     },
+    backup,
+    balance_history,
+    cipher::Cipher,
+    config::SALT_SIZE,
     daemon_api::DaemonAPI,
+    invoice::{Invoice, MAX_DESCRIPTION_SIZE},
+    kdf::KdfParams,
+    message::{self, Message, MAX_MESSAGE_SIZE},
     mnemonics,
+    offline::UnsignedTransactionRequest,
+    pending::PendingTransaction,
     network_handler::{
         NetworkError,
         NetworkHandler,
         SharedNetworkHandler
     },
     storage::{
+        Balance,
         EncryptedStorage,
         Storage
     },
@@ -85,9 +101,25 @@ use log::{
 
 #[cfg(feature = "api_server")]
 use {
+    std::collections::{HashMap, HashSet},
+    serde::{de::DeserializeOwned, Deserialize},
     serde_json::{json, Value},
     async_trait::async_trait,
+    xelis_common::{
+        api::wallet::{
+            BatchPayoutParams,
+            BuildTransactionParams,
+            PayoutEntry,
+            PayoutStatus,
+            SendMessageParams,
+            TransactionResponse
+        },
+        config::XELIS_ASSET,
+        transaction::MAX_TRANSFER_COUNT
+    },
     crate::api::{
+        ApplicationPolicy,
+        PolicyError,
         XSWDNodeMethodHandler,
         register_rpc_methods,
         XSWD,
@@ -95,8 +127,10 @@ use {
         AuthConfig,
         APIServer,
         AppStateShared,
+        Permission,
         PermissionResult,
         PermissionRequest,
+        TrustedApplication,
         XSWDPermissionHandler
     },
     xelis_common::rpc_server::{
@@ -180,6 +214,54 @@ pub enum WalletError {
     CiphertextDecode,
     #[error(transparent)]
     AEADCipherFormatError(#[from] aead::CipherFormatError),
+    #[error("Unsigned request was created for another account")]
+    InvalidRequestSource,
+    #[error("Unsigned request was created for network {} but wallet is on {}", _0, _1)]
+    InvalidRequestNetwork(Network, Network),
+    #[error("Signed transaction doesn't match the unsigned request: invalid {}", _0)]
+    SignedTransactionMismatch(&'static str),
+    #[error("Account {} was not found", _0)]
+    AccountNotFound(u32),
+    #[error("No payment proof found for transfer {} of transaction {}", _1, _0)]
+    PaymentProofNotFound(Hash, u8),
+    #[error(transparent)]
+    PaymentProof(#[from] PaymentProofError),
+    #[error("Transaction {} is not executed in a block", _0)]
+    TransactionNotExecuted(Hash),
+    #[error("This operation requires the private key, wallet is in watch-only mode")]
+    WatchOnly,
+    #[error("Invalid backup file")]
+    InvalidBackup,
+    #[error("Unsupported backup version {}", _0)]
+    UnsupportedBackupVersion(u8),
+    #[error("Backup was created for network {} but wallet is on {}", _0, _1)]
+    InvalidBackupNetwork(Network, Network),
+    #[error("Wallet {} already exists", _0)]
+    WalletAlreadyExists(String),
+    #[error("Invalid contact label: must be between 1 and {} bytes without whitespace", MAX_LABEL_SIZE)]
+    InvalidContactLabel,
+    #[error("Contact notes are too long, maximum is {} bytes", MAX_NOTES_SIZE)]
+    ContactNotesTooLong,
+    #[error("Contact '{}' was not found", _0)]
+    ContactNotFound(String),
+    #[error("Contact '{}' already exists", _0)]
+    ContactAlreadyExists(String),
+    #[error("Contact address is not for the network of this wallet")]
+    InvalidContactNetwork,
+    #[error("XSWD application {} was not found", _0)]
+    XSWDApplicationNotFound(String),
+    #[error("Invoice amount must be greater than zero")]
+    InvalidInvoiceAmount,
+    #[error("Invoice description is too long, maximum is {} bytes", MAX_DESCRIPTION_SIZE)]
+    InvoiceDescriptionTooLong,
+    #[error("Invoice {} was not found", _0)]
+    InvoiceNotFound(Hash),
+    #[error("Message cannot be empty")]
+    EmptyMessage,
+    #[error("Message is too long, maximum is {} bytes", MAX_MESSAGE_SIZE)]
+    MessageTooLong,
+    #[error("Message can't be sent to an integrated address")]
+    MessageWithIntegratedAddress,
 }
 
 #[derive(Serialize, Clone)]
@@ -205,6 +287,8 @@ pub enum Event {
     Rescan {
         start_topoheight: u64   
     },
+    // When an incoming transaction paid an invoice
+    InvoiceUpdated(InvoiceEntry),
     // Wallet is now in online mode
     Online,
     // Wallet is now in offline mode
@@ -219,6 +303,7 @@ impl Event {
             Event::BalanceChanged(_) => NotifyEvent::BalanceChanged,
             Event::NewAsset(_) => NotifyEvent::NewAsset,
             Event::Rescan { .. } => NotifyEvent::Rescan,
+            Event::InvoiceUpdated(_) => NotifyEvent::InvoiceUpdated,
             Event::Online => NotifyEvent::Online,
             Event::Offline => NotifyEvent::Offline
         }
@@ -278,13 +363,65 @@ impl PrecomputedTables {
 
 pub const PRECOMPUTED_TABLES_L1: usize = 26;
 
+// Account derived from the wallet seed
+pub struct Account {
+    // Index used to derive the account
+    index: u32,
+    // Private & Public key of this account
+    // None in watch-only mode
+    keypair: Option<KeyPair>,
+    // Compressed public key
+    public_key: PublicKey
+}
+
+impl Account {
+    // Derive the account at the requested index from the seed keypair
+    // Account 0 is using directly the keypair of the seed
+    fn derive(seed: &KeyPair, index: u32) -> Self {
+        let keypair = if index == 0 {
+            seed.clone()
+        } else {
+            KeyPair::from_private_key(seed.get_private_key().derive_child(index))
+        };
+
+        Self {
+            index,
+            public_key: keypair.get_public_key().compress(),
+            keypair: Some(keypair)
+        }
+    }
+
+    // Account watched using only its public key
+    fn watch_only(public_key: PublicKey) -> Self {
+        Self {
+            index: 0,
+            keypair: None,
+            public_key
+        }
+    }
+
+    pub fn get_index(&self) -> u32 {
+        self.index
+    }
+
+    // Keypair of this account, unavailable in watch-only mode
+    pub fn get_keypair(&self) -> Result<&KeyPair, WalletError> {
+        self.keypair.as_ref().ok_or(WalletError::WatchOnly)
+    }
+
+    pub fn get_public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+}
+
 pub struct Wallet {
     // Encrypted Wallet Storage
     storage: RwLock<EncryptedStorage>,
-    // Private & Public key linked for this wallet
-    keypair: KeyPair,
-    // Compressed public key
-    public_key: PublicKey,
+    // Private & Public key linked to the seed of this wallet
+    // None if the wallet is watch-only
+    keypair: Option<KeyPair>,
+    // Account currently selected
+    account: StdRwLock<Arc<Account>>,
     // network handler for online mode to keep wallet synced
     network_handler: Mutex<Option<SharedNetworkHandler>>,
     // network on which we are connected
@@ -301,12 +438,6 @@ pub struct Wallet {
     precomputed_tables: PrecomputedTablesShared
 }
 
-pub fn hash_password(password: String, salt: &[u8]) -> Result<[u8; PASSWORD_HASH_SIZE], WalletError> {
-    let mut output = [0; PASSWORD_HASH_SIZE];
-    PASSWORD_ALGORITHM.hash_password_into(password.as_bytes(), salt, &mut output).map_err(|e| WalletError::AlgorithmHashingError(e.to_string()))?;
-    Ok(output)
-}
-
 impl Wallet {
     // This will read from file if exists, or generate and store it in file
     // This must be call only one time, and can be cloned to be shared through differents wallets
@@ -337,11 +468,11 @@ impl Wallet {
     }
 
     // Create a new wallet with the specificed storage, keypair and its network
-    fn new(storage: EncryptedStorage, keypair: KeyPair, network: Network, precomputed_tables: PrecomputedTablesShared) -> Arc<Self> {
+    fn new(storage: EncryptedStorage, keypair: Option<KeyPair>, account: Account, network: Network, precomputed_tables: PrecomputedTablesShared) -> Arc<Self> {
         let zelf = Self {
             storage: RwLock::new(storage),
-            public_key: keypair.get_public_key().compress(),
             keypair,
+            account: StdRwLock::new(Arc::new(account)),
             network_handler: Mutex::new(None),
             network,
             #[cfg(feature = "api_server")]
@@ -356,29 +487,74 @@ impl Wallet {
     }
 
     // Create a new wallet on disk
-    pub fn create(name: String, password: String, seed: Option<String>, network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
+    // When a passphrase is set, the wallet key is derived from the seed key and the passphrase
+    pub fn create(name: String, password: String, seed: Option<String>, passphrase: Option<String>, kdf_params: KdfParams, network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
         if name.is_empty() {
             return Err(WalletError::EmptyName.into())
         }
 
-        // generate random keypair or recover it from seed
-        let keypair = if let Some(seed) = seed {
-        debug!("Retrieving keypair from seed...");
-        let words: Vec<String> = seed.split_whitespace().map(str::to_string).collect();
-        let key = mnemonics::words_to_key(&words)?;
-            KeyPair::from_private_key(key)
+        // generate random key or recover it from seed
+        let seed_key = if let Some(seed) = seed {
+            debug!("Retrieving keypair from seed...");
+            let words: Vec<String> = seed.split_whitespace().map(str::to_string).collect();
+            mnemonics::words_to_key(&words)?
         } else {
             debug!("Generating a new keypair...");
-            KeyPair::new()
+            KeyPair::new().get_private_key().clone()
+        };
+
+        // An empty passphrase is the same as no passphrase
+        let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+        let keypair = match passphrase.as_ref() {
+            Some(passphrase) => {
+                debug!("Deriving keypair using passphrase...");
+                KeyPair::from_private_key(mnemonics::apply_passphrase(&seed_key, passphrase)?)
+            },
+            None => KeyPair::from_private_key(seed_key.clone())
         };
 
+        let mut storage = Self::create_storage(name, password, kdf_params, network)?;
+
+        // Store the private key
+        storage.set_private_key(&keypair.get_private_key())?;
+        // Keep the seed key to be able to display the seed
+        if passphrase.is_some() {
+            storage.set_seed_key(&seed_key)?;
+        }
+
+        let account = Account::derive(&keypair, 0);
+        Ok(Self::new(storage, Some(keypair), account, network, precomputed_tables))
+    }
+
+    // Create a new watch-only wallet on disk from the address to watch
+    // It can sync the account but can't decrypt amounts or sign transactions
+    pub fn create_watch_only(name: String, password: String, address: Address, kdf_params: KdfParams, network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
+        if name.is_empty() {
+            return Err(WalletError::EmptyName.into())
+        }
+
+        if address.is_mainnet() != network.is_mainnet() {
+            return Err(WalletError::InvalidAddressParams.into())
+        }
+
+        let mut storage = Self::create_storage(name, password, kdf_params, network)?;
+
+        // Store the public key to watch
+        let public_key = address.to_public_key();
+        storage.set_watch_only_key(&public_key)?;
+
+        Ok(Self::new(storage, None, Account::watch_only(public_key), network, precomputed_tables))
+    }
+
+    // Create the encrypted storage of a new wallet on disk
+    fn create_storage(name: String, password: String, kdf_params: KdfParams, network: Network) -> Result<EncryptedStorage, Error> {
         // generate random salt for hashed password
         let mut salt: [u8; SALT_SIZE] = [0; SALT_SIZE];
         OsRng.fill_bytes(&mut salt);
 
         // generate hashed password which will be used as key to encrypt master_key
-        debug!("hashing provided password");
-        let hashed_password = hash_password(password, &salt)?;
+        debug!("hashing provided password using {}", kdf_params);
+        let hashed_password = kdf_params.hash_password(password, &salt)?;
 
         debug!("Creating storage for {}", name);
         let mut inner = Storage::new(name)?;
@@ -386,30 +562,24 @@ impl Wallet {
         // generate the Cipher
         let cipher = Cipher::new(&hashed_password, None)?;
 
-        // save the salt used for password
-        debug!("Save password salt in public storage");
-        inner.set_password_salt(&salt)?;
-
-        // generate the master key which is used for storage and then save it in encrypted form
+        // generate the master key which is used for storage and then encrypt it
         let mut master_key: [u8; 32] = [0; 32];
         OsRng.fill_bytes(&mut master_key);
         let encrypted_master_key = cipher.encrypt_value(&master_key)?;
-        debug!("Save encrypted master key in public storage");
-        inner.set_encrypted_master_key(&encrypted_master_key)?;
         
-        // generate the storage salt and save it in encrypted form
+        // generate the storage salt and encrypt it
         let mut storage_salt = [0; SALT_SIZE];
         OsRng.fill_bytes(&mut storage_salt);
         let encrypted_storage_salt = cipher.encrypt_value(&storage_salt)?;
-        inner.set_encrypted_storage_salt(&encrypted_storage_salt)?;
 
-        debug!("Creating encrypted storage");
-        let mut storage = EncryptedStorage::new(inner, &master_key, storage_salt, network)?;
+        // save the password salt, the KDF parameters and the encrypted keys in public storage
+        debug!("Save password salt and encrypted master key in public storage");
+        inner.set_password_keys(&salt, &kdf_params, &encrypted_master_key, &encrypted_storage_salt)?;
 
-        // Store the private key
-        storage.set_private_key(&keypair.get_private_key())?;
+        debug!("Creating encrypted storage");
+        let storage = EncryptedStorage::new(inner, &master_key, storage_salt, network)?;
 
-        Ok(Self::new(storage, keypair, network, precomputed_tables))
+        Ok(storage)
     }
 
     // Open an existing wallet on disk
@@ -429,7 +599,8 @@ impl Wallet {
         debug!("Retrieving encrypted master key from public storage");
         let encrypted_master_key = storage.get_encrypted_master_key()?;
 
-        let hashed_password = hash_password(password, &salt)?;
+        let kdf_params = storage.get_kdf_params()?;
+        let hashed_password = kdf_params.hash_password(password, &salt)?;
 
         // decrypt the encrypted master key using the hashed password (used as key)
         let cipher = Cipher::new(&hashed_password, None)?;
@@ -448,11 +619,51 @@ impl Wallet {
 
         debug!("Creating encrypted storage");
         let storage = EncryptedStorage::new(storage, &master_key, salt, network)?;
+        if !storage.has_private_key()? {
+            debug!("Retrieving watched public key from encrypted storage");
+            let public_key = storage.get_watch_only_key()?;
+            return Ok(Self::new(storage, None, Account::watch_only(public_key), network, precomputed_tables))
+        }
+
         debug!("Retrieving private key from encrypted storage");
         let private_key =  storage.get_private_key()?;
         let keypair = KeyPair::from_private_key(private_key);
+        let account = Account::derive(&keypair, storage.get_account());
 
-        Ok(Self::new(storage, keypair, network, precomputed_tables))
+        Ok(Self::new(storage, Some(keypair), account, network, precomputed_tables))
+    }
+
+    // Restore a wallet from a backup file in a new directory
+    // Backup is encrypted with the wallet password used during its export,
+    // the same password is then used to open the restored wallet
+    pub fn import_backup(name: String, password: String, content: &[u8], network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
+        if name.is_empty() {
+            return Err(WalletError::EmptyName.into())
+        }
+
+        let backup = backup::read_backup(content, password.clone())?;
+        if backup.network != network {
+            return Err(WalletError::InvalidBackupNetwork(backup.network, network).into())
+        }
+
+        // Restored directory is deleted on failure, it must not be an existing wallet
+        if Path::new(&name).exists() {
+            return Err(WalletError::WalletAlreadyExists(name).into())
+        }
+
+        debug!("Restoring backup in {}", name);
+        let res = Storage::new(name.clone())
+            .and_then(|mut storage| storage.import_trees(backup.trees))
+            .and_then(|_| Self::open(name.clone(), password, network, precomputed_tables));
+
+        if res.is_err() {
+            debug!("Deleting restored wallet {}", name);
+            if let Err(e) = remove_dir_all(&name) {
+                error!("Error while deleting restored wallet {}: {}", name, e);
+            }
+        }
+
+        res
     }
 
     // Close the wallet
@@ -590,6 +801,54 @@ impl Wallet {
         Ok(())
     }
 
+    // Revoke an application approved through XSWD
+    // It is disconnected if it is currently connected
+    #[cfg(feature = "api_server")]
+    pub async fn revoke_xswd_application(&self, id: &String) -> Result<(), WalletError> {
+        trace!("revoke xswd application {}", id);
+        {
+            let mut storage = self.storage.write().await;
+            if !storage.has_xswd_application(id)? {
+                return Err(WalletError::XSWDApplicationNotFound(id.clone()))
+            }
+            storage.delete_xswd_application(id)?;
+        }
+
+        let lock = self.api_server.lock().await;
+        if let Some(APIServer::XSWD(xswd)) = lock.as_ref() {
+            let session = {
+                let applications = xswd.get_handler().get_applications().read().await;
+                applications.iter()
+                    .find(|(_, app)| app.get_id() == id)
+                    .map(|(session, _)| session.clone())
+            };
+
+            if let Some(session) = session {
+                debug!("Disconnecting revoked application {}", id);
+                session.get_server().delete_session(&session, None).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Set the policy enforced on the requests of an application approved through XSWD
+    // It is applied on the next request, even if the application is already connected
+    #[cfg(feature = "api_server")]
+    pub async fn set_xswd_policy(&self, id: &String, policy: ApplicationPolicy) -> Result<(), WalletError> {
+        trace!("set xswd policy {}", id);
+        let mut storage = self.storage.write().await;
+        if !storage.has_xswd_application(id)? {
+            return Err(WalletError::XSWDApplicationNotFound(id.clone()))
+        }
+
+        let mut application = storage.get_xswd_application(id)?;
+        application.set_policy(policy);
+        storage.set_xswd_application(&application)?;
+
+        Ok(())
+    }
+
     #[cfg(feature = "api_server")]
     pub fn get_api_server<'a>(&'a self) -> &Mutex<Option<APIServer<Arc<Self>>>> {
         &self.api_server
@@ -600,21 +859,40 @@ impl Wallet {
         let mut encrypted_storage = self.storage.write().await;
         let storage = encrypted_storage.get_mutable_public_storage();
         let salt = storage.get_password_salt()?;
-        let hashed_password = hash_password(password, &salt)?;
+        let hashed_password = storage.get_kdf_params()?.hash_password(password, &salt)?;
         let cipher = Cipher::new(&hashed_password, None)?;
         let encrypted_master_key = storage.get_encrypted_master_key()?;
         let _ = cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided")?;
         Ok(())
     }
 
+    // Export the whole wallet storage in a single file encrypted with the wallet password
+    // Restoring it doesn't require to rescan the chain
+    pub async fn export_backup(&self, password: String) -> Result<Vec<u8>, Error> {
+        trace!("export backup");
+        self.is_valid_password(password.clone()).await?;
+        let storage = self.storage.read().await;
+        let public_storage = storage.get_public_storage();
+        let kdf_params = public_storage.get_kdf_params()?;
+        backup::create_backup(public_storage, self.network, password, &kdf_params)
+    }
+
+    // KDF parameters used to derive the password-based key
+    pub async fn get_kdf_params(&self) -> Result<KdfParams, Error> {
+        let storage = self.storage.read().await;
+        storage.get_public_storage().get_kdf_params()
+    }
+
     // change the current password wallet to a new one
-    pub async fn set_password(&self, old_password: String, password: String) -> Result<(), Error> {
+    // KDF parameters can be upgraded at the same time, current ones are kept if None
+    pub async fn set_password(&self, old_password: String, password: String, kdf_params: Option<KdfParams>) -> Result<(), Error> {
         let mut encrypted_storage = self.storage.write().await;
         let storage = encrypted_storage.get_mutable_public_storage();
+        let current_kdf_params = storage.get_kdf_params()?;
         let (master_key, storage_salt) = {
             // retrieve old salt to build key from current password
             let salt = storage.get_password_salt()?;
-            let hashed_password = hash_password(old_password, &salt)?;
+            let hashed_password = current_kdf_params.hash_password(old_password, &salt)?;
 
             let encrypted_master_key = storage.get_encrypted_master_key()?;
             let encrypted_storage_salt = storage.get_encrypted_storage_salt()?;
@@ -631,7 +909,8 @@ impl Wallet {
         OsRng.fill_bytes(&mut salt);
 
         // generate the password-based derivated key to encrypt the master key
-        let hashed_password = hash_password(password, &salt)?;
+        let kdf_params = kdf_params.unwrap_or(current_kdf_params);
+        let hashed_password = kdf_params.hash_password(password, &salt)?;
         let cipher = Cipher::new(&hashed_password, None)?;
 
         // encrypt the master key using the new password
@@ -640,10 +919,8 @@ impl Wallet {
         // encrypt the salt with the new password
         let encrypted_storage_salt = cipher.encrypt_value(&storage_salt)?;
 
-        // save on disk
-        storage.set_password_salt(&salt)?;
-        storage.set_encrypted_master_key(&encrypted_key)?;
-        storage.set_encrypted_storage_salt(&encrypted_storage_salt)?;
+        // save on disk in a single batch
+        storage.set_password_keys(&salt, &kdf_params, &encrypted_key, &encrypted_storage_salt)?;
 
         Ok(())
     }
@@ -651,18 +928,30 @@ impl Wallet {
     // Wallet has to be under a Arc to be shared to the spawn_blocking function
     pub async fn decrypt_ciphertext(self: Arc<Self>, ciphertext: Ciphertext) -> Result<u64, WalletError> {
         trace!("decrypt ciphertext");
+        let account = self.get_account();
         tokio::task::spawn_blocking(move || {
             let view = ECDLPTablesFileView::<PRECOMPUTED_TABLES_L1>::from_bytes(self.precomputed_tables.get());
-            self.keypair.get_private_key()
+            account.get_keypair()?.get_private_key()
                 .decrypt(&view, &ciphertext)
                 .ok_or(WalletError::CiphertextDecode)
         }).await.context("Error while decrypting ciphertext")?
     }
 
+    // Decrypt the ciphertext if the private key is available
+    // Returns None in watch-only mode as the amount can't be decrypted
+    pub async fn try_decrypt_ciphertext(self: Arc<Self>, ciphertext: Ciphertext) -> Result<Option<u64>, WalletError> {
+        if self.is_watch_only() {
+            return Ok(None)
+        }
+
+        self.decrypt_ciphertext(ciphertext).await.map(Some)
+    }
+
     // Decrypt the extra data from a transfer
     pub fn decrypt_extra_data(&self, cipher: AEADCipher, handle: &DecryptHandle) -> Result<DataElement, WalletError> {
         trace!("decrypt extra data");
-        let key = aead::derive_aead_key_from_handle(&self.keypair.get_private_key(), handle);
+        let account = self.get_account();
+        let key = aead::derive_aead_key_from_handle(account.get_keypair()?.get_private_key(), handle);
         let plaintext = cipher.decrypt_in_place(&key)?;
         DataElement::from_bytes(&plaintext.0).map_err(|_| WalletError::CiphertextDecode)
     }
@@ -674,7 +963,7 @@ impl Wallet {
         let mut storage = self.storage.write().await;
         let (mut state, transaction) = self.create_transaction_with_storage(&mut storage, transaction_type, fee).await?;
 
-        state.apply_changes(&mut storage).await?;
+        state.apply_changes(&mut storage, &transaction.hash()).await?;
 
         Ok(transaction)
     }
@@ -729,36 +1018,282 @@ impl Wallet {
             }
         }
 
+        // Amounts of the transfers, required to create their payment proofs
+        let amounts: Vec<u64> = match &transaction_type {
+            TransactionTypeBuilder::Transfers(transfers) => transfers.iter().map(|t| t.amount).collect(),
+            _ => Vec::new()
+        };
+
         // Create the transaction builder
-        let builder = TransactionBuilder::new(0, self.public_key.clone(), transaction_type, fee);
+        let account = self.get_account();
+        let builder = TransactionBuilder::new(0, account.public_key.clone(), transaction_type, fee);
 
         // Build the final transaction
-        let transaction = builder.build(&mut state, &self.keypair)
+        let (transaction, openings) = builder.build_with_openings(&mut state, account.get_keypair()?)
             .map_err(|e| WalletError::Any(e.into()))?;
 
+        if !openings.is_empty() {
+            let hash = transaction.hash();
+            let proofs = amounts.into_iter()
+                .zip(openings)
+                .enumerate()
+                .map(|(i, (amount, opening))| PaymentProof::new(hash.clone(), i as u8, amount, opening))
+                .collect();
+            state.set_payment_proofs(hash, proofs);
+        }
+
         Ok((state, transaction))
     }
 
+    // Build and sign the transaction from an unsigned request exported by an online process
+    // Nothing is read from the storage, so it can be used in an air-gapped wallet
+    // Only the payment proofs of the transfers are saved, they must be retrieved from this wallet
+    // Wallet has to be under a Arc to decrypt the balances of the request
+    pub async fn sign_unsigned_request(self: &Arc<Self>, request: UnsignedTransactionRequest) -> Result<Transaction, WalletError> {
+        trace!("sign unsigned request");
+        if request.network != self.network {
+            return Err(WalletError::InvalidRequestNetwork(request.network, self.network))
+        }
+
+        let account = self.get_account();
+        if *request.source.get_public_key() != account.public_key {
+            return Err(WalletError::InvalidRequestSource)
+        }
+
+        let mut state = TransactionBuilderState::new(
+            self.network.is_mainnet(),
+            request.reference,
+            request.nonce
+        );
+
+        let mut balances = request.balances;
+        for asset in request.tx_type.used_assets() {
+            trace!("Decrypting balance for asset {}", asset);
+            let ciphertext = balances.remove(&asset)
+                .ok_or_else(|| WalletError::BalanceNotFound(asset.clone()))?;
+            let decompressed = ciphertext.clone()
+                .take_ciphertext()
+                .map_err(|e| WalletError::Any(e.into()))?;
+            let amount = Arc::clone(self).decrypt_ciphertext(decompressed).await?;
+            state.add_balance(asset, Balance::new(amount, ciphertext));
+        }
+
+        for key in request.registered_keys {
+            state.add_registered_key(key.to_public_key());
+        }
+
+        let amounts: Vec<u64> = match &request.tx_type {
+            TransactionTypeBuilder::Transfers(transfers) => transfers.iter().map(|t| t.amount).collect(),
+            _ => Vec::new()
+        };
+
+        let builder = TransactionBuilder::new(0, account.public_key.clone(), request.tx_type, request.fee);
+        let (transaction, openings) = builder.build_with_openings(&mut state, account.get_keypair()?)
+            .map_err(|e| WalletError::Any(e.into()))?;
+
+        if !openings.is_empty() {
+            let hash = transaction.hash();
+            let proofs = amounts.into_iter()
+                .zip(openings)
+                .enumerate()
+                .map(|(i, (amount, opening))| PaymentProof::new(hash.clone(), i as u8, amount, opening))
+                .collect();
+
+            let mut storage = self.storage.write().await;
+            storage.set_payment_proofs(&hash, &proofs)?;
+        }
+
+        Ok(transaction)
+    }
+
     // submit a transaction to the network through the connection to daemon
     // It will increase the local nonce by 1 if the TX is accepted by the daemon
+    // Once accepted, the TX is tracked as pending until it is included in a block
     // returns error if the wallet is in offline mode or if the TX is rejected
     pub async fn submit_transaction(&self, transaction: &Transaction) -> Result<(), WalletError> {
         trace!("submit transaction");
         let network_handler = self.network_handler.lock().await;
         if let Some(network_handler) = network_handler.as_ref() {
             network_handler.get_api().submit_transaction(transaction).await?;
+
+            // TX was accepted by the daemon, so don't report an error if it can't be tracked
+            let mut storage = self.storage.write().await;
+            if let Err(e) = storage.set_pending_transaction(&PendingTransaction::new(transaction.clone(), get_current_time_in_seconds())) {
+                error!("Error while saving pending transaction {}: {}", transaction.hash(), e);
+            }
             Ok(())
         } else {
             Err(WalletError::NotOnlineMode)
         }
     }
 
+    // Retrieve the payment proof of a transfer sent by this wallet
+    pub async fn get_payment_proof(&self, hash: &Hash, transfer_index: u8) -> Result<PaymentProof, WalletError> {
+        trace!("get payment proof for transfer {} of {}", transfer_index, hash);
+        let storage = self.storage.read().await;
+        if !storage.has_payment_proofs(hash)? {
+            return Err(WalletError::PaymentProofNotFound(hash.clone(), transfer_index))
+        }
+
+        storage.get_payment_proofs(hash)?
+            .into_iter()
+            .find(|proof| proof.transfer_index == transfer_index)
+            .ok_or_else(|| WalletError::PaymentProofNotFound(hash.clone(), transfer_index))
+    }
+
+    // Add a new contact in the address book
+    pub async fn add_contact(&self, contact: Contact) -> Result<(), WalletError> {
+        trace!("add contact {}", contact.get_label());
+        if contact.get_address().is_mainnet() != self.network.is_mainnet() {
+            return Err(WalletError::InvalidContactNetwork)
+        }
+
+        let mut storage = self.storage.write().await;
+        if storage.has_contact(contact.get_label())? {
+            return Err(WalletError::ContactAlreadyExists(contact.get_label().clone()))
+        }
+
+        storage.set_contact(&contact)?;
+        Ok(())
+    }
+
+    // Retrieve a contact from the address book using its label
+    pub async fn get_contact(&self, label: &str) -> Result<Contact, WalletError> {
+        trace!("get contact {}", label);
+        let storage = self.storage.read().await;
+        if !storage.has_contact(label)? {
+            return Err(WalletError::ContactNotFound(label.to_owned()))
+        }
+
+        Ok(storage.get_contact(label)?)
+    }
+
+    // Remove a contact from the address book
+    pub async fn remove_contact(&self, label: &str) -> Result<(), WalletError> {
+        trace!("remove contact {}", label);
+        let mut storage = self.storage.write().await;
+        if !storage.has_contact(label)? {
+            return Err(WalletError::ContactNotFound(label.to_owned()))
+        }
+
+        storage.delete_contact(label)?;
+        Ok(())
+    }
+
+    // Create an invoice for the selected account
+    // Payer must send the funds to the integrated address of the invoice
+    pub async fn create_invoice(&self, asset: Hash, amount: u64, description: Option<String>, expires_in: Option<u64>) -> Result<Invoice, WalletError> {
+        trace!("create invoice");
+        let now = get_current_time_in_seconds();
+        let expiry = expires_in.map(|delay| now.saturating_add(delay));
+        let invoice = Invoice::new(asset, amount, description, now, expiry)?;
+
+        let mut storage = self.storage.write().await;
+        storage.set_invoice(&invoice)?;
+        Ok(invoice)
+    }
+
+    // Retrieve an invoice of the selected account using its ID
+    pub async fn get_invoice(&self, id: &Hash) -> Result<Invoice, WalletError> {
+        trace!("get invoice {}", id);
+        let storage = self.storage.read().await;
+        if !storage.has_invoice(id)? {
+            return Err(WalletError::InvoiceNotFound(id.clone()))
+        }
+
+        Ok(storage.get_invoice(id)?)
+    }
+
+    // Retrieve all the invoices of the selected account
+    pub async fn get_invoices(&self) -> Result<Vec<Invoice>, WalletError> {
+        trace!("get invoices");
+        let storage = self.storage.read().await;
+        Ok(storage.get_invoices()?)
+    }
+
+    // Invoice with its integrated address and its current status
+    pub fn get_invoice_entry(&self, invoice: &Invoice) -> InvoiceEntry {
+        invoice.to_entry(&self.get_public_key(), self.network.is_mainnet(), get_current_time_in_seconds())
+    }
+
+    // Build a transfer carrying an encrypted message to the destination
+    // Amount can be zero to only send the message
+    pub async fn create_message_transaction(&self, destination: Address, message: &Message, asset: Hash, amount: u64, fee: FeeBuilder) -> Result<Transaction, WalletError> {
+        trace!("create message transaction");
+        if destination.is_mainnet() != self.network.is_mainnet() {
+            return Err(WalletError::InvalidAddressParams)
+        }
+
+        // Message is sent as extra data, which can't be combined with the data of an integrated address
+        if !destination.is_normal() {
+            return Err(WalletError::MessageWithIntegratedAddress)
+        }
+
+        self.create_transaction(TransactionTypeBuilder::Transfers(vec![message.to_transfer(destination, asset, amount)]), fee).await
+    }
+
+    // Retrieve the messages exchanged by the selected account, oldest first
+    pub async fn get_messages(&self, counterparty: Option<&PublicKey>, min_topoheight: Option<u64>, max_topoheight: Option<u64>, accept_incoming: bool, accept_outgoing: bool) -> Result<Vec<MessageEntry>, WalletError> {
+        trace!("get messages");
+        let storage = self.storage.read().await;
+        Ok(message::get_messages(&storage, counterparty, min_topoheight, max_topoheight, accept_incoming, accept_outgoing, self.network.is_mainnet())?)
+    }
+
+    // Retrieve the messages received grouped per sender
+    pub async fn get_inbox(&self) -> Result<Vec<InboxEntry>, WalletError> {
+        trace!("get inbox");
+        let storage = self.storage.read().await;
+        Ok(message::get_inbox(&storage, self.network.is_mainnet())?)
+    }
+
+    // Running balance per asset after each transaction, with the computed balances
+    // compared to the balances decrypted from the daemon
+    pub async fn get_balance_history(&self, asset: Option<&Hash>, min_topoheight: Option<u64>, max_topoheight: Option<u64>) -> Result<GetBalanceHistoryResult, WalletError> {
+        trace!("get balance history");
+        let storage = self.storage.read().await;
+        let (history, computed) = balance_history::get_balance_history(&storage, asset, min_topoheight, max_topoheight)?;
+        let reconciliation = balance_history::reconcile(&storage, computed, asset, self.is_watch_only()).await?;
+
+        Ok(GetBalanceHistoryResult {
+            history,
+            reconciliation
+        })
+    }
+
+    // Verify a payment proof against the transaction fetched from the daemon
+    // Transaction must be executed in a block, a transaction in mempool or orphaned didn't pay anything
+    // Returns the destination, asset and amount proven
+    pub async fn verify_payment_proof(&self, proof: &PaymentProof) -> Result<(Address, Hash, u64), WalletError> {
+        trace!("verify payment proof for {}", proof.tx_hash);
+        let network_handler = self.network_handler.lock().await;
+        let Some(network_handler) = network_handler.as_ref() else {
+            return Err(WalletError::NotOnlineMode)
+        };
+
+        let api = network_handler.get_api();
+        let response = api.get_transaction_with_status(&proof.tx_hash).await?;
+        let Some(block_hash) = response.executed_in_block else {
+            return Err(WalletError::TransactionNotExecuted(proof.tx_hash.clone()))
+        };
+
+        // Same check as the sync to be sure the block is still executing it
+        if !api.is_tx_executed_in_block(&proof.tx_hash, &block_hash).await? {
+            return Err(WalletError::TransactionNotExecuted(proof.tx_hash.clone()))
+        }
+
+        let tx = api.get_transaction(&proof.tx_hash).await?;
+        let transfer = proof.verify(&tx)?;
+        let destination = transfer.get_destination().as_address(self.network.is_mainnet());
+
+        Ok((destination, transfer.get_asset().clone(), proof.amount))
+    }
+
     // Estimate fees for a given transaction type
     // Estimated fees returned are the minimum required to be valid on chain
     pub async fn estimate_fees(&self, tx_type: TransactionTypeBuilder) -> Result<u64, WalletError> {
         trace!("estimate fees");
         let mut state = EstimateFeesState::new();
-        let builder = TransactionBuilder::new(0, self.public_key.clone(), tx_type, FeeBuilder::default());
+        let builder = TransactionBuilder::new(0, self.get_public_key(), tx_type, FeeBuilder::default());
         let estimated_fees = builder.estimate_fees(&mut state)
             .map_err(|e| WalletError::Any(e.into()))?;
 
@@ -767,6 +1302,12 @@ impl Wallet {
 
     // set wallet in online mode: start a communication task which will keep the wallet synced
     pub async fn set_online_mode(self: &Arc<Self>, daemon_address: &String) -> Result<(), WalletError> {
+        self.set_online_mode_with_failover(vec![daemon_address.clone()]).await
+    }
+
+    // set wallet in online mode using several daemons by order of preference
+    // the wallet switches to another daemon if the one used is unreachable, lagging or on another chain
+    pub async fn set_online_mode_with_failover(self: &Arc<Self>, daemon_addresses: Vec<String>) -> Result<(), WalletError> {
         trace!("Set online mode");
         if self.is_online().await {
             // user have to set in offline mode himself first
@@ -774,7 +1315,7 @@ impl Wallet {
         }
 
         // create the network handler
-        let network_handler = NetworkHandler::new(Arc::clone(&self), daemon_address).await?;
+        let network_handler = NetworkHandler::with_failover(Arc::clone(&self), daemon_addresses).await?;
         // start the task
         network_handler.start().await?;
         *self.network_handler.lock().await = Some(network_handler);
@@ -881,31 +1422,131 @@ impl Wallet {
     }
 
     // Create a signature of the given data
-    pub fn sign_data(&self, data: &[u8]) -> Signature {
-        self.keypair.sign(data)
+    pub fn sign_data(&self, data: &[u8]) -> Result<Signature, WalletError> {
+        Ok(self.get_account().get_keypair()?.sign(data))
     }
 
-    // Get the public key of the wallet
-    pub fn get_public_key(&self) -> &PublicKey {
-        &self.public_key
+    // Keypair signing the applications approved through XSWD
+    // It is generated on first use and doesn't depend on the account selected,
+    // so the signatures stay valid after switching account and in watch-only mode
+    #[cfg(feature = "api_server")]
+    async fn get_xswd_keypair(&self) -> Result<KeyPair, WalletError> {
+        let mut storage = self.storage.write().await;
+        if storage.has_xswd_key()? {
+            return Ok(KeyPair::from_private_key(storage.get_xswd_key()?))
+        }
+
+        let keypair = KeyPair::new();
+        storage.set_xswd_key(keypair.get_private_key())?;
+        Ok(keypair)
     }
 
-    // Get the address of the wallet using its network used
+    // Check if the wallet only watches an account without its private key
+    pub fn is_watch_only(&self) -> bool {
+        self.keypair.is_none()
+    }
+
+    // Keypair of the seed, unavailable in watch-only mode
+    fn get_seed_keypair(&self) -> Result<&KeyPair, WalletError> {
+        self.keypair.as_ref().ok_or(WalletError::WatchOnly)
+    }
+
+    // Get the public key of the selected account
+    pub fn get_public_key(&self) -> PublicKey {
+        self.get_account().public_key.clone()
+    }
+
+    // Get the address of the selected account using its network used
     pub fn get_address(&self) -> Address {
-        self.keypair.get_public_key().to_address(self.get_network().is_mainnet())
+        self.get_account().public_key.to_address(self.get_network().is_mainnet())
     }
 
     // Get the address with integrated data and using its network used
     pub fn get_address_with(&self, data: DataElement) -> Address {
-        self.keypair.get_public_key().to_address_with(self.get_network().is_mainnet(), data)
+        self.get_account().public_key.to_address_with(self.get_network().is_mainnet(), data)
+    }
+
+    // Get the account currently selected
+    pub fn get_account(&self) -> Arc<Account> {
+        self.account.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    // Create a new account derived from the seed
+    pub async fn create_account(&self) -> Result<Account, WalletError> {
+        trace!("create account");
+        let seed = self.get_seed_keypair()?;
+        let mut storage = self.storage.write().await;
+        let index = storage.get_accounts_count()?;
+        storage.set_accounts_count(index + 1)?;
+
+        Ok(Account::derive(seed, index))
+    }
+
+    // Get all the accounts created from the seed
+    pub async fn get_accounts(&self) -> Result<Vec<Account>, WalletError> {
+        trace!("get accounts");
+        let Some(seed) = self.keypair.as_ref() else {
+            return Ok(vec![Account::watch_only(self.get_public_key())])
+        };
+
+        let count = self.storage.read().await.get_accounts_count()?;
+        Ok((0..count).map(|index| Account::derive(seed, index)).collect())
+    }
+
+    // Switch to another account previously created
+    // Balances, transactions, nonce and sync state are stored per account
+    // If the wallet is online, the network handler is restarted to sync the new account
+    pub async fn switch_account(&self, index: u32) -> Result<(), WalletError> {
+        trace!("switch to account {}", index);
+        let seed = self.get_seed_keypair()?;
+        let mut storage = self.storage.write().await;
+        if index >= storage.get_accounts_count()? {
+            return Err(WalletError::AccountNotFound(index))
+        }
+
+        let handler = self.network_handler.lock().await;
+        let mut restart = false;
+        if let Some(network_handler) = handler.as_ref() {
+            if network_handler.is_running().await {
+                debug!("Stopping network handler!");
+                network_handler.stop().await?;
+                restart = true;
+            }
+        }
+
+        storage.switch_account(index)?;
+        *self.account.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(Account::derive(seed, index));
+
+        if restart {
+            if let Some(network_handler) = handler.as_ref() {
+                debug!("Starting again network handler");
+                network_handler.start().await.context("Error while restarting network handler")?;
+            }
+        }
+
+        Ok(())
     }
 
     // Returns the seed using the language index provided
-    pub fn get_seed(&self, language_index: usize) -> Result<String, Error> {
-        let words = mnemonics::key_to_words(self.keypair.get_private_key(), language_index)?;
+    // If the wallet is protected by a passphrase, the seed alone is not enough to recover it
+    pub async fn get_seed(&self, language_index: usize) -> Result<String, Error> {
+        let keypair = self.get_seed_keypair()?;
+        let storage = self.storage.read().await;
+        let words = if storage.has_seed_key()? {
+            mnemonics::key_to_words(&storage.get_seed_key()?, language_index)?
+        } else {
+            mnemonics::key_to_words(keypair.get_private_key(), language_index)?
+        };
+
         Ok(words.join(" "))
     }
 
+    // Check if the wallet key is derived from its seed using a passphrase
+    pub async fn has_passphrase(&self) -> Result<bool, Error> {
+        let storage = self.storage.read().await;
+        storage.has_seed_key()
+    }
+
     // Current account nonce for transactions
     // Nonce is used against replay attacks on-chain
     pub async fn get_nonce(&self) -> u64 {
@@ -926,7 +1567,8 @@ impl Wallet {
 
 #[cfg(feature = "api_server")]
 pub enum XSWDEvent {
-    RequestPermission(AppStateShared, RpcRequest, OneshotSender<Result<PermissionResult, Error>>),
+    // Policy of the application is given to be displayed with the request
+    RequestPermission(AppStateShared, RpcRequest, ApplicationPolicy, OneshotSender<Result<PermissionResult, Error>>),
     // bool represents if it was signed or not
     RequestApplication(AppStateShared, bool, OneshotSender<Result<PermissionResult, Error>>),
     CancelRequest(AppStateShared, OneshotSender<Result<(), Error>>)
@@ -943,7 +1585,12 @@ impl XSWDPermissionHandler for Arc<Wallet> {
             let (callback, receiver) = channel();
             let event = match request {
                 PermissionRequest::Application(signed) => XSWDEvent::RequestApplication(app_state, signed, callback),
-                PermissionRequest::Request(request) => XSWDEvent::RequestPermission(app_state, request.clone(), callback)
+                PermissionRequest::Request(request) => {
+                    let policy = self.get_trusted_application(app_state.get_id()).await?
+                        .map(|application| application.get_policy().clone())
+                        .unwrap_or_default();
+                    XSWDEvent::RequestPermission(app_state, request.clone(), policy, callback)
+                }
             };
 
             // Send the XSWD Message
@@ -971,9 +1618,212 @@ impl XSWDPermissionHandler for Arc<Wallet> {
         Err(WalletError::NoHandlerAvailable.into())
     }
 
-    async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error> {
-        Ok(self.keypair.get_public_key())
+    async fn get_public_key(&self) -> Result<DecompressedPublicKey, Error> {
+        Ok(self.get_xswd_keypair().await?.get_public_key().clone())
+    }
+
+    async fn sign_application(&self, manifest: &[u8]) -> Result<Signature, Error> {
+        Ok(self.get_xswd_keypair().await?.sign(manifest))
+    }
+
+    async fn get_trusted_application(&self, id: &String) -> Result<Option<TrustedApplication>, Error> {
+        let storage = self.storage.read().await;
+        if !storage.has_xswd_application(id)? {
+            return Ok(None)
+        }
+
+        Ok(Some(storage.get_xswd_application(id)?))
+    }
+
+    async fn save_trusted_application(&self, application: &TrustedApplication) -> Result<(), Error> {
+        let mut storage = self.storage.write().await;
+        storage.set_xswd_application(application)
+    }
+
+    async fn update_trusted_permissions(&self, app_state: &AppStateShared, permissions: &HashMap<String, Permission>) -> Result<(), Error> {
+        let mut storage = self.storage.write().await;
+        // Application may have been revoked in the meantime
+        if !storage.has_xswd_application(app_state.get_id())? {
+            return Ok(())
+        }
+
+        let mut application = storage.get_xswd_application(app_state.get_id())?;
+        application.get_mut_data().set_permissions(permissions.clone());
+        storage.set_xswd_application(&application)
     }
+
+    async fn verify_policy(&self, app_state: &AppStateShared, request: &RpcRequest) -> Result<bool, Error> {
+        let application = match self.get_trusted_application(app_state.get_id()).await? {
+            Some(application) => application,
+            None => return Err(WalletError::XSWDApplicationNotFound(app_state.get_id().clone()).into())
+        };
+
+        let policy = application.get_policy();
+        if policy.is_unrestricted() {
+            return Ok(false)
+        }
+
+        let mut destinations = Vec::new();
+        let mut amounts = HashMap::new();
+        let fee = match request.method.as_str() {
+            "build_transaction" => {
+                let params: BuildTransactionParams = parse_policy_params(request)?;
+                // A transaction not broadcasted by the wallet can't be counted in the spend limits
+                if !params.broadcast && !policy.spend_limits.is_empty() {
+                    return Err(PolicyError::BroadcastRequired.into())
+                }
+
+                add_transaction_amounts(&mut amounts, &mut destinations, &params.tx_type);
+                let fee = match params.fee.unwrap_or_default() {
+                    FeeBuilder::Value(value) => value,
+                    FeeBuilder::Multiplier(multiplier) => (self.estimate_fees(params.tx_type).await? as f64 * multiplier) as u64
+                };
+
+                // Fee is spent in XELIS
+                add_amount(&mut amounts, &XELIS_ASSET, fee);
+                Some(fee)
+            },
+            "send_message" => {
+                let params: SendMessageParams = parse_policy_params(request)?;
+                let asset = params.asset.unwrap_or(XELIS_ASSET);
+                destinations.push(params.destination.get_public_key().clone());
+                add_amount(&mut amounts, &asset, params.amount);
+
+                let fee = match params.fee.unwrap_or_default() {
+                    FeeBuilder::Value(value) => value,
+                    FeeBuilder::Multiplier(multiplier) => {
+                        let message = Message::new(params.message, params.reply_to)?;
+                        let tx_type = TransactionTypeBuilder::Transfers(vec![message.to_transfer(params.destination, asset, params.amount)]);
+                        (self.estimate_fees(tx_type).await? as f64 * multiplier) as u64
+                    }
+                };
+
+                add_amount(&mut amounts, &XELIS_ASSET, fee);
+                Some(fee)
+            },
+            "batch_payout" => {
+                let params: BatchPayoutParams = parse_policy_params(request)?;
+                // Entries already submitted or being broadcasted are skipped when resuming a payout
+                let mut count = 0;
+                for entry in params.entries.iter().filter(|entry| entry.status.can_be_sent()) {
+                    destinations.push(entry.destination.get_public_key().clone());
+                    add_amount(&mut amounts, &entry.asset, entry.amount);
+                    count += 1;
+                }
+
+                // Several transactions may be built, so only a fixed fee can be verified
+                match params.fee {
+                    Some(FeeBuilder::Value(value)) => {
+                        // At least one transaction is built per group of transfers
+                        let transactions = count.div_ceil(MAX_TRANSFER_COUNT) as u64;
+                        add_amount(&mut amounts, &XELIS_ASSET, value.saturating_mul(transactions));
+                        Some(value)
+                    },
+                    _ => None
+                }
+            },
+            "get_value_from_key" | "has_key" | "get_matching_keys" | "query_db" => {
+                let params: TreeParams = parse_policy_params(request)?;
+                policy.verify_tree_read(&params.tree)?;
+                return Ok(false)
+            },
+            "store" | "delete" => {
+                policy.verify_tree_write()?;
+                return Ok(false)
+            },
+            _ => return Ok(false)
+        };
+
+        let destinations: Vec<&PublicKey> = destinations.iter().collect();
+        policy.verify_spending(application.get_spendings(), &destinations, &amounts, fee, get_current_time_in_seconds())?;
+
+        Ok(true)
+    }
+
+    async fn record_spending(&self, app_state: &AppStateShared, request: &RpcRequest, response: &Value) -> Result<(), Error> {
+        // Only the transactions submitted are counted
+        let mut amounts = HashMap::new();
+        match request.method.as_str() {
+            "build_transaction" => {
+                let params: BuildTransactionParams = parse_policy_params(request)?;
+                if !params.broadcast {
+                    return Ok(())
+                }
+
+                let response: TransactionResponse = serde_json::from_value(response.clone()).context("Invalid transaction response")?;
+                add_transaction_amounts(&mut amounts, &mut Vec::new(), &params.tx_type);
+                add_amount(&mut amounts, &XELIS_ASSET, response.inner.data.get_fee());
+            },
+            "send_message" => {
+                let params: SendMessageParams = parse_policy_params(request)?;
+                let response: TransactionResponse = serde_json::from_value(response.clone()).context("Invalid transaction response")?;
+                add_amount(&mut amounts, &params.asset.unwrap_or(XELIS_ASSET), params.amount);
+                add_amount(&mut amounts, &XELIS_ASSET, response.inner.data.get_fee());
+            },
+            "batch_payout" => {
+                let params: BatchPayoutParams = parse_policy_params(request)?;
+                let report: Vec<PayoutEntry> = serde_json::from_value(response.clone()).context("Invalid payout report")?;
+                let mut transactions = HashSet::new();
+                for (entry, result) in params.entries.iter().zip(report.iter()) {
+                    if let (true, PayoutStatus::Submitted(hash)) = (entry.status.can_be_sent(), &result.status) {
+                        add_amount(&mut amounts, &entry.asset, entry.amount);
+                        transactions.insert(hash);
+                    }
+                }
+
+                // Fee can only be known when it is a fixed value
+                if let Some(FeeBuilder::Value(value)) = params.fee {
+                    add_amount(&mut amounts, &XELIS_ASSET, value.saturating_mul(transactions.len() as u64));
+                }
+            },
+            _ => return Ok(())
+        };
+
+        if amounts.is_empty() {
+            return Ok(())
+        }
+
+        let mut storage = self.storage.write().await;
+        if !storage.has_xswd_application(app_state.get_id())? {
+            return Ok(())
+        }
+
+        let mut application = storage.get_xswd_application(app_state.get_id())?;
+        application.add_spendings(amounts, get_current_time_in_seconds());
+        storage.set_xswd_application(&application)
+    }
+}
+
+// Add an amount to the total spent for an asset
+#[cfg(feature = "api_server")]
+fn add_amount(amounts: &mut HashMap<Hash, u64>, asset: &Hash, amount: u64) {
+    let total = amounts.entry(asset.clone()).or_insert(0u64);
+    *total = total.saturating_add(amount);
+}
+
+// Add the amounts and destinations of a transaction to build
+#[cfg(feature = "api_server")]
+fn add_transaction_amounts(amounts: &mut HashMap<Hash, u64>, destinations: &mut Vec<PublicKey>, tx_type: &TransactionTypeBuilder) {
+    match tx_type {
+        TransactionTypeBuilder::Transfers(transfers) => for transfer in transfers {
+            destinations.push(transfer.destination.get_public_key().clone());
+            add_amount(amounts, &transfer.asset, transfer.amount);
+        },
+        TransactionTypeBuilder::Burn(payload) => add_amount(amounts, &payload.asset, payload.amount)
+    };
+}
+
+// Only the tree is needed to verify the custom data requests
+#[cfg(feature = "api_server")]
+#[derive(Deserialize)]
+struct TreeParams {
+    tree: String
+}
+
+// Parse the params of a request verified against the policy of an application
+#[cfg(feature = "api_server")]
+fn parse_policy_params<T: DeserializeOwned>(request: &RpcRequest) -> Result<T, Error> {
+    serde_json::from_value(request.params.clone().unwrap_or(Value::Null)).context("Invalid params for request")
 }
 
 #[cfg(feature = "api_server")]